@@ -2,8 +2,18 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use rayon::prelude::{ParallelBridge, ParallelIterator};
 use rust_stack_example::model::{FinancialDataResponse, StatisticsResponse};
 
+/// Client sending the key of `BENCH_API_KEY`, if set, for servers with authentication enabled.
+fn bench_client() -> reqwest::blocking::Client {
+    let mut headers = reqwest::header::HeaderMap::new();
+    if let Ok(key) = std::env::var("BENCH_API_KEY") {
+        headers.insert("X-Api-Key", key.parse().expect("BENCH_API_KEY must be a valid header value."));
+    }
+    reqwest::blocking::Client::builder().default_headers(headers).build().unwrap()
+}
+
 fn make_request(client: &reqwest::blocking::Client, endpoint: &str, symbol: &str, start_date: &time::Date, end_date: &time::Date) {
     let resp = client.get(format!("http://127.0.0.1:8080/api/{endpoint}?symbol={symbol}&start_date={start_date}&end_date={end_date}")).send().unwrap();
+    assert!(resp.status().is_success(), "`{endpoint}` answered `{}`.", resp.status());
     match endpoint {
         "financial_data" => { resp.json::<FinancialDataResponse>().unwrap(); },
         "statistics" => { resp.json::<StatisticsResponse>().unwrap(); },
@@ -11,16 +21,15 @@ fn make_request(client: &reqwest::blocking::Client, endpoint: &str, symbol: &str
     };
 }
 
+type RequestZipper<'a> = std::iter::Zip<
+    std::iter::Cycle<std::slice::Iter<'a, &'a str>>,
+    std::iter::Zip<std::iter::Cycle<std::slice::Iter<'a, time::Date>>, std::iter::Cycle<std::slice::Iter<'a, time::Date>>>
+>;
+type BenchInput<'a> = (&'a str, reqwest::blocking::Client, RequestZipper<'a>);
+
 fn executor(
     (endpoint, client,zipper):
-    (
-        &str,
-        reqwest::blocking::Client,
-        std::iter::Zip<
-            std::iter::Cycle<std::slice::Iter<&str>>,
-            std::iter::Zip<std::iter::Cycle<std::slice::Iter<time::Date>>, std::iter::Cycle<std::slice::Iter<time::Date>>>
-        >
-    )
+    BenchInput
 ) {
     zipper
         .take(5_000)
@@ -29,14 +38,7 @@ fn executor(
 
 fn executor_parallel(
     (endpoint, client,zipper):
-    (
-        &str,
-        reqwest::blocking::Client,
-        std::iter::Zip<
-            std::iter::Cycle<std::slice::Iter<&str>>,
-            std::iter::Zip<std::iter::Cycle<std::slice::Iter<time::Date>>, std::iter::Cycle<std::slice::Iter<time::Date>>>
-        >
-    )
+    BenchInput
 ) {
     zipper
         .take(5_000)
@@ -52,7 +54,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function(
         "sequencial requests to `financial data`",
         |b| b.iter_batched(
-            || ("financial_data", reqwest::blocking::Client::new(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
+            || ("financial_data", bench_client(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
             executor,
             criterion::BatchSize::SmallInput
         )
@@ -61,7 +63,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function(
         "sequencial requests to `statistics`",
         |b| b.iter_batched(
-            || ("statistics", reqwest::blocking::Client::new(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
+            || ("statistics", bench_client(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
             executor,
             criterion::BatchSize::SmallInput
         )
@@ -70,7 +72,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function(
         "parallel requests to `financial data`",
         |b| b.iter_batched(
-            || ("financial_data", reqwest::blocking::Client::new(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
+            || ("financial_data", bench_client(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
             executor_parallel,
             criterion::BatchSize::SmallInput
         )
@@ -79,7 +81,7 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function(
         "parallel requests to `statistics`",
         |b| b.iter_batched(
-            || ("statistics", reqwest::blocking::Client::new(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
+            || ("statistics", bench_client(), std::iter::zip(symbols.iter().cycle(), std::iter::zip(starts.iter().cycle(), ends.iter().cycle()))),
             executor_parallel,
             criterion::BatchSize::SmallInput
         )
@@ -0,0 +1,133 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_stack_example::{
+    model::StatisticsReport, routes::AGGREGATED_STATISTICS_QUERY, tasks::refresh_aggregates,
+};
+use time::macros::date;
+
+/// Query of the `statistics` endpoint before aggregates were kept, scanning every entry of the date range.
+const RAW_STATISTICS_QUERY: &str = r#"
+    SELECT *
+    FROM (
+        SELECT
+            $1 as symbol,
+            $2 as start_date,
+            $3 as end_date,
+            AVG(open_price) as average_daily_open_price,
+            AVG(close_price) as average_daily_close_price,
+            CAST(AVG(volume) as FLOAT8) as average_daily_volume
+        FROM financial_data
+        WHERE symbol = COALESCE($1, symbol) AND date BETWEEN COALESCE($2, date) AND COALESCE($3, date)
+    ) as statistics
+    WHERE average_daily_volume IS NOT NULL;
+    "#;
+
+/// Synthetic equity holding an entry for every day since 1990, removed once the benchmark is over.
+const SYMBOL: &str = "BENCH";
+
+const SEED_QUERY: &str = r#"
+    WITH seeded AS (
+        INSERT INTO financial_data (symbol, date, open_price, close_price, volume)
+        SELECT $1, day, 100 + sin(n) * 10, 100 + cos(n) * 10, 1000 + n % 500
+        FROM generate_series(DATE '1990-01-01', DATE '2022-12-31', INTERVAL '1 day') WITH ORDINALITY AS days (day, n)
+        ON CONFLICT DO NOTHING
+        RETURNING symbol, date
+    )
+    INSERT INTO financial_data_stale_months (symbol, month)
+    SELECT DISTINCT symbol, CAST(date_trunc('month', date) AS DATE) FROM seeded
+    ON CONFLICT DO NOTHING;
+    "#;
+
+const CLEANUP_QUERIES: [&str; 3] = [
+    "DELETE FROM financial_data WHERE symbol = $1;",
+    "DELETE FROM financial_data_monthly WHERE symbol = $1;",
+    "DELETE FROM financial_data_stale_months WHERE symbol = $1;",
+];
+
+async fn statistics(
+    pool: &sqlx::PgPool,
+    query: &str,
+    start_date: time::Date,
+    end_date: time::Date,
+) -> Option<StatisticsReport> {
+    sqlx::query_as::<_, StatisticsReport>(query)
+        .bind(SYMBOL)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+}
+
+fn criterion_benchmark(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+    let database_url = std::env::var("DATABASE_URL")
+        .unwrap_or_else(|_| "postgres://postgres@localhost:5432/postgres".into());
+    let pool = runtime
+        .block_on(sqlx::PgPool::connect(&database_url))
+        .unwrap();
+    runtime
+        .block_on(
+            sqlx::query("SELECT create_financial_data_partitions(1990, 2022);").execute(&pool),
+        )
+        .unwrap();
+    runtime
+        .block_on(sqlx::query(SEED_QUERY).bind(SYMBOL).execute(&pool))
+        .unwrap();
+    runtime.block_on(refresh_aggregates(&pool)).unwrap();
+    runtime
+        .block_on(sqlx::query("ANALYZE financial_data, financial_data_monthly;").execute(&pool))
+        .unwrap();
+
+    let ranges = [
+        ("1 month", date!(2022 - 03 - 10), date!(2022 - 04 - 09)),
+        ("1 year", date!(2021 - 06 - 15), date!(2022 - 06 - 14)),
+        ("10 years", date!(2012 - 06 - 15), date!(2022 - 06 - 14)),
+        ("30 years", date!(1992 - 06 - 15), date!(2022 - 06 - 14)),
+    ];
+    for (_, start, end) in ranges {
+        let raw = runtime
+            .block_on(statistics(&pool, RAW_STATISTICS_QUERY, start, end))
+            .unwrap();
+        let aggregated = runtime
+            .block_on(statistics(&pool, AGGREGATED_STATISTICS_QUERY, start, end))
+            .unwrap();
+        assert!((raw.average_daily_open_price - aggregated.average_daily_open_price).abs() < 1e-6);
+        assert!(
+            (raw.average_daily_close_price - aggregated.average_daily_close_price).abs() < 1e-6
+        );
+        assert!((raw.average_daily_volume - aggregated.average_daily_volume).abs() < 1e-6);
+    }
+
+    let mut group = c.benchmark_group("statistics");
+    for (name, start, end) in ranges {
+        group.bench_with_input(
+            BenchmarkId::new("raw", name),
+            &(start, end),
+            |b, &(start, end)| {
+                b.iter(|| runtime.block_on(statistics(&pool, RAW_STATISTICS_QUERY, start, end)))
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("aggregated", name),
+            &(start, end),
+            |b, &(start, end)| {
+                b.iter(|| {
+                    runtime.block_on(statistics(&pool, AGGREGATED_STATISTICS_QUERY, start, end))
+                })
+            },
+        );
+    }
+    group.finish();
+
+    for query in CLEANUP_QUERIES {
+        runtime
+            .block_on(sqlx::query(query).bind(SYMBOL).execute(&pool))
+            .unwrap();
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
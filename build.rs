@@ -0,0 +1,8 @@
+/// Compiles the gRPC service definitions with a vendored `protoc`, so that none needs to be installed.
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/financial_data.proto"], &["proto"])?;
+    Ok(())
+}
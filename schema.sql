@@ -1,4 +1,14 @@
 -- Add migration script here
+-- `financial_data` is range-partitioned by year. A table created before partitioning is migrated into it.
+DO $$
+BEGIN
+    IF EXISTS (SELECT 1 FROM pg_class WHERE oid = to_regclass('financial_data') AND relkind = 'r') THEN
+        ALTER TABLE financial_data RENAME TO financial_data_unpartitioned;
+        ALTER TABLE financial_data_unpartitioned
+            RENAME CONSTRAINT financial_data_symbol_date_key TO financial_data_unpartitioned_symbol_date_key;
+    END IF;
+END $$;
+
 CREATE TABLE IF NOT EXISTS financial_data (
     symbol CHAR(8),
     date DATE,
@@ -6,4 +16,231 @@ CREATE TABLE IF NOT EXISTS financial_data (
     close_price FLOAT8,
     volume INT,
     UNIQUE(symbol, date)
-);
\ No newline at end of file
+) PARTITION BY RANGE (date);
+
+-- Serves the queries filtering on dates only, which cannot use the `(symbol, date)` index.
+CREATE INDEX IF NOT EXISTS financial_data_date_symbol ON financial_data (date, symbol);
+
+-- Creates the missing partitions of `financial_data` from `first_year` to `last_year`, each named after its year.
+CREATE OR REPLACE FUNCTION create_financial_data_partitions(first_year INT, last_year INT) RETURNS VOID AS $$
+DECLARE
+    partition_year INT;
+BEGIN
+    FOR partition_year IN first_year..last_year LOOP
+        IF to_regclass(format('financial_data_%s', partition_year)) IS NULL THEN
+            BEGIN
+                EXECUTE format(
+                    'CREATE TABLE financial_data_%s PARTITION OF financial_data FOR VALUES FROM (%L) TO (%L)',
+                    partition_year,
+                    make_date(partition_year, 1, 1),
+                    make_date(partition_year + 1, 1, 1)
+                );
+            -- Another session created the partition first.
+            EXCEPTION WHEN duplicate_table THEN
+                NULL;
+            END;
+        END IF;
+    END LOOP;
+END;
+$$ LANGUAGE plpgsql STRICT;
+
+SELECT create_financial_data_partitions(
+    CAST(EXTRACT(YEAR FROM CURRENT_DATE) AS INT),
+    CAST(EXTRACT(YEAR FROM CURRENT_DATE) AS INT) + 1
+);
+
+DO $$
+BEGIN
+    IF to_regclass('financial_data_unpartitioned') IS NOT NULL THEN
+        PERFORM create_financial_data_partitions(
+            CAST(EXTRACT(YEAR FROM MIN(date)) AS INT),
+            CAST(EXTRACT(YEAR FROM MAX(date)) AS INT)
+        )
+        FROM financial_data_unpartitioned;
+        INSERT INTO financial_data (symbol, date, open_price, close_price, volume)
+        SELECT symbol, date, open_price, close_price, volume
+        FROM financial_data_unpartitioned;
+        DROP TABLE financial_data_unpartitioned;
+    END IF;
+END $$;
+CREATE TABLE IF NOT EXISTS alert_rules (
+    id SERIAL PRIMARY KEY,
+    symbol CHAR(8) NOT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('price_cross', 'daily_move', 'volume_spike')),
+    threshold FLOAT8 NOT NULL,
+    webhook_url TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS alert_events (
+    id SERIAL PRIMARY KEY,
+    rule_id INT NOT NULL REFERENCES alert_rules (id) ON DELETE CASCADE,
+    symbol CHAR(8) NOT NULL,
+    date DATE NOT NULL,
+    value FLOAT8 NOT NULL,
+    triggered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(rule_id, date)
+);
+
+CREATE TABLE IF NOT EXISTS alert_deliveries (
+    id SERIAL PRIMARY KEY,
+    event_id INT NOT NULL REFERENCES alert_events (id) ON DELETE CASCADE,
+    attempt INT NOT NULL,
+    status_code INT,
+    error TEXT,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS ingestion_runs (
+    id SERIAL PRIMARY KEY,
+    source TEXT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    rows_changed INT NOT NULL DEFAULT 0,
+    error TEXT
+);
+
+CREATE TABLE IF NOT EXISTS api_keys (
+    id SERIAL PRIMARY KEY,
+    name TEXT NOT NULL,
+    prefix TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS rate_limits (
+    client TEXT PRIMARY KEY,
+    tokens FLOAT8 NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+    day DATE NOT NULL,
+    used_today BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS financial_data_audit (
+    id BIGSERIAL PRIMARY KEY,
+    action TEXT NOT NULL CHECK (action IN ('insert', 'update', 'delete')),
+    symbol CHAR(8) NOT NULL,
+    date DATE NOT NULL,
+    old_open_price FLOAT8,
+    old_close_price FLOAT8,
+    old_volume INT,
+    new_open_price FLOAT8,
+    new_close_price FLOAT8,
+    new_volume INT,
+    author TEXT NOT NULL,
+    api_key_id INT REFERENCES api_keys (id) ON DELETE SET NULL,
+    changed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS financial_data_audit_symbol_date ON financial_data_audit (symbol, date);
+
+CREATE TABLE IF NOT EXISTS financial_data_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    symbol CHAR(8) NOT NULL,
+    date DATE NOT NULL,
+    open_price FLOAT8 NOT NULL,
+    close_price FLOAT8 NOT NULL,
+    volume INT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    superseded_at TIMESTAMPTZ,
+    CHECK (superseded_at IS NULL OR superseded_at >= recorded_at)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS financial_data_revisions_current ON financial_data_revisions (symbol, date) WHERE superseded_at IS NULL;
+CREATE INDEX IF NOT EXISTS financial_data_revisions_validity ON financial_data_revisions (symbol, date, recorded_at);
+
+-- Entries saved before revisions were kept start their history when the schema is applied.
+INSERT INTO financial_data_revisions (symbol, date, open_price, close_price, volume)
+SELECT symbol, date, open_price, close_price, volume
+FROM financial_data
+WHERE NOT EXISTS (
+    SELECT 1
+    FROM financial_data_revisions
+    WHERE financial_data_revisions.symbol = financial_data.symbol
+        AND financial_data_revisions.date = financial_data.date
+        AND financial_data_revisions.superseded_at IS NULL
+);
+
+CREATE TABLE IF NOT EXISTS financial_data_monthly (
+    symbol CHAR(8) NOT NULL,
+    month DATE NOT NULL,
+    entries BIGINT NOT NULL,
+    sum_open_price FLOAT8 NOT NULL,
+    sum_close_price FLOAT8 NOT NULL,
+    sum_volume BIGINT NOT NULL,
+    sum_squares_open_price FLOAT8 NOT NULL,
+    sum_squares_close_price FLOAT8 NOT NULL,
+    sum_squares_volume FLOAT8 NOT NULL,
+    min_open_price FLOAT8 NOT NULL,
+    max_open_price FLOAT8 NOT NULL,
+    min_close_price FLOAT8 NOT NULL,
+    max_close_price FLOAT8 NOT NULL,
+    min_volume INT NOT NULL,
+    max_volume INT NOT NULL,
+    PRIMARY KEY (symbol, month)
+);
+
+-- Months whose entries changed since `financial_data_monthly` was last refreshed.
+CREATE TABLE IF NOT EXISTS financial_data_stale_months (
+    symbol CHAR(8) NOT NULL,
+    month DATE NOT NULL,
+    PRIMARY KEY (symbol, month)
+);
+
+-- Months saved before aggregates were kept are aggregated on the next refresh.
+INSERT INTO financial_data_stale_months (symbol, month)
+SELECT DISTINCT symbol, CAST(date_trunc('month', date) AS DATE)
+FROM financial_data
+WHERE NOT EXISTS (
+    SELECT 1
+    FROM financial_data_monthly
+    WHERE financial_data_monthly.symbol = financial_data.symbol
+        AND financial_data_monthly.month = CAST(date_trunc('month', financial_data.date) AS DATE)
+)
+ON CONFLICT (symbol, month) DO NOTHING;
+
+-- Start of the daily entries kept for each symbol pruned by the retention policies.
+-- The aggregates of the months before it are kept as they were when the months were pruned.
+CREATE TABLE IF NOT EXISTS financial_data_retention (
+    symbol CHAR(8) PRIMARY KEY,
+    pruned_before DATE NOT NULL,
+    pruned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE OR REPLACE VIEW financial_data_yearly AS
+SELECT
+    symbol,
+    CAST(date_trunc('year', month) AS DATE) AS year,
+    SUM(entries) AS entries,
+    SUM(sum_open_price) AS sum_open_price,
+    SUM(sum_close_price) AS sum_close_price,
+    SUM(sum_volume) AS sum_volume,
+    SUM(sum_squares_open_price) AS sum_squares_open_price,
+    SUM(sum_squares_close_price) AS sum_squares_close_price,
+    SUM(sum_squares_volume) AS sum_squares_volume,
+    MIN(min_open_price) AS min_open_price,
+    MAX(max_open_price) AS max_open_price,
+    MIN(min_close_price) AS min_close_price,
+    MAX(max_close_price) AS max_close_price,
+    MIN(min_volume) AS min_volume,
+    MAX(max_volume) AS max_volume
+FROM financial_data_monthly
+GROUP BY symbol, CAST(date_trunc('year', month) AS DATE);
+
+CREATE TABLE IF NOT EXISTS trading_calendar_overrides (
+    date DATE PRIMARY KEY,
+    is_trading_day BOOLEAN NOT NULL,
+    reason TEXT NOT NULL DEFAULT ''
+);
+
+-- Exceptional closures of the NYSE, which no holiday rule describes.
+INSERT INTO trading_calendar_overrides (date, is_trading_day, reason)
+VALUES
+    ('2012-10-29', FALSE, 'Hurricane Sandy'),
+    ('2012-10-30', FALSE, 'Hurricane Sandy'),
+    ('2018-12-05', FALSE, 'National Day of Mourning for George H. W. Bush'),
+    ('2025-01-09', FALSE, 'National Day of Mourning for Jimmy Carter')
+ON CONFLICT (date) DO NOTHING;
@@ -0,0 +1,2 @@
+mod require_scope;
+pub use require_scope::*;
@@ -0,0 +1,96 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, Request},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+
+use crate::{
+    error::AuthError,
+    model::{ApiKey, ApiVersion, Scope},
+    tasks,
+};
+
+/// Header holding the API key, as an alternative to `Authorization: Bearer <key>`.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Gets the API key sent with a request, from the `Authorization` or the `X-Api-Key` header.
+fn presented_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            headers
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// Middleware rejecting requests without a valid API key granted `scope`.
+///
+/// The `ApiKey` is added to the extensions of accepted requests.
+/// Needs the `PgPool` extension to be added by an outer layer.
+pub async fn require_scope<B>(
+    State(scope): State<Scope>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let version = ApiVersion::of(&request);
+    match authenticate(scope, request).await {
+        Ok(request) => next.run(request).await,
+        Err(err) => err.into_versioned_response(version),
+    }
+}
+
+/// Checks the API key of a request, adding it to the extensions of the request.
+async fn authenticate<B>(scope: Scope, mut request: Request<B>) -> Result<Request<B>, AuthError> {
+    let pool = request
+        .extensions()
+        .get::<sqlx::PgPool>()
+        .cloned()
+        .ok_or(AuthError::Unavailable)?;
+    let api_key = authorize_key(&pool, presented_key(request.headers()), scope).await?;
+    request.extensions_mut().insert(api_key);
+    Ok(request)
+}
+
+/// Checks that `key` exists, isn't revoked, and was granted `scope`.
+///
+/// Shared by every protocol, which each find the key in their own headers.
+pub async fn authorize_key(
+    pool: &sqlx::PgPool,
+    key: Option<&str>,
+    scope: Scope,
+) -> Result<ApiKey, AuthError> {
+    let key = key.ok_or(AuthError::MissingKey)?;
+    let api_key = tasks::authenticate_api_key(pool, key)
+        .await
+        .map_err(|err| {
+            log::error!("{:?}", err);
+            AuthError::Unavailable
+        })?
+        .ok_or(AuthError::InvalidKey)?;
+    if !api_key.has_scope(scope) {
+        log::warn!(
+            "API key `{}` was denied the `{}` scope.",
+            api_key.prefix,
+            scope
+        );
+        return Err(AuthError::MissingScope(scope));
+    }
+
+    log::trace!("API key `{}` was granted `{}`.", api_key.prefix, scope);
+    Ok(api_key)
+}
+
+/// Restricts every route of `router` to API keys granted `scope`, unless authentication is disabled.
+pub fn with_scope(router: Router, scope: Scope, enabled: bool) -> Router {
+    match enabled {
+        true => router.route_layer(middleware::from_fn_with_state(scope, require_scope)),
+        false => router,
+    }
+}
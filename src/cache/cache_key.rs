@@ -0,0 +1,25 @@
+/// Normalized query of a cached response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub endpoint: &'static str,
+    /// Global equity the response is about, `None` if it is about every equity.
+    pub symbol: Option<String>,
+    /// Remaining parameters of the query, with their defaults applied.
+    pub params: String,
+}
+
+impl CacheKey {
+    /// Symbols are stored and notified in uppercase, so the key holds them the same way.
+    pub fn new(endpoint: &'static str, symbol: Option<&str>, params: impl std::fmt::Debug) -> Self {
+        CacheKey {
+            endpoint,
+            symbol: symbol.map(|symbol| symbol.trim().to_uppercase()),
+            params: format!("{:?}", params),
+        }
+    }
+
+    /// Whether the response may change when the time series of `symbol` is updated.
+    pub fn depends_on(&self, symbol: &str) -> bool {
+        self.symbol.as_deref().is_none_or(|s| s == symbol)
+    }
+}
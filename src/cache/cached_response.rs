@@ -0,0 +1,59 @@
+use axum::{
+    body::{Bytes, Full},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sha2::{Digest, Sha256};
+
+/// JSON body of a response, with its entity tag.
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub body: Bytes,
+    pub etag: HeaderValue,
+}
+
+impl CachedResponse {
+    pub fn new(body: Vec<u8>) -> Self {
+        let digest = Sha256::digest(&body);
+        let etag = format!("\"{}\"", hex::encode(&digest[..16]));
+        CachedResponse {
+            body: body.into(),
+            etag: HeaderValue::from_str(&etag).expect("Hex digests are valid header values."),
+        }
+    }
+
+    /// Whether the `If-None-Match` header of a request matches the entity tag,
+    /// meaning the client already has this response.
+    fn is_fresh(&self, request_headers: &HeaderMap) -> bool {
+        let Some(etag) = self.etag.to_str().ok() else {
+            return false;
+        };
+        request_headers
+            .get_all(header::IF_NONE_MATCH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|tag| tag.trim())
+            .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    }
+
+    /// Answers with `304 Not Modified` if the client already has this response, or with the body otherwise.
+    pub fn respond(self, request_headers: &HeaderMap, cache_control: HeaderValue) -> Response {
+        let headers = [
+            (header::ETAG, self.etag.clone()),
+            (header::CACHE_CONTROL, cache_control),
+        ];
+        if self.is_fresh(request_headers) {
+            return (StatusCode::NOT_MODIFIED, headers).into_response();
+        }
+        (
+            headers,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )],
+            Full::new(self.body),
+        )
+            .into_response()
+    }
+}
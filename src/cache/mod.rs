@@ -0,0 +1,6 @@
+mod cache_key;
+pub use cache_key::*;
+mod cached_response;
+pub use cached_response::*;
+mod response_cache;
+pub use response_cache::*;
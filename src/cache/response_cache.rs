@@ -0,0 +1,171 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    http::{HeaderMap, HeaderValue},
+    response::Response,
+};
+use error_stack::{IntoReport, Report, ResultExt};
+use serde::Serialize;
+
+use crate::{config::CacheConfig, error::RouteError, metrics};
+
+use super::{CacheKey, CachedResponse};
+
+#[derive(Debug)]
+struct CacheEntry {
+    response: CachedResponse,
+    stored_at: Instant,
+    /// Value of the `CacheState::clock` when the entry was last read, for the LRU eviction.
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct CacheState {
+    entries: HashMap<CacheKey, CacheEntry>,
+    clock: u64,
+    /// Incremented on every invalidation, so that responses computed before it are not stored.
+    generation: u64,
+}
+
+/// In-process TTL and LRU cache of JSON responses, invalidated per symbol when the time series is updated.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    ttl: Duration,
+    /// `0` when the cache is disabled.
+    capacity: usize,
+    cache_control: HeaderValue,
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl ResponseCache {
+    /// Creates a cache. Responses are marked as `private` when `shared` is false,
+    /// e.g. when they need an API key, so that proxies don't serve them to other clients.
+    pub fn new(config: &CacheConfig, shared: bool) -> Self {
+        let cache_control = match (config.enabled, shared) {
+            (false, _) => "no-cache".to_string(),
+            (true, true) => format!("public, max-age={}", config.ttl_secs),
+            (true, false) => format!("private, max-age={}", config.ttl_secs),
+        };
+        ResponseCache {
+            ttl: Duration::from_secs(config.ttl_secs),
+            capacity: if config.enabled { config.capacity } else { 0 },
+            cache_control: HeaderValue::from_str(&cache_control)
+                .expect("Cache-Control directives are valid header values."),
+            state: Default::default(),
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let mut state = self.state();
+        state.clock += 1;
+        let clock = state.clock;
+        match state.entries.get_mut(key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => {
+                entry.last_used = clock;
+                Some(entry.response.clone())
+            }
+            Some(_) => {
+                state.entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: CacheKey, response: CachedResponse, generation: u64) {
+        let mut state = self.state();
+        if self.capacity == 0 || state.generation != generation {
+            return;
+        }
+        if state.entries.len() >= self.capacity && !state.entries.contains_key(&key) {
+            let ttl = self.ttl;
+            state
+                .entries
+                .retain(|_, entry| entry.stored_at.elapsed() < ttl);
+            if state.entries.len() >= self.capacity {
+                let least_recent = state
+                    .entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.last_used)
+                    .map(|(key, _)| key.clone());
+                if let Some(least_recent) = least_recent {
+                    state.entries.remove(&least_recent);
+                }
+            }
+        }
+        state.clock += 1;
+        let last_used = state.clock;
+        state.entries.insert(
+            key,
+            CacheEntry {
+                response,
+                stored_at: Instant::now(),
+                last_used,
+            },
+        );
+    }
+
+    /// Removes the responses that may include the time series of `symbol`.
+    pub fn invalidate_symbol(&self, symbol: &str) {
+        let mut state = self.state();
+        state.generation += 1;
+        state.entries.retain(|key, _| !key.depends_on(symbol));
+    }
+
+    /// Removes every response.
+    pub fn clear(&self) {
+        let mut state = self.state();
+        state.generation += 1;
+        state.entries.clear();
+    }
+
+    /// Answers a request with the cached response for `key`, or with the response computed by `compute`,
+    /// which is then cached.
+    ///
+    /// Responses carry an `ETag` and `Cache-Control`, and are answered with `304 Not Modified`
+    /// when the `If-None-Match` header of the request matches.
+    pub async fn respond<T, E, F>(
+        &self,
+        key: CacheKey,
+        request_headers: &HeaderMap,
+        compute: F,
+    ) -> Result<Response, E>
+    where
+        T: Serialize,
+        E: From<Report<RouteError>>,
+        F: Future<Output = Result<T, E>>,
+    {
+        let endpoint = key.endpoint;
+        if let Some(cached) = self.get(&key) {
+            log::trace!("Responding from cache to `{}`.", endpoint);
+            metrics::CACHE_LOOKUPS
+                .with_label_values(&[endpoint, "hit"])
+                .inc();
+            return Ok(cached.respond(request_headers, self.cache_control.clone()));
+        }
+        metrics::CACHE_LOOKUPS
+            .with_label_values(&[endpoint, "miss"])
+            .inc();
+
+        let generation = self.state().generation;
+        let value = compute.await?;
+        let body = serde_json::to_vec(&value)
+            .into_report()
+            .change_context(RouteError(endpoint))
+            .attach("Failed to serialize response.")?;
+        let response = CachedResponse::new(body);
+        self.insert(key, response.clone(), generation);
+        Ok(response.respond(request_headers, self.cache_control.clone()))
+    }
+}
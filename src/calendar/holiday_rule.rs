@@ -0,0 +1,222 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use time::{Date, Duration, Month, Weekday};
+
+/// Holiday on which the NYSE is closed, recurring every year.
+///
+/// Holidays falling on a Sunday are observed on the next Monday, and holidays falling on a Saturday on the previous
+/// Friday, except for New Year's Day, which is then not observed at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum HolidayRule {
+    /// January 1st.
+    NewYear,
+    /// Third Monday of January, since 1998.
+    MartinLutherKingDay,
+    /// Third Monday of February.
+    WashingtonsBirthday,
+    /// Friday before Easter Sunday.
+    GoodFriday,
+    /// Last Monday of May.
+    MemorialDay,
+    /// June 19th, since 2022.
+    Juneteenth,
+    /// July 4th.
+    IndependenceDay,
+    /// First Monday of September.
+    LaborDay,
+    /// Fourth Thursday of November.
+    Thanksgiving,
+    /// December 25th.
+    Christmas,
+}
+
+impl HolidayRule {
+    /// Every rule followed by the NYSE.
+    pub const NYSE: [HolidayRule; 10] = [
+        HolidayRule::NewYear,
+        HolidayRule::MartinLutherKingDay,
+        HolidayRule::WashingtonsBirthday,
+        HolidayRule::GoodFriday,
+        HolidayRule::MemorialDay,
+        HolidayRule::Juneteenth,
+        HolidayRule::IndependenceDay,
+        HolidayRule::LaborDay,
+        HolidayRule::Thanksgiving,
+        HolidayRule::Christmas,
+    ];
+
+    /// Date on which the holiday is observed in `year`, `None` if it isn't observed that year.
+    pub fn observed(self, year: i32) -> Option<Date> {
+        match self {
+            HolidayRule::NewYear => {
+                let date = fixed_date(year, Month::January, 1)?;
+                match date.weekday() {
+                    Weekday::Saturday => None,
+                    Weekday::Sunday => date.next_day(),
+                    _ => Some(date),
+                }
+            }
+            HolidayRule::MartinLutherKingDay if year >= 1998 => {
+                nth_weekday(year, Month::January, Weekday::Monday, 3)
+            }
+            HolidayRule::MartinLutherKingDay => None,
+            HolidayRule::WashingtonsBirthday => {
+                nth_weekday(year, Month::February, Weekday::Monday, 3)
+            }
+            HolidayRule::GoodFriday => easter_sunday(year)?.checked_sub(Duration::days(2)),
+            HolidayRule::MemorialDay => last_weekday(year, Month::May, Weekday::Monday),
+            HolidayRule::Juneteenth if year >= 2022 => {
+                fixed_date(year, Month::June, 19).map(observed_weekday)
+            }
+            HolidayRule::Juneteenth => None,
+            HolidayRule::IndependenceDay => fixed_date(year, Month::July, 4).map(observed_weekday),
+            HolidayRule::LaborDay => nth_weekday(year, Month::September, Weekday::Monday, 1),
+            HolidayRule::Thanksgiving => nth_weekday(year, Month::November, Weekday::Thursday, 4),
+            HolidayRule::Christmas => fixed_date(year, Month::December, 25).map(observed_weekday),
+        }
+    }
+}
+
+impl FromStr for HolidayRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        <HolidayRule as ValueEnum>::from_str(value, true)
+    }
+}
+
+fn fixed_date(year: i32, month: Month, day: u8) -> Option<Date> {
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+/// Moves a holiday falling on a weekend to the closest weekday.
+fn observed_weekday(date: Date) -> Date {
+    match date.weekday() {
+        Weekday::Saturday => date.previous_day().unwrap_or(date),
+        Weekday::Sunday => date.next_day().unwrap_or(date),
+        _ => date,
+    }
+}
+
+/// `n`th `weekday` of the month, starting at 1.
+fn nth_weekday(year: i32, month: Month, weekday: Weekday, n: u8) -> Option<Date> {
+    let first = fixed_date(year, month, 1)?;
+    let offset =
+        (7 + weekday.number_days_from_monday() - first.weekday().number_days_from_monday()) % 7;
+    first.checked_add(Duration::days((offset + 7 * (n - 1)) as i64))
+}
+
+/// Last `weekday` of the month.
+fn last_weekday(year: i32, month: Month, weekday: Weekday) -> Option<Date> {
+    let last = fixed_date(year, month, time::util::days_in_year_month(year, month))?;
+    let offset =
+        (7 + last.weekday().number_days_from_monday() - weekday.number_days_from_monday()) % 7;
+    last.checked_sub(Duration::days(offset as i64))
+}
+
+/// Easter Sunday of the Gregorian calendar, from the anonymous algorithm.
+fn easter_sunday(year: i32) -> Option<Date> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = Month::try_from(((h + l - 7 * m + 114) / 31) as u8).ok()?;
+    let day = ((h + l - 7 * m + 114) % 31 + 1) as u8;
+    fixed_date(year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    fn observed(year: i32) -> Vec<Date> {
+        HolidayRule::NYSE
+            .iter()
+            .filter_map(|rule| rule.observed(year))
+            .collect()
+    }
+
+    #[test]
+    fn nyse_closures_of_2024() {
+        assert_eq!(
+            observed(2024),
+            [
+                date!(2024 - 01 - 01),
+                date!(2024 - 01 - 15),
+                date!(2024 - 02 - 19),
+                date!(2024 - 03 - 29),
+                date!(2024 - 05 - 27),
+                date!(2024 - 06 - 19),
+                date!(2024 - 07 - 04),
+                date!(2024 - 09 - 02),
+                date!(2024 - 11 - 28),
+                date!(2024 - 12 - 25),
+            ]
+        );
+    }
+
+    #[test]
+    fn weekend_holidays_are_observed_on_weekdays() {
+        // New Year's Day on a Saturday is not observed on the previous Friday, which ends the year.
+        assert_eq!(HolidayRule::NewYear.observed(2022), None);
+        assert_eq!(
+            HolidayRule::NewYear.observed(2023),
+            Some(date!(2023 - 01 - 02))
+        );
+        assert_eq!(
+            HolidayRule::Juneteenth.observed(2022),
+            Some(date!(2022 - 06 - 20))
+        );
+        assert_eq!(
+            HolidayRule::IndependenceDay.observed(2021),
+            Some(date!(2021 - 07 - 05))
+        );
+        assert_eq!(
+            HolidayRule::Christmas.observed(2021),
+            Some(date!(2021 - 12 - 24))
+        );
+        assert_eq!(
+            HolidayRule::Christmas.observed(2022),
+            Some(date!(2022 - 12 - 26))
+        );
+    }
+
+    #[test]
+    fn holidays_are_only_observed_once_introduced() {
+        assert_eq!(HolidayRule::Juneteenth.observed(2021), None);
+        assert_eq!(HolidayRule::MartinLutherKingDay.observed(1997), None);
+        assert_eq!(
+            HolidayRule::MartinLutherKingDay.observed(1998),
+            Some(date!(1998 - 01 - 19))
+        );
+    }
+
+    #[test]
+    fn good_friday_follows_easter() {
+        assert_eq!(
+            HolidayRule::GoodFriday.observed(2019),
+            Some(date!(2019 - 04 - 19))
+        );
+        assert_eq!(
+            HolidayRule::GoodFriday.observed(2022),
+            Some(date!(2022 - 04 - 15))
+        );
+        assert_eq!(
+            HolidayRule::GoodFriday.observed(2038),
+            Some(date!(2038 - 04 - 23))
+        );
+    }
+}
@@ -0,0 +1,7 @@
+//! Trading calendar of the NYSE, telling which dates the time series should have an entry for.
+
+mod holiday_rule;
+pub use holiday_rule::*;
+
+mod trading_calendar;
+pub use trading_calendar::*;
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+
+use error_stack::{IntoReport, Result, ResultExt};
+use time::{Date, Weekday};
+
+use crate::{config::CalendarConfig, error::CalendarError};
+
+use super::HolidayRule;
+
+/// Days on which the exchange is open: weekdays, except the days its holidays are observed.
+///
+/// The dates of the `trading_calendar_overrides` table take precedence over the rules,
+/// for the exceptional closures and openings of the exchange.
+#[derive(Debug, Clone)]
+pub struct TradingCalendar {
+    holidays: Vec<HolidayRule>,
+    overrides: BTreeMap<Date, bool>,
+}
+
+impl TradingCalendar {
+    pub fn new(holidays: Vec<HolidayRule>, overrides: BTreeMap<Date, bool>) -> Self {
+        TradingCalendar {
+            holidays,
+            overrides,
+        }
+    }
+
+    /// Builds the calendar of the configured holidays, along with the overrides saved on the database.
+    pub async fn load<'c>(
+        db: impl sqlx::PgExecutor<'c>,
+        config: &CalendarConfig,
+    ) -> Result<TradingCalendar, CalendarError> {
+        let query = r#"
+        SELECT date, is_trading_day
+        FROM trading_calendar_overrides;"#;
+
+        log::trace!("Querying trading calendar overrides from database.");
+        let overrides = sqlx::query_as::<_, (Date, bool)>(query)
+            .fetch_all(db)
+            .await
+            .into_report()
+            .change_context(CalendarError)
+            .attach("Failed to query trading calendar overrides on Postgres database.")?;
+        Ok(TradingCalendar::new(
+            config.holidays.clone(),
+            overrides.into_iter().collect(),
+        ))
+    }
+
+    /// Checks if the exchange is open on `date`.
+    pub fn is_trading_day(&self, date: Date) -> bool {
+        if let Some(open) = self.overrides.get(&date) {
+            return *open;
+        }
+        if matches!(date.weekday(), Weekday::Saturday | Weekday::Sunday) {
+            return false;
+        }
+        !self
+            .holidays
+            .iter()
+            .any(|holiday| holiday.observed(date.year()) == Some(date))
+    }
+
+    /// Days the exchange is open between `start` and `end`, both included, in chronological order.
+    pub fn trading_days(&self, start: Date, end: Date) -> impl Iterator<Item = Date> + '_ {
+        std::iter::successors(Some(start), |date| date.next_day())
+            .take_while(move |date| *date <= end)
+            .filter(|date| self.is_trading_day(*date))
+    }
+
+    /// Days the exchange is open between `start` and `end`, both included,
+    /// on which the time series of `symbol` has no entry.
+    ///
+    /// Days pruned by the retention policies are not missing, so the days before `pruned_before` are skipped.
+    pub async fn missing_days<'c>(
+        &self,
+        db: impl sqlx::PgExecutor<'c>,
+        symbol: &str,
+        start: Date,
+        end: Date,
+    ) -> Result<Vec<Date>, CalendarError> {
+        let query = r#"
+        SELECT
+            (
+                SELECT pruned_before
+                FROM financial_data_retention
+                WHERE symbol = CAST($1 AS BPCHAR)
+            ),
+            ARRAY(
+                SELECT date
+                FROM financial_data
+                WHERE symbol = CAST($1 AS BPCHAR) AND date BETWEEN $2 AND $3
+            );"#;
+
+        log::trace!("Querying saved dates of `{}` from database.", symbol);
+        let (pruned_before, saved) = sqlx::query_as::<_, (Option<Date>, Vec<Date>)>(query)
+            .bind(symbol)
+            .bind(start)
+            .bind(end)
+            .fetch_one(db)
+            .await
+            .into_report()
+            .change_context(CalendarError)
+            .attach("Failed to query saved dates on Postgres database.")?;
+        let saved = saved.into_iter().collect::<std::collections::BTreeSet<_>>();
+        let start = pruned_before.map_or(start, |pruned_before| start.max(pruned_before));
+        Ok(self
+            .trading_days(start, end)
+            .filter(|date| !saved.contains(date))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    fn nyse() -> TradingCalendar {
+        TradingCalendar::new(HolidayRule::NYSE.to_vec(), BTreeMap::new())
+    }
+
+    #[test]
+    fn known_closures_are_not_trading_days() {
+        let calendar = nyse();
+        assert!(!calendar.is_trading_day(date!(2024 - 03 - 29)));
+        assert!(!calendar.is_trading_day(date!(2022 - 06 - 20)));
+        assert!(!calendar.is_trading_day(date!(2022 - 12 - 26)));
+        assert!(!calendar.is_trading_day(date!(2024 - 03 - 30)));
+        // New Year's Day 2022 fell on a Saturday, so the exchange was open on the Friday before.
+        assert!(calendar.is_trading_day(date!(2021 - 12 - 31)));
+        assert!(calendar.is_trading_day(date!(2022 - 01 - 03)));
+    }
+
+    #[test]
+    fn trading_days_of_a_year() {
+        let calendar = nyse();
+        let count = |year| {
+            let start = Date::from_ordinal_date(year, 1).unwrap();
+            let end = Date::from_ordinal_date(year, time::util::days_in_year(year)).unwrap();
+            calendar.trading_days(start, end).count()
+        };
+        assert_eq!(count(2022), 251);
+        assert_eq!(count(2023), 250);
+        assert_eq!(count(2024), 252);
+    }
+
+    #[test]
+    fn overrides_take_precedence() {
+        // Closed for the national day of mourning of George H. W. Bush.
+        let overrides = BTreeMap::from([
+            (date!(2018 - 12 - 05), false),
+            (date!(2024 - 03 - 29), true),
+        ]);
+        let calendar = TradingCalendar::new(HolidayRule::NYSE.to_vec(), overrides);
+        assert!(!calendar.is_trading_day(date!(2018 - 12 - 05)));
+        assert!(calendar.is_trading_day(date!(2024 - 03 - 29)));
+        assert_eq!(
+            calendar
+                .trading_days(date!(2018 - 12 - 03), date!(2018 - 12 - 09))
+                .collect::<Vec<_>>(),
+            [
+                date!(2018 - 12 - 03),
+                date!(2018 - 12 - 04),
+                date!(2018 - 12 - 06),
+                date!(2018 - 12 - 07),
+            ]
+        );
+    }
+}
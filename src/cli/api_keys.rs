@@ -0,0 +1,89 @@
+use clap::{Args, Subcommand};
+use error_stack::{Report, Result, ResultExt};
+
+use crate::{config::Config, error::CommandError, model::Scope, tasks};
+
+/// Subcommands of the `api-keys` command.
+#[derive(Debug, Subcommand)]
+pub enum ApiKeysCommand {
+    /// Creates an API key, printing it once.
+    Create(CreateApiKeyArgs),
+    /// Lists the API keys, without revealing them.
+    List,
+    /// Revokes an API key, which is rejected from then on.
+    Revoke {
+        /// ID of the API key, as shown by `api-keys list`.
+        id: i32,
+    },
+}
+
+/// Arguments of the `api-keys create` command.
+#[derive(Debug, Args)]
+pub struct CreateApiKeyArgs {
+    /// Name describing who or what uses the key.
+    #[arg(long)]
+    pub name: String,
+    /// Comma separated scopes granted to the key.
+    #[arg(long, value_delimiter = ',', required = true)]
+    pub scopes: Vec<Scope>,
+}
+
+/// Runs a subcommand of the `api-keys` command.
+pub async fn run_api_keys(config: Config, command: ApiKeysCommand) -> Result<(), CommandError> {
+    log::trace!("Connecting to database");
+    let pool = tasks::connect_to_database(&config.database)
+        .await
+        .change_context(CommandError("api-keys"))?;
+    log::trace!("Creating table");
+    tasks::create_table_if_not_exists(pool.clone(), &config.database.schema_path)
+        .await
+        .change_context(CommandError("api-keys"))
+        .attach("Failed to create table on Postgres database.")?;
+
+    match command {
+        ApiKeysCommand::Create(args) => {
+            let (api_key, key) = tasks::create_api_key(&pool, &args.name, &args.scopes)
+                .await
+                .change_context(CommandError("api-keys"))?;
+            println!(
+                "Created API key `{}` ({}) with scopes {}.",
+                api_key.id,
+                api_key.name,
+                api_key.scopes.join(",")
+            );
+            println!("{}", key);
+            println!("Store it now, it can't be shown again.");
+        }
+        ApiKeysCommand::List => {
+            let api_keys = tasks::list_api_keys(&pool)
+                .await
+                .change_context(CommandError("api-keys"))?;
+            for api_key in api_keys {
+                let status = match (api_key.revoked_at, api_key.last_used_at) {
+                    (Some(revoked_at), _) => format!("revoked {}", revoked_at.date()),
+                    (None, Some(last_used_at)) => format!("last used {}", last_used_at.date()),
+                    (None, None) => "never used".into(),
+                };
+                println!(
+                    "{}\t{}…\t{}\t{}\t{}",
+                    api_key.id,
+                    api_key.prefix,
+                    api_key.name,
+                    api_key.scopes.join(","),
+                    status
+                );
+            }
+        }
+        ApiKeysCommand::Revoke { id } => {
+            let api_key = tasks::revoke_api_key(&pool, id)
+                .await
+                .change_context(CommandError("api-keys"))?
+                .ok_or_else(|| Report::new(CommandError("api-keys")))
+                .attach_printable_lazy(|| {
+                    format!("There is no API key `{}`, or it was already revoked.", id)
+                })?;
+            println!("Revoked API key `{}` ({}).", api_key.id, api_key.name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use clap::Subcommand;
+use error_stack::{IntoReport, Result, ResultExt};
+
+use crate::{config::Config, error::CommandError};
+
+/// Subcommands of the `config` command.
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+    /// Validates the configuration and prints it as TOML, with secrets redacted.
+    Check,
+}
+
+/// Runs a subcommand of the `config` command on the already validated configuration.
+pub fn run_config(config: Config, command: ConfigCommand) -> Result<(), CommandError> {
+    match command {
+        ConfigCommand::Check => {
+            let effective = toml::to_string_pretty(&config.redacted())
+                .into_report()
+                .change_context(CommandError("config"))
+                .attach("Failed to serialize configuration.")?;
+            print!("{}", effective);
+            Ok(())
+        }
+    }
+}
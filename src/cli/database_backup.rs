@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use error_stack::{IntoReport, Result, ResultExt};
+
+use crate::{config::Config, error::CommandError, tasks};
+
+/// Arguments of the `export-db` command.
+#[derive(Debug, Args)]
+pub struct ExportDbArgs {
+    /// Zip archive to write the tables to.
+    #[arg(short, long)]
+    pub output: PathBuf,
+}
+
+/// Arguments of the `import-db` command.
+#[derive(Debug, Args)]
+pub struct ImportDbArgs {
+    /// Zip archive written by the `export-db` command.
+    pub archive: PathBuf,
+}
+
+/// Reads the schema file, whose hash is the schema version of the archives.
+fn read_schema(config: &Config, command: &'static str) -> Result<String, CommandError> {
+    std::fs::read_to_string(&config.database.schema_path)
+        .into_report()
+        .change_context(CommandError(command))
+        .attach_printable_lazy(|| {
+            format!(
+                "Failed to read `{}`.",
+                config.database.schema_path.display()
+            )
+        })
+}
+
+/// Writes every table of the application into the archive of the `export-db` command.
+pub async fn run_export_db(config: Config, args: ExportDbArgs) -> Result<(), CommandError> {
+    let schema = read_schema(&config, "export-db")?;
+
+    log::trace!("Connecting to database");
+    let pool = tasks::connect_to_database(&config.database)
+        .await
+        .change_context(CommandError("export-db"))?;
+
+    log::trace!("Creating output file.");
+    let file = std::fs::File::create(&args.output)
+        .into_report()
+        .change_context(CommandError("export-db"))
+        .attach_printable_lazy(|| format!("Failed to create `{}`.", args.output.display()))?;
+
+    let manifest = tasks::export_database(&pool, &schema, std::io::BufWriter::new(file))
+        .await
+        .change_context(CommandError("export-db"))?;
+    for table in manifest.tables.iter() {
+        println!("export {}: {} rows", table.name, table.rows);
+    }
+    println!(
+        "Exported {} rows of {} tables to `{}`.",
+        manifest.tables.iter().map(|table| table.rows).sum::<u64>(),
+        manifest.tables.len(),
+        args.output.display()
+    );
+    Ok(())
+}
+
+/// Restores the rows of the archive of the `import-db` command that are missing from the database.
+pub async fn run_import_db(config: Config, args: ImportDbArgs) -> Result<(), CommandError> {
+    let schema = read_schema(&config, "import-db")?;
+    let file = std::fs::File::open(&args.archive)
+        .into_report()
+        .change_context(CommandError("import-db"))
+        .attach_printable_lazy(|| format!("Failed to open `{}`.", args.archive.display()))?;
+
+    log::trace!("Connecting to database");
+    let pool = tasks::connect_to_database(&config.database)
+        .await
+        .change_context(CommandError("import-db"))?;
+
+    log::trace!("Creating table");
+    tasks::create_table_if_not_exists(pool.clone(), &config.database.schema_path)
+        .await
+        .change_context(CommandError("import-db"))
+        .attach("Failed to create table on Postgres database.")?;
+
+    let restored = tasks::import_database(&pool, &schema, std::io::BufReader::new(file))
+        .await
+        .change_context(CommandError("import-db"))?;
+    for table in restored.iter() {
+        match table.skipped {
+            true => println!(
+                "import {}: {} rows skipped, the ids could clash with existing rows",
+                table.name, table.rows
+            ),
+            false => println!(
+                "import {}: {} of {} rows were missing",
+                table.name, table.inserted, table.rows
+            ),
+        }
+    }
+    let (skipped, restored): (Vec<_>, Vec<_>) = restored.iter().partition(|table| table.skipped);
+    println!(
+        "Imported {} rows from `{}`, {} were already present, {} were skipped.",
+        restored.iter().map(|table| table.inserted).sum::<u64>(),
+        args.archive.display(),
+        restored
+            .iter()
+            .map(|table| table.rows.saturating_sub(table.inserted))
+            .sum::<u64>(),
+        skipped.iter().map(|table| table.rows).sum::<u64>()
+    );
+    Ok(())
+}
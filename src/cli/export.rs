@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use error_stack::{IntoReport, Result, ResultExt};
+
+use crate::{config::Config, error::CommandError, model::ExportFormat, tasks};
+
+use super::parse_date;
+
+/// Arguments of the `export` command.
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// File to write the export to.
+    #[arg(short, long)]
+    pub output: PathBuf,
+    /// Format of the export.
+    #[arg(long, value_enum, default_value = "parquet")]
+    pub format: ExportFormat,
+    /// Comma separated global equities to export. Exports all equities if not set.
+    #[arg(long, value_delimiter = ',')]
+    pub symbols: Option<Vec<String>>,
+    /// Filters out dates earlier than this date.
+    #[arg(long, value_parser = parse_date)]
+    pub start_date: Option<time::Date>,
+    /// Filters out dates later than this date.
+    #[arg(long, value_parser = parse_date)]
+    pub end_date: Option<time::Date>,
+}
+
+/// Writes the `financial_data` rows selected by the `export` command into a file.
+pub async fn run_export(config: Config, args: ExportArgs) -> Result<(), CommandError> {
+    log::trace!("Connecting to database");
+    let pool = tasks::connect_to_database(&config.database)
+        .await
+        .change_context(CommandError("export"))?;
+
+    log::trace!("Creating output file.");
+    let file = std::fs::File::create(&args.output)
+        .into_report()
+        .change_context(CommandError("export"))
+        .attach_printable_lazy(|| format!("Failed to create `{}`.", args.output.display()))?;
+
+    let count = tasks::export_financial_data(
+        &pool,
+        args.symbols,
+        args.start_date,
+        args.end_date,
+        args.format,
+        std::io::BufWriter::new(file),
+    )
+    .await
+    .change_context(CommandError("export"))?;
+    println!("Exported {} rows to `{}`.", count, args.output.display());
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use clap::Args;
+use error_stack::{IntoReport, Report, Result, ResultExt};
+
+use crate::{config::Config, error::CommandError};
+
+/// Arguments of the `healthcheck` command.
+#[derive(Debug, Args)]
+pub struct HealthcheckArgs {
+    /// Probes `healthz` instead of `readyz`.
+    #[arg(long)]
+    pub live: bool,
+}
+
+/// Probes the server running on the configured address, failing unless it answers with success.
+///
+/// Used by the Docker Compose health check, since the image has no HTTP client.
+pub async fn run_healthcheck(config: Config, args: HealthcheckArgs) -> Result<(), CommandError> {
+    let endpoint = match args.live {
+        true => "healthz",
+        false => "readyz",
+    };
+    let url = format!(
+        "http://127.0.0.1:{}/{}",
+        config.server.bind_address.port(),
+        endpoint
+    );
+
+    log::trace!("Probing `{}`.", url);
+    let resp = reqwest::get(&url)
+        .await
+        .into_report()
+        .change_context(CommandError("healthcheck"))
+        .attach_printable_lazy(|| format!("Failed to query `{}`.", url))?;
+    let status = resp.status();
+    println!("{}", resp.text().await.unwrap_or_default());
+    match status.is_success() {
+        true => Ok(()),
+        false => Err(Report::new(CommandError("healthcheck")))
+            .attach_printable(format!("`{}` responded with `{}`.", url, status)),
+    }
+}
@@ -0,0 +1,160 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use error_stack::{Report, Result, ResultExt};
+
+use crate::{
+    config::Config,
+    error::CommandError,
+    tasks::{self, ColumnMapping, ImportFormat, SymbolSource},
+};
+
+/// Arguments of the `import` command.
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// Files to import.
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+    /// Format of the files. Guessed from the extension if not set.
+    #[arg(long, value_enum)]
+    pub format: Option<ImportFormat>,
+    /// Symbol used for every row. Defaults to the file name, e.g. `ibm.csv` => `IBM`.
+    #[arg(long, conflicts_with = "symbol_column")]
+    pub symbol: Option<String>,
+    /// Column holding the symbol of each row.
+    #[arg(long)]
+    pub symbol_column: Option<String>,
+    /// Column holding the date of each row.
+    #[arg(long, default_value = "timestamp")]
+    pub date_column: String,
+    /// Column holding the opening price of each row.
+    #[arg(long, default_value = "open")]
+    pub open_column: String,
+    /// Column holding the closing price of each row.
+    #[arg(long, default_value = "close")]
+    pub close_column: String,
+    /// Column holding the volume of each row.
+    #[arg(long, default_value = "volume")]
+    pub volume_column: String,
+    /// Format of the dates, using the `time` crate format description syntax.
+    #[arg(long, default_value = "[year]-[month]-[day]")]
+    pub date_format: String,
+    /// Reports what would change on the database without changing it.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+impl ImportArgs {
+    /// Builds the column mapping from the arguments.
+    fn column_mapping(&self) -> ColumnMapping {
+        let symbol = match (&self.symbol, &self.symbol_column) {
+            (Some(symbol), _) => SymbolSource::Fixed(symbol.clone()),
+            (None, Some(column)) => SymbolSource::Column(column.clone()),
+            (None, None) => SymbolSource::FileName,
+        };
+        ColumnMapping {
+            symbol,
+            date: self.date_column.clone(),
+            open: self.open_column.clone(),
+            close: self.close_column.clone(),
+            volume: self.volume_column.clone(),
+        }
+    }
+}
+
+/// Reads the files passed to the `import` command and upserts their values into the database.
+pub async fn run_import(config: Config, args: ImportArgs) -> Result<(), CommandError> {
+    let mapping = args.column_mapping();
+
+    log::trace!("Reading files.");
+    let mut rows = vec![];
+    for path in args.files.iter() {
+        let format = args
+            .format
+            .or_else(|| ImportFormat::from_path(path))
+            .ok_or_else(|| Report::new(CommandError("import")))
+            .attach_printable_lazy(|| {
+                format!(
+                    "Could not guess format of `{}`, use `--format`.",
+                    path.display()
+                )
+            })?;
+        rows.extend(
+            tasks::read_import_file(path, format, &mapping, &args.date_format)
+                .change_context(CommandError("import"))?,
+        );
+    }
+
+    log::trace!("Connecting to database");
+    let pool = tasks::connect_to_database(&config.database)
+        .await
+        .change_context(CommandError("import"))?;
+
+    if args.dry_run {
+        log::trace!("Comparing rows against database.");
+        let plan = tasks::plan_upsert(pool, rows)
+            .await
+            .change_context(CommandError("import"))?;
+        for r in plan.inserted.iter() {
+            println!(
+                "insert {} {}: open={} close={} volume={}",
+                r.symbol, r.date, r.open_price, r.close_price, r.volume
+            );
+        }
+        for (old, new) in plan.updated.iter() {
+            println!(
+                "update {} {}: open={}=>{} close={}=>{} volume={}=>{}",
+                new.symbol,
+                new.date,
+                old.open_price,
+                new.open_price,
+                old.close_price,
+                new.close_price,
+                old.volume,
+                new.volume
+            );
+        }
+        println!(
+            "Dry run: {} rows would be inserted, {} updated, and {} are unchanged.",
+            plan.inserted.len(),
+            plan.updated.len(),
+            plan.unchanged
+        );
+        if plan.duplicates > 0 {
+            println!(
+                "{} rows are overridden by a later row with the same symbol and date.",
+                plan.duplicates
+            );
+        }
+        return Ok(());
+    }
+
+    log::trace!("Creating table");
+    tasks::create_table_if_not_exists(pool.clone(), &config.database.schema_path)
+        .await
+        .change_context(CommandError("import"))
+        .attach("Failed to create table on Postgres database.")?;
+
+    let count = rows.len();
+    let started_at = time::OffsetDateTime::now_utc();
+    log::trace!("Saving values into database");
+    let updated = tasks::upsert_in_database(pool.clone(), rows)
+        .await
+        .change_context(CommandError("import"))?;
+    tasks::record_ingestion_run(&pool, tasks::IMPORT_SOURCE, started_at, Ok(updated.len()))
+        .await
+        .change_context(CommandError("import"))?;
+
+    log::trace!("Evaluating alert rules");
+    let deliveries = tasks::evaluate_alerts(pool, &updated)
+        .await
+        .change_context(CommandError("import"))?;
+    futures::future::join_all(deliveries).await;
+    println!(
+        "Imported {} rows from {} files, {} were new or changed.",
+        count,
+        args.files.len(),
+        updated.len()
+    );
+    Ok(())
+}
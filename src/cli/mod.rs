@@ -0,0 +1,60 @@
+use clap::{Parser, Subcommand};
+
+use crate::config::ConfigArgs;
+
+mod api_keys;
+pub use api_keys::*;
+mod config;
+pub use config::*;
+mod database_backup;
+pub use database_backup::*;
+mod export;
+pub use export::*;
+mod healthcheck;
+pub use healthcheck::*;
+mod import;
+pub use import::*;
+mod retention;
+pub use retention::*;
+
+/// Parses a `YYYY-MM-DD` date from the command line.
+fn parse_date(value: &str) -> std::result::Result<time::Date, String> {
+    let format =
+        time::format_description::parse("[year]-[month]-[day]").map_err(|err| err.to_string())?;
+    time::Date::parse(value, &format).map_err(|err| err.to_string())
+}
+
+/// Command line interface of the application.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(flatten)]
+    pub config: ConfigArgs,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands available on the command line. Runs the server if none is given.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Runs the Axum server and the recurring task that queries Alpha Vantage.
+    Serve,
+    /// Imports daily time series entries from CSV or JSON files into the database.
+    Import(ImportArgs),
+    /// Exports daily time series entries as an Apache Arrow IPC stream or Apache Parquet file.
+    Export(ExportArgs),
+    /// Backs up every table of the application into a versioned zip archive.
+    ExportDb(ExportDbArgs),
+    /// Restores a backup of the `export-db` command, keeping the rows that already exist.
+    ImportDb(ImportDbArgs),
+    /// Inspects the configuration loaded from the file, environment variables and flags.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Manages the API keys allowed to call the endpoints.
+    #[command(subcommand)]
+    ApiKeys(ApiKeysCommand),
+    /// Prunes the entries older than the retention policies, archiving them first if configured.
+    Retention(RetentionArgs),
+    /// Probes the readiness of the running server, exiting with an error unless it is ready.
+    Healthcheck(HealthcheckArgs),
+}
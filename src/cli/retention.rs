@@ -0,0 +1,53 @@
+use clap::Args;
+use error_stack::{Result, ResultExt};
+
+use crate::{config::Config, error::CommandError, tasks};
+
+/// Arguments of the `retention` command.
+#[derive(Debug, Args)]
+pub struct RetentionArgs {
+    /// Reports what would be pruned without changing the database or writing archives.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Prunes the `financial_data` rows older than the retention policies once, as the server does on its interval.
+pub async fn run_retention(config: Config, args: RetentionArgs) -> Result<(), CommandError> {
+    log::trace!("Connecting to database");
+    let pool = tasks::connect_to_database(&config.database)
+        .await
+        .change_context(CommandError("retention"))?;
+
+    if !args.dry_run {
+        log::trace!("Creating table");
+        tasks::create_table_if_not_exists(pool.clone(), &config.database.schema_path)
+            .await
+            .change_context(CommandError("retention"))
+            .attach("Failed to create table on Postgres database.")?;
+    }
+
+    let reports = tasks::enforce_retention(&pool, &config.retention, args.dry_run)
+        .await
+        .change_context(CommandError("retention"))?;
+    for report in reports.iter() {
+        let archive = match &report.archive {
+            Some(path) => format!(", archived to `{}`", path.display()),
+            None => "".into(),
+        };
+        println!(
+            "prune {}: {} rows from {} to {}, keeping {} onwards{}",
+            report.symbol, report.rows, report.first_date, report.last_date, report.cutoff, archive
+        );
+    }
+    let rows: usize = reports.iter().map(|report| report.rows).sum();
+    if args.dry_run {
+        println!(
+            "Dry run: {} rows of {} symbols would be pruned.",
+            rows,
+            reports.len()
+        );
+    } else {
+        println!("Pruned {} rows of {} symbols.", rows, reports.len());
+    }
+    Ok(())
+}
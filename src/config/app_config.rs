@@ -0,0 +1,612 @@
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use error_stack::{IntoReport, Report, Result, ResultExt};
+use serde::Serialize;
+use time::format_description::well_known::Iso8601;
+
+use crate::{calendar::HolidayRule, error::ConfigError};
+
+use super::{ArchiveFormat, ConfigArgs, ConfigFile, LogFormat, RateLimitBackend, RetentionPolicy};
+
+/// Configuration file read when none is passed.
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// Locations probed for the schema file when none is configured.
+const DEFAULT_SCHEMA_PATHS: [&str; 2] = ["schema.sql", "/var/app/schema.sql"];
+
+/// Day `/api/v1` stops being served when none is configured.
+const DEFAULT_V1_SUNSET: &str = "2027-04-19";
+
+/// Replaces secrets when printing the configuration.
+const REDACTED: &str = "REDACTED";
+
+/// Parts of the names of URL query parameters holding secrets, e.g. `password`, `sslpassword` or `apikey`.
+const SECRET_PARAMETERS: [&str; 4] = ["password", "secret", "token", "key"];
+
+/// Replaces the password and the query parameters holding secrets of a URL, or the whole URL if it can't be parsed.
+fn redact_url(url: &str) -> String {
+    let Ok(mut url) = reqwest::Url::parse(url) else {
+        return REDACTED.into();
+    };
+    if url.password().is_some() && url.set_password(Some(REDACTED)).is_err() {
+        return REDACTED.into();
+    }
+    if url.query().is_some() {
+        let pairs: Vec<(String, String)> = url
+            .query_pairs()
+            .map(|(name, value)| {
+                let lowercase = name.to_ascii_lowercase();
+                match SECRET_PARAMETERS
+                    .iter()
+                    .any(|secret| lowercase.contains(secret))
+                {
+                    true => (name.into_owned(), REDACTED.into()),
+                    false => (name.into_owned(), value.into_owned()),
+                }
+            })
+            .collect();
+        url.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    url.to_string()
+}
+
+/// Settings of the application.
+///
+/// Loaded from the defaults, overridden by the TOML configuration file,
+/// then by environment variables, then by command line flags.
+#[derive(Debug, Clone, Serialize)]
+pub struct Config {
+    pub database: DatabaseConfig,
+    pub server: ServerConfig,
+    pub ingestion: IngestionConfig,
+    pub logging: LoggingConfig,
+    pub auth: AuthConfig,
+    pub rate_limit: RateLimitConfig,
+    pub cache: CacheConfig,
+    pub api: ApiConfig,
+    pub graphql: GraphqlConfig,
+    pub grpc: GrpcConfig,
+    pub calendar: CalendarConfig,
+    pub retention: RetentionConfig,
+}
+
+/// Settings of the connection to the database.
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseConfig {
+    /// `DATABASE_URL` environment variable.
+    pub url: String,
+    /// `DATABASE_MAX_CONNECTIONS` environment variable, or `--max-connections` flag.
+    pub max_connections: u32,
+    /// `SCHEMA_PATH` environment variable, or `--schema-path` flag.
+    pub schema_path: PathBuf,
+}
+
+/// Settings of the Axum server.
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerConfig {
+    /// `BIND_ADDRESS` environment variable, or `--bind-address` flag.
+    pub bind_address: SocketAddr,
+    /// `SHUTDOWN_DEADLINE_SECS` environment variable.
+    pub shutdown_deadline_secs: u64,
+}
+
+/// Settings of the recurring task querying Alpha Vantage.
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestionConfig {
+    /// `ALPHA_VANTAGE_API_KEY` environment variable.
+    pub api_key: Option<String>,
+    /// `INGESTION_INTERVAL_DAYS` environment variable, or `--interval-days` flag.
+    pub interval_days: i64,
+    /// `INGESTION_FRESHNESS_SLO_SECS` environment variable.
+    /// Defaults to the interval plus an hour of slack.
+    pub freshness_slo_secs: u64,
+    /// `INGESTION_GAP_FILL_DAYS` environment variable.
+    /// Trading days missing within this many days are requested again on each run, `0` to disable.
+    pub gap_fill_days: u32,
+}
+
+/// Settings of the logs and traces.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoggingConfig {
+    /// `LOG_FORMAT` environment variable, or `--log-format` flag.
+    pub format: LogFormat,
+    /// `OTEL_EXPORTER_OTLP_ENDPOINT` environment variable.
+    /// Traces are exported to this OpenTelemetry collector if set, which needs the `otlp` feature.
+    pub otlp_endpoint: Option<String>,
+}
+
+/// Settings of the authentication of the endpoints.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthConfig {
+    /// `AUTH_ENABLED` environment variable.
+    /// When disabled, every endpoint is open, which is only meant for local development.
+    pub enabled: bool,
+}
+
+/// Settings of the rate limits of the endpoints that need an API key.
+///
+/// Each API key, or each client IP when authentication is disabled, has a bucket of `burst` requests
+/// refilled at `requests_per_second`, and can make up to `daily_quota` requests per UTC day.
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitConfig {
+    /// `RATE_LIMIT_ENABLED` environment variable.
+    pub enabled: bool,
+    /// `RATE_LIMIT_REQUESTS_PER_SECOND` environment variable.
+    pub requests_per_second: f64,
+    /// `RATE_LIMIT_BURST` environment variable.
+    pub burst: u32,
+    /// `RATE_LIMIT_DAILY_QUOTA` environment variable. No quota if not set.
+    pub daily_quota: Option<u64>,
+    /// `RATE_LIMIT_BACKEND` environment variable.
+    pub backend: RateLimitBackend,
+    /// `RATE_LIMIT_TRUSTED_PROXIES` environment variable.
+    /// Number of reverse proxies in front of the server, each appending the address of its client to the
+    /// `X-Forwarded-For` header. Clients are identified by the address of the connection when `0`.
+    pub trusted_proxies: usize,
+}
+
+/// Settings of the cache of the `statistics` and `financial_data` responses.
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheConfig {
+    /// `CACHE_ENABLED` environment variable.
+    /// When disabled, responses are still given an `ETag`, but are computed on every request.
+    pub enabled: bool,
+    /// `CACHE_TTL_SECS` environment variable.
+    pub ttl_secs: u64,
+    /// `CACHE_CAPACITY` environment variable. Least recently used responses are evicted above it.
+    pub capacity: usize,
+}
+
+/// Settings of the versions of the API.
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiConfig {
+    /// `API_V1_SUNSET` environment variable, as a `YYYY-MM-DD` date.
+    /// Announced in the `Sunset` header of every `/api/v1` response.
+    pub v1_sunset: time::Date,
+}
+
+/// Settings of the `/graphql` endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphqlConfig {
+    /// `GRAPHQL_ENABLED` environment variable.
+    pub enabled: bool,
+    /// `GRAPHQL_MAX_DEPTH` environment variable. Deeper queries are rejected before running.
+    pub max_depth: usize,
+    /// `GRAPHQL_MAX_COMPLEXITY` environment variable.
+    /// Each field counts 1, multiplied by the page size of the connections it is nested in.
+    pub max_complexity: usize,
+}
+
+/// Settings of the gRPC server, which runs alongside the Axum server on its own port.
+#[derive(Debug, Clone, Serialize)]
+pub struct GrpcConfig {
+    /// `GRPC_ENABLED` environment variable.
+    pub enabled: bool,
+    /// `GRPC_BIND_ADDRESS` environment variable.
+    pub bind_address: SocketAddr,
+}
+
+/// Settings of the trading calendar, used to find the trading days missing from the time series.
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarConfig {
+    /// `CALENDAR_HOLIDAYS` environment variable, as comma separated rules. Defaults to every NYSE holiday.
+    /// Exceptional closures and openings are saved on the `trading_calendar_overrides` table.
+    pub holidays: Vec<HolidayRule>,
+}
+
+/// Settings of the pruning of old `financial_data` rows.
+#[derive(Debug, Clone, Serialize)]
+pub struct RetentionConfig {
+    /// `RETENTION_ENABLED` environment variable.
+    /// When disabled, rows are only pruned by the `retention` command.
+    pub enabled: bool,
+    /// `RETENTION_INTERVAL_HOURS` environment variable.
+    pub interval_hours: u64,
+    /// `RETENTION_POLICIES` environment variable, as comma separated `SYMBOL|SYMBOL=YEARS` policies.
+    /// Symbols matching no policy are kept forever.
+    pub policies: Vec<RetentionPolicy>,
+    /// `RETENTION_ARCHIVE_DIR` environment variable. Pruned rows are archived there before being deleted if set.
+    pub archive_dir: Option<PathBuf>,
+    /// `RETENTION_ARCHIVE_FORMAT` environment variable.
+    pub archive_format: ArchiveFormat,
+}
+
+impl RetentionConfig {
+    /// Policy applying to `symbol`: the one naming it, or else the `*` one.
+    pub fn policy(&self, symbol: &str) -> Option<&RetentionPolicy> {
+        self.policies
+            .iter()
+            .find(|policy| policy.names(symbol))
+            .or_else(|| self.policies.iter().find(|policy| policy.is_default()))
+    }
+}
+
+/// Reads an environment variable, recording a problem if it can't be parsed.
+fn env_var<T: FromStr>(name: &str, problems: &mut Vec<String>) -> Option<T> {
+    let value = std::env::var(name).ok()?;
+    match value.parse() {
+        Ok(value) => Some(value),
+        Err(_) => {
+            problems.push(format!(
+                "Environment variable `{}` has invalid value `{}`.",
+                name, value
+            ));
+            None
+        }
+    }
+}
+
+/// Reads an environment variable holding a comma separated list, recording a problem if an item can't be parsed.
+fn env_list<T: FromStr>(name: &str, problems: &mut Vec<String>) -> Option<Vec<T>> {
+    let value = std::env::var(name).ok()?;
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            item.parse().map_err(|_| {
+                problems.push(format!(
+                    "Environment variable `{}` has invalid item `{}`.",
+                    name, item
+                ))
+            })
+        })
+        .collect::<std::result::Result<_, _>>()
+        .ok()
+}
+
+/// Reads the configuration file, which is optional unless passed explicitly.
+fn read_config_file(path: Option<&Path>) -> Result<ConfigFile, ConfigError> {
+    let (path, explicit) = match path {
+        Some(path) => (path, true),
+        None => (Path::new(DEFAULT_CONFIG_PATH), false),
+    };
+    if !explicit && !path.exists() {
+        return Ok(ConfigFile::default());
+    }
+
+    log::trace!("Reading configuration file `{}`.", path.display());
+    let content = std::fs::read_to_string(path)
+        .into_report()
+        .change_context(ConfigError)
+        .attach_printable_lazy(|| format!("Failed to read `{}`.", path.display()))?;
+    toml::from_str(&content)
+        .into_report()
+        .change_context(ConfigError)
+        .attach_printable_lazy(|| format!("Failed to parse `{}`.", path.display()))
+}
+
+impl Config {
+    /// Loads and validates the configuration, reporting every invalid or missing setting.
+    pub fn load(args: &ConfigArgs) -> Result<Config, ConfigError> {
+        let config_path = args
+            .config
+            .clone()
+            .or_else(|| std::env::var_os("CONFIG_FILE").map(PathBuf::from));
+        let file = read_config_file(config_path.as_deref())?;
+        let mut problems = vec![];
+
+        let url = env_var::<String>("DATABASE_URL", &mut problems).or(file.database.url);
+        let max_connections = args
+            .max_connections
+            .or_else(|| env_var("DATABASE_MAX_CONNECTIONS", &mut problems))
+            .or(file.database.max_connections)
+            .unwrap_or(5);
+        let schema_path = args
+            .schema_path
+            .clone()
+            .or_else(|| env_var("SCHEMA_PATH", &mut problems))
+            .or(file.database.schema_path);
+        let bind_address = args
+            .bind_address
+            .clone()
+            .or_else(|| env_var("BIND_ADDRESS", &mut problems))
+            .or(file.server.bind_address)
+            .unwrap_or_else(|| "0.0.0.0:8000".into());
+        let shutdown_deadline_secs = env_var("SHUTDOWN_DEADLINE_SECS", &mut problems)
+            .or(file.server.shutdown_deadline_secs)
+            .unwrap_or(10);
+        let api_key = env_var("ALPHA_VANTAGE_API_KEY", &mut problems).or(file.ingestion.api_key);
+        let interval_days = args
+            .interval_days
+            .or_else(|| env_var("INGESTION_INTERVAL_DAYS", &mut problems))
+            .or(file.ingestion.interval_days)
+            .unwrap_or(1);
+        let freshness_slo_secs = env_var("INGESTION_FRESHNESS_SLO_SECS", &mut problems)
+            .or(file.ingestion.freshness_slo_secs)
+            .unwrap_or_else(|| interval_days.max(1) as u64 * 24 * 60 * 60 + 60 * 60);
+        let gap_fill_days = env_var("INGESTION_GAP_FILL_DAYS", &mut problems)
+            .or(file.ingestion.gap_fill_days)
+            .unwrap_or(90);
+        let log_format = args
+            .log_format
+            .or_else(|| env_var("LOG_FORMAT", &mut problems))
+            .or(file.logging.format)
+            .unwrap_or_default();
+        let otlp_endpoint =
+            env_var("OTEL_EXPORTER_OTLP_ENDPOINT", &mut problems).or(file.logging.otlp_endpoint);
+        let auth_enabled = env_var("AUTH_ENABLED", &mut problems)
+            .or(file.auth.enabled)
+            .unwrap_or(true);
+        let rate_limit = RateLimitConfig {
+            enabled: env_var("RATE_LIMIT_ENABLED", &mut problems)
+                .or(file.rate_limit.enabled)
+                .unwrap_or(true),
+            requests_per_second: env_var("RATE_LIMIT_REQUESTS_PER_SECOND", &mut problems)
+                .or(file.rate_limit.requests_per_second)
+                .unwrap_or(10.),
+            burst: env_var("RATE_LIMIT_BURST", &mut problems)
+                .or(file.rate_limit.burst)
+                .unwrap_or(20),
+            daily_quota: env_var("RATE_LIMIT_DAILY_QUOTA", &mut problems)
+                .or(file.rate_limit.daily_quota),
+            backend: env_var("RATE_LIMIT_BACKEND", &mut problems)
+                .or(file.rate_limit.backend)
+                .unwrap_or_default(),
+            trusted_proxies: env_var("RATE_LIMIT_TRUSTED_PROXIES", &mut problems)
+                .or(file.rate_limit.trusted_proxies)
+                .unwrap_or(0),
+        };
+        let cache = CacheConfig {
+            enabled: env_var("CACHE_ENABLED", &mut problems)
+                .or(file.cache.enabled)
+                .unwrap_or(true),
+            ttl_secs: env_var("CACHE_TTL_SECS", &mut problems)
+                .or(file.cache.ttl_secs)
+                .unwrap_or(60),
+            capacity: env_var("CACHE_CAPACITY", &mut problems)
+                .or(file.cache.capacity)
+                .unwrap_or(1024),
+        };
+        let v1_sunset = env_var::<String>("API_V1_SUNSET", &mut problems)
+            .or(file.api.v1_sunset)
+            .unwrap_or_else(|| DEFAULT_V1_SUNSET.into());
+        let graphql = GraphqlConfig {
+            enabled: env_var("GRAPHQL_ENABLED", &mut problems)
+                .or(file.graphql.enabled)
+                .unwrap_or(true),
+            max_depth: env_var("GRAPHQL_MAX_DEPTH", &mut problems)
+                .or(file.graphql.max_depth)
+                .unwrap_or(8),
+            max_complexity: env_var("GRAPHQL_MAX_COMPLEXITY", &mut problems)
+                .or(file.graphql.max_complexity)
+                .unwrap_or(1000),
+        };
+        let grpc = GrpcConfig {
+            enabled: env_var("GRPC_ENABLED", &mut problems)
+                .or(file.grpc.enabled)
+                .unwrap_or(true),
+            bind_address: env_var("GRPC_BIND_ADDRESS", &mut problems)
+                .or(file.grpc.bind_address)
+                .unwrap_or_else(|| SocketAddr::from(([0, 0, 0, 0], 50051))),
+        };
+        let calendar = CalendarConfig {
+            holidays: env_list("CALENDAR_HOLIDAYS", &mut problems)
+                .or(file.calendar.holidays)
+                .unwrap_or_else(|| HolidayRule::NYSE.to_vec()),
+        };
+        let retention = RetentionConfig {
+            enabled: env_var("RETENTION_ENABLED", &mut problems)
+                .or(file.retention.enabled)
+                .unwrap_or(true),
+            interval_hours: env_var("RETENTION_INTERVAL_HOURS", &mut problems)
+                .or(file.retention.interval_hours)
+                .unwrap_or(24),
+            policies: env_list("RETENTION_POLICIES", &mut problems)
+                .or(file.retention.policies)
+                .unwrap_or_default(),
+            archive_dir: env_var("RETENTION_ARCHIVE_DIR", &mut problems)
+                .or(file.retention.archive_dir),
+            archive_format: env_var("RETENTION_ARCHIVE_FORMAT", &mut problems)
+                .or(file.retention.archive_format)
+                .unwrap_or_default(),
+        };
+
+        log::trace!("Validating configuration.");
+        if url.is_none() {
+            problems.push(
+                "Database URL is not set, use the `DATABASE_URL` environment variable.".into(),
+            );
+        }
+        if max_connections == 0 {
+            problems.push("Maximum number of database connections must be bigger than 0.".into());
+        }
+        let schema_path = match schema_path {
+            Some(path) if path.is_file() => Some(path),
+            Some(path) => {
+                problems.push(format!("Schema file `{}` does not exist.", path.display()));
+                None
+            }
+            None => {
+                let found = DEFAULT_SCHEMA_PATHS
+                    .iter()
+                    .map(PathBuf::from)
+                    .find(|path| path.is_file());
+                if found.is_none() {
+                    problems.push(format!(
+                        "Schema file was not found on {:?}, use `SCHEMA_PATH`.",
+                        DEFAULT_SCHEMA_PATHS
+                    ));
+                }
+                found
+            }
+        };
+        let bind_address = match bind_address.parse::<SocketAddr>() {
+            Ok(address) => Some(address),
+            Err(_) => {
+                problems.push(format!(
+                    "Bind address `{}` must be an IP address and port, e.g. `0.0.0.0:8000`.",
+                    bind_address
+                ));
+                None
+            }
+        };
+        if grpc.enabled && bind_address == Some(grpc.bind_address) {
+            problems.push(format!(
+                "gRPC server must bind to another address than `{}`, used by the Axum server.",
+                grpc.bind_address
+            ));
+        }
+        if interval_days <= 0 {
+            problems.push("Ingestion interval must be at least 1 day.".into());
+        }
+        if rate_limit.requests_per_second.is_nan() || rate_limit.requests_per_second <= 0. {
+            problems.push("Rate limit must allow more than 0 requests per second.".into());
+        }
+        if rate_limit.burst == 0 {
+            problems.push("Rate limit burst must be at least 1 request.".into());
+        }
+        if cache.enabled && (cache.ttl_secs == 0 || cache.capacity == 0) {
+            problems.push(
+                "Cache TTL and capacity must be bigger than 0, or the cache disabled.".into(),
+            );
+        }
+        if graphql.enabled && (graphql.max_depth == 0 || graphql.max_complexity == 0) {
+            problems.push(
+                "GraphQL depth and complexity limits must be bigger than 0, or GraphQL disabled."
+                    .into(),
+            );
+        }
+        let v1_sunset = match time::Date::parse(&v1_sunset, &Iso8601::DATE) {
+            Ok(date) => Some(date),
+            Err(_) => {
+                problems.push(format!(
+                    "API v1 sunset `{}` must be a date, e.g. `{}`.",
+                    v1_sunset, DEFAULT_V1_SUNSET
+                ));
+                None
+            }
+        };
+        if retention.interval_hours == 0 {
+            problems.push("Retention interval must be at least 1 hour.".into());
+        }
+        if retention
+            .policies
+            .iter()
+            .any(|policy| policy.keep_years == 0)
+        {
+            problems.push("Retention policies must keep at least 1 year.".into());
+        }
+        let mut symbols = std::collections::HashSet::new();
+        for symbol in retention.policies.iter().flat_map(|policy| &policy.symbols) {
+            if !symbols.insert(symbol.trim().to_uppercase()) {
+                problems.push(format!(
+                    "Symbol `{}` is on more than one retention policy.",
+                    symbol
+                ));
+            }
+        }
+        if let Some(dir) = retention.archive_dir.as_ref().filter(|dir| dir.exists()) {
+            if !dir.is_dir() {
+                problems.push(format!(
+                    "Retention archive directory `{}` is not a directory.",
+                    dir.display()
+                ));
+            }
+        }
+        if otlp_endpoint.is_some() && !cfg!(feature = "otlp") {
+            problems.push(
+                "An OTLP endpoint is set, but the application was built without the `otlp` feature."
+                    .into(),
+            );
+        }
+
+        match (url, schema_path, bind_address, v1_sunset) {
+            (Some(url), Some(schema_path), Some(bind_address), Some(v1_sunset))
+                if problems.is_empty() =>
+            {
+                Ok(Config {
+                    database: DatabaseConfig {
+                        url,
+                        max_connections,
+                        schema_path,
+                    },
+                    server: ServerConfig {
+                        bind_address,
+                        shutdown_deadline_secs,
+                    },
+                    ingestion: IngestionConfig {
+                        api_key,
+                        interval_days,
+                        freshness_slo_secs,
+                        gap_fill_days,
+                    },
+                    logging: LoggingConfig {
+                        format: log_format,
+                        otlp_endpoint,
+                    },
+                    auth: AuthConfig {
+                        enabled: auth_enabled,
+                    },
+                    rate_limit,
+                    cache,
+                    api: ApiConfig { v1_sunset },
+                    graphql,
+                    grpc,
+                    calendar,
+                    retention,
+                })
+            }
+            _ => Err(problems
+                .into_iter()
+                .fold(Report::new(ConfigError), |report, problem| {
+                    report.attach_printable(problem)
+                })),
+        }
+    }
+
+    /// Gets the Alpha Vantage API key, which is only required to run the server.
+    pub fn api_key(&self) -> Result<&str, ConfigError> {
+        self.ingestion
+            .api_key
+            .as_deref()
+            .ok_or_else(|| Report::new(ConfigError))
+            .attach("Environment variable `ALPHA_VANTAGE_API_KEY` is not set")
+    }
+
+    /// Time given to open connections to finish once a shutdown is requested.
+    pub fn shutdown_deadline(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.server.shutdown_deadline_secs)
+    }
+
+    /// Maximum time since the last successful ingestion before the server is reported as not ready.
+    pub fn freshness_slo(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.ingestion.freshness_slo_secs)
+    }
+
+    /// Copy of the configuration with the secrets replaced, for printing.
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        config.database.url = redact_url(&self.database.url);
+        config.logging.otlp_endpoint = self.logging.otlp_endpoint.as_deref().map(redact_url);
+        config.ingestion.api_key = config.ingestion.api_key.map(|_| REDACTED.into());
+        config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_are_redacted() {
+        assert_eq!(
+            redact_url("postgres://user:hunter2@db:5432/app"),
+            "postgres://user:REDACTED@db:5432/app"
+        );
+        assert_eq!(
+            redact_url("postgres://db/app?user=app&password=hunter2&sslmode=require"),
+            "postgres://db/app?user=app&password=REDACTED&sslmode=require"
+        );
+        assert_eq!(
+            redact_url("http://collector:4317/?apikey=abc&api-token=def"),
+            "http://collector:4317/?apikey=REDACTED&api-token=REDACTED"
+        );
+        assert_eq!(redact_url("postgres://db/app"), "postgres://db/app");
+        assert_eq!(redact_url("not a url"), REDACTED);
+    }
+}
@@ -0,0 +1,33 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Format of the files the pruned `financial_data` rows are archived to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveFormat {
+    /// Gzip compressed CSV file, with the columns the `import` command reads by default.
+    Csv,
+    /// Apache Parquet file, with the schema of the `export` command.
+    #[default]
+    Parquet,
+}
+
+impl ArchiveFormat {
+    /// File extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ArchiveFormat::Csv => "csv.gz",
+            ArchiveFormat::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for ArchiveFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        <ArchiveFormat as ValueEnum>::from_str(value, true)
+    }
+}
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use clap::Args;
+
+use super::LogFormat;
+
+/// Configuration flags accepted by every command, overriding the configuration file and environment variables.
+#[derive(Debug, Default, Args)]
+pub struct ConfigArgs {
+    /// TOML configuration file. Defaults to the `CONFIG_FILE` environment variable, then to `config.toml` if it exists.
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    /// Address the server binds to.
+    #[arg(long, global = true)]
+    pub bind_address: Option<String>,
+    /// Maximum number of connections to the database.
+    #[arg(long, global = true)]
+    pub max_connections: Option<u32>,
+    /// Path of the SQL schema file.
+    #[arg(long, global = true)]
+    pub schema_path: Option<PathBuf>,
+    /// Number of days between queries of Alpha Vantage.
+    #[arg(long, global = true)]
+    pub interval_days: Option<i64>,
+    /// Output format of the logs.
+    #[arg(long, global = true)]
+    pub log_format: Option<LogFormat>,
+}
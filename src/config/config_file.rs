@@ -0,0 +1,142 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::calendar::HolidayRule;
+
+use super::{ArchiveFormat, LogFormat, RateLimitBackend, RetentionPolicy};
+
+/// Values read from the TOML configuration file. Every value is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    #[serde(default)]
+    pub database: DatabaseConfigFile,
+    #[serde(default)]
+    pub server: ServerConfigFile,
+    #[serde(default)]
+    pub ingestion: IngestionConfigFile,
+    #[serde(default)]
+    pub logging: LoggingConfigFile,
+    #[serde(default)]
+    pub auth: AuthConfigFile,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfigFile,
+    #[serde(default)]
+    pub cache: CacheConfigFile,
+    #[serde(default)]
+    pub api: ApiConfigFile,
+    #[serde(default)]
+    pub graphql: GraphqlConfigFile,
+    #[serde(default)]
+    pub grpc: GrpcConfigFile,
+    #[serde(default)]
+    pub calendar: CalendarConfigFile,
+    #[serde(default)]
+    pub retention: RetentionConfigFile,
+}
+
+/// `[database]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DatabaseConfigFile {
+    pub url: Option<String>,
+    pub max_connections: Option<u32>,
+    pub schema_path: Option<PathBuf>,
+}
+
+/// `[server]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfigFile {
+    pub bind_address: Option<String>,
+    pub shutdown_deadline_secs: Option<u64>,
+}
+
+/// `[ingestion]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IngestionConfigFile {
+    pub api_key: Option<String>,
+    pub interval_days: Option<i64>,
+    pub freshness_slo_secs: Option<u64>,
+    pub gap_fill_days: Option<u32>,
+}
+
+/// `[logging]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoggingConfigFile {
+    pub format: Option<LogFormat>,
+    pub otlp_endpoint: Option<String>,
+}
+
+/// `[auth]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfigFile {
+    pub enabled: Option<bool>,
+}
+
+/// `[rate_limit]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfigFile {
+    pub enabled: Option<bool>,
+    pub requests_per_second: Option<f64>,
+    pub burst: Option<u32>,
+    pub daily_quota: Option<u64>,
+    pub backend: Option<RateLimitBackend>,
+    pub trusted_proxies: Option<usize>,
+}
+
+/// `[cache]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CacheConfigFile {
+    pub enabled: Option<bool>,
+    pub ttl_secs: Option<u64>,
+    pub capacity: Option<usize>,
+}
+
+/// `[api]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConfigFile {
+    pub v1_sunset: Option<String>,
+}
+
+/// `[graphql]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GraphqlConfigFile {
+    pub enabled: Option<bool>,
+    pub max_depth: Option<usize>,
+    pub max_complexity: Option<usize>,
+}
+
+/// `[grpc]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrpcConfigFile {
+    pub enabled: Option<bool>,
+    pub bind_address: Option<SocketAddr>,
+}
+
+/// `[calendar]` section of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CalendarConfigFile {
+    pub holidays: Option<Vec<HolidayRule>>,
+}
+
+/// `[retention]` section of the configuration file, with a `[[retention.policies]]` table per policy.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionConfigFile {
+    pub enabled: Option<bool>,
+    pub interval_hours: Option<u64>,
+    pub policies: Option<Vec<RetentionPolicy>>,
+    pub archive_dir: Option<PathBuf>,
+    pub archive_format: Option<ArchiveFormat>,
+}
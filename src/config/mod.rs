@@ -0,0 +1,14 @@
+mod app_config;
+pub use app_config::*;
+mod archive_format;
+pub use archive_format::*;
+mod config_args;
+pub use config_args::*;
+mod config_file;
+pub use config_file::*;
+mod log_format;
+pub use log_format::*;
+mod rate_limit_backend;
+pub use rate_limit_backend::*;
+mod retention_policy;
+pub use retention_policy::*;
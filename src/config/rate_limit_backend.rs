@@ -0,0 +1,23 @@
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Where the state of the rate limits is kept.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RateLimitBackend {
+    /// In the memory of each replica, so each replica enforces the limits separately.
+    #[default]
+    Memory,
+    /// On the `rate_limits` table, shared by every replica.
+    Postgres,
+}
+
+impl FromStr for RateLimitBackend {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        <RateLimitBackend as ValueEnum>::from_str(value, true)
+    }
+}
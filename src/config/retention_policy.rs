@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::Date;
+
+/// Symbol matching every symbol without a policy of its own.
+const ANY_SYMBOL: &str = "*";
+
+/// Years of daily entries kept on the `financial_data` table for a group of symbols.
+///
+/// Written `SYMBOL|SYMBOL=YEARS` in environment variables, e.g. `IBM|AAPL=5` or `*=20`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionPolicy {
+    /// Symbols of the group, or `*` for every symbol without a policy of its own.
+    pub symbols: Vec<String>,
+    /// Years of daily entries kept. The monthly aggregates are kept forever.
+    pub keep_years: u32,
+}
+
+impl RetentionPolicy {
+    /// Whether the policy names `symbol` explicitly.
+    pub fn names(&self, symbol: &str) -> bool {
+        self.symbols
+            .iter()
+            .any(|name| name.trim().eq_ignore_ascii_case(symbol.trim()))
+    }
+
+    /// Whether the policy applies to every symbol without a policy of its own.
+    pub fn is_default(&self) -> bool {
+        self.names(ANY_SYMBOL)
+    }
+
+    /// First day kept on `today`: the first day of the same month `keep_years` years earlier,
+    /// so that only whole months are pruned.
+    pub fn cutoff(&self, today: Date) -> Date {
+        let year = today.year().saturating_sub_unsigned(self.keep_years);
+        Date::from_calendar_date(year, today.month(), 1).unwrap_or(Date::MIN)
+    }
+}
+
+impl FromStr for RetentionPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (symbols, keep_years) = value
+            .split_once('=')
+            .ok_or_else(|| format!("Retention policy `{}` must be `SYMBOL=YEARS`.", value))?;
+        let symbols: Vec<String> = symbols
+            .split('|')
+            .map(|symbol| symbol.trim().to_uppercase())
+            .filter(|symbol| !symbol.is_empty())
+            .collect();
+        if symbols.is_empty() {
+            return Err(format!("Retention policy `{}` has no symbol.", value));
+        }
+        let keep_years = keep_years.trim().parse().map_err(|_| {
+            format!(
+                "Retention policy `{}` must keep a whole number of years.",
+                value
+            )
+        })?;
+        Ok(RetentionPolicy {
+            symbols,
+            keep_years,
+        })
+    }
+}
@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AggregateRefreshError;
+
+impl std::fmt::Display for AggregateRefreshError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to refresh the aggregates of financial data.")
+    }
+}
+
+impl Context for AggregateRefreshError {}
@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct AlertError;
+
+impl std::fmt::Display for AlertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to evaluate or deliver alerts.")
+    }
+}
+
+impl Context for AlertError {}
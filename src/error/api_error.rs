@@ -0,0 +1,88 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use error_stack::{Context, Report};
+
+use crate::model::{ApiErrorDetail, ApiErrorResponse, ApiVersion, ErrorResponse, ResponseInfo};
+
+/// Failure of a request, answered with its status code and an error body shaped after the version of the API.
+#[derive(Debug, Clone)]
+pub struct ApiError {
+    pub status: StatusCode,
+    /// Stable identifier of the error, only sent by `v2`.
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        ApiError {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn bad_request(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::BAD_REQUEST, "invalid_request", message)
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        ApiError::new(StatusCode::NOT_FOUND, "not_found", message)
+    }
+
+    /// Answers with the `ErrorResponse` of `v1`, or the `ApiErrorResponse` of `v2`.
+    pub fn versioned(self, version: ApiVersion) -> Response {
+        match version {
+            ApiVersion::V1 => (
+                self.status,
+                Json(ErrorResponse {
+                    info: ResponseInfo {
+                        error: self.message,
+                    },
+                }),
+            )
+                .into_response(),
+            ApiVersion::V2 => self.into_response(),
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (
+            self.status,
+            Json(ApiErrorResponse {
+                error: ApiErrorDetail {
+                    code: self.code.into(),
+                    message: self.message,
+                },
+            }),
+        )
+            .into_response()
+    }
+}
+
+impl<C: Context> From<Report<C>> for ApiError {
+    /// Logs the report, along with the request ID, and hides it behind an internal error.
+    fn from(report: Report<C>) -> Self {
+        let report = match crate::telemetry::current_request_id() {
+            Some(id) => report.attach_printable(format!("Request ID: `{}`.", id)),
+            None => report,
+        };
+        log::error!("{:?}", report);
+        ApiError::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal",
+            "The request could not be processed.",
+        )
+    }
+}
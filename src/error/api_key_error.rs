@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKeyError;
+
+impl std::fmt::Display for ApiKeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to manage API keys.")
+    }
+}
+
+impl Context for ApiKeyError {}
@@ -0,0 +1,68 @@
+use axum::{
+    http::{header, HeaderValue, StatusCode},
+    response::Response,
+};
+
+use crate::model::{ApiVersion, Scope};
+
+use super::ApiError;
+
+/// Rejection of a request by the authentication layer.
+#[derive(Debug, Clone, Copy)]
+pub enum AuthError {
+    /// No API key was sent.
+    MissingKey,
+    /// The API key doesn't exist or was revoked.
+    InvalidKey,
+    /// The API key wasn't granted the scope of the endpoint.
+    MissingScope(Scope),
+    /// The API key couldn't be checked.
+    Unavailable,
+}
+
+impl std::fmt::Display for AuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthError::MissingKey => write!(
+                f,
+                "An API key is required, use the `Authorization: Bearer <key>` or `X-Api-Key` header."
+            ),
+            AuthError::InvalidKey => write!(f, "The API key is invalid or was revoked."),
+            AuthError::MissingScope(scope) => {
+                write!(f, "The API key is missing the `{}` scope.", scope)
+            }
+            AuthError::Unavailable => write!(f, "The API key could not be verified."),
+        }
+    }
+}
+
+impl AuthError {
+    /// Answers with `401 Unauthorized` and a `WWW-Authenticate` challenge, or `403 Forbidden`,
+    /// with an error body shaped after `version`.
+    pub fn into_versioned_response(self, version: ApiVersion) -> Response {
+        let (status, code) = match self {
+            AuthError::MissingKey => (StatusCode::UNAUTHORIZED, "missing_api_key"),
+            AuthError::InvalidKey => (StatusCode::UNAUTHORIZED, "invalid_api_key"),
+            AuthError::MissingScope(_) => (StatusCode::FORBIDDEN, "missing_scope"),
+            AuthError::Unavailable => (StatusCode::INTERNAL_SERVER_ERROR, "internal"),
+        };
+        let mut response = ApiError::new(status, code, self.to_string()).versioned(version);
+        if status == StatusCode::UNAUTHORIZED {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+        }
+        response
+    }
+
+    /// Answers a gRPC call with `UNAUTHENTICATED`, `PERMISSION_DENIED` or `UNAVAILABLE`.
+    pub fn into_status(self) -> tonic::Status {
+        match self {
+            AuthError::MissingKey | AuthError::InvalidKey => {
+                tonic::Status::unauthenticated(self.to_string())
+            }
+            AuthError::MissingScope(_) => tonic::Status::permission_denied(self.to_string()),
+            AuthError::Unavailable => tonic::Status::unavailable(self.to_string()),
+        }
+    }
+}
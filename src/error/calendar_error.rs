@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CalendarError;
+
+impl std::fmt::Display for CalendarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to compute the trading calendar.")
+    }
+}
+
+impl Context for CalendarError {}
@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct CommandError(pub &'static str);
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "The `{}` command has failed.", self.0)
+    }
+}
+
+impl Context for CommandError {}
@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseBackupError;
+
+impl std::fmt::Display for DatabaseBackupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to back up the database.")
+    }
+}
+
+impl Context for DatabaseBackupError {}
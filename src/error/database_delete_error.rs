@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseDeleteError;
+
+impl std::fmt::Display for DatabaseDeleteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to delete values from database.")
+    }
+}
+
+impl Context for DatabaseDeleteError {}
@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseListenError;
+
+impl std::fmt::Display for DatabaseListenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to listen for notifications from database.")
+    }
+}
+
+impl Context for DatabaseListenError {}
@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DatabaseRestoreError;
+
+impl std::fmt::Display for DatabaseRestoreError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to restore the database.")
+    }
+}
+
+impl Context for DatabaseRestoreError {}
@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct FileImportError;
+
+impl std::fmt::Display for FileImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Failed to import financial data from file.")
+    }
+}
+
+impl Context for FileImportError {}
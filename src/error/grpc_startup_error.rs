@@ -0,0 +1,12 @@
+use error_stack::Context;
+
+#[derive(Debug, Clone, Copy)]
+pub struct GrpcStartupError;
+
+impl std::fmt::Display for GrpcStartupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "gRPC server has encountered error while setting up.")
+    }
+}
+
+impl Context for GrpcStartupError {}
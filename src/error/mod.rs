@@ -1,15 +1,51 @@
+mod aggregate_refresh_error;
+pub use aggregate_refresh_error::*;
+mod alert_error;
+pub use alert_error::*;
+mod api_error;
+pub use api_error::*;
+mod api_key_error;
+pub use api_key_error::*;
+mod auth_error;
+pub use auth_error::*;
+mod calendar_error;
+pub use calendar_error::*;
+mod command_error;
+pub use command_error::*;
+mod config_error;
+pub use config_error::*;
+mod database_backup_error;
+pub use database_backup_error::*;
 mod database_connect_error;
 pub use database_connect_error::*;
+mod database_delete_error;
+pub use database_delete_error::*;
 mod database_initialization_error;
 pub use database_initialization_error::*;
+mod database_listen_error;
+pub use database_listen_error::*;
+mod database_restore_error;
+pub use database_restore_error::*;
 mod database_upsert_error;
 pub use database_upsert_error::*;
+mod export_error;
+pub use export_error::*;
+mod file_import_error;
+pub use file_import_error::*;
+mod grpc_startup_error;
+pub use grpc_startup_error::*;
 mod server_error;
 pub use server_error::*;
 mod server_startup_error;
 pub use server_startup_error::*;
+mod rate_limit_error;
+pub use rate_limit_error::*;
+mod retention_error;
+pub use retention_error::*;
 mod route_error;
 pub use route_error::*;
+mod telemetry_error;
+pub use telemetry_error::*;
 
 use axum::response::IntoResponse;
 use error_stack::Report;
@@ -36,8 +72,12 @@ where
         + 'static,
 {
     fn into_response(self) -> axum::response::Response {
-        log::error!("{:?}", self.0);
-        let inner = self.0.current_context();
+        let report = match crate::telemetry::current_request_id() {
+            Some(id) => self.0.attach_printable(format!("Request ID: `{}`.", id)),
+            None => self.0,
+        };
+        log::error!("{:?}", report);
+        let inner = report.current_context();
         (*inner).into_response()
     }
 }
@@ -0,0 +1,38 @@
+use async_graphql::{dataloader::DataLoader, EmptyMutation, EmptySubscription, Schema};
+
+use crate::{config::GraphqlConfig, model::ApiKey};
+
+use super::{LatestLoader, QueryRoot, StatisticsLoader, SummaryLoader};
+
+/// Schema of `/graphql`, which is read-only.
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Builds the schema, rejecting queries deeper or more complex than configured before running them.
+pub fn build_schema(config: &GraphqlConfig, pool: sqlx::PgPool) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(pool)
+        .limit_depth(config.max_depth)
+        .limit_complexity(config.max_complexity)
+        .finish()
+}
+
+/// Adds the data loaders, and the API key of the client if any, to a request.
+///
+/// Loaders are created for each request, so that their caches never outlive it.
+pub fn prepare_request(
+    request: async_graphql::Request,
+    pool: &sqlx::PgPool,
+    api_key: Option<ApiKey>,
+) -> async_graphql::Request {
+    let request = request
+        .data(DataLoader::new(SummaryLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(LatestLoader(pool.clone()), tokio::spawn))
+        .data(DataLoader::new(
+            StatisticsLoader(pool.clone()),
+            tokio::spawn,
+        ));
+    match api_key {
+        Some(api_key) => request.data(api_key),
+        None => request,
+    }
+}
@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::FinancialDataReport;
+
+/// Position of an entry in the `financialData` connection, which is ordered by date then symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinancialDataCursor {
+    pub date: time::Date,
+    pub symbol: String,
+}
+
+impl From<&FinancialDataReport> for FinancialDataCursor {
+    fn from(report: &FinancialDataReport) -> Self {
+        FinancialDataCursor {
+            date: report.date,
+            symbol: report.symbol.clone(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use async_graphql::{Context, Object};
+use error_stack::{IntoReport, ResultExt};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::{error::RouteError, metrics, model::FinancialDataFilters};
+
+use super::internal_error;
+
+/// Fields of the `financialData` connection besides its edges, only queried when selected.
+pub struct FinancialDataTotals {
+    pub symbol: Option<String>,
+    pub start_date: Option<time::Date>,
+    pub end_date: Option<time::Date>,
+}
+
+#[Object]
+impl FinancialDataTotals {
+    /// Number of entries matching the filters, across every page.
+    async fn total_count(&self, ctx: &Context<'_>) -> async_graphql::Result<i64> {
+        let pool = ctx.data_unchecked::<sqlx::PgPool>();
+        let mut query = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM financial_data");
+        FinancialDataFilters::new(self.symbol.clone(), self.start_date, self.end_date)
+            .push_where(&mut query);
+        metrics::time_query(
+            "graphql_count",
+            query.build_query_as::<(i64,)>().fetch_one(pool),
+        )
+        .await
+        .map(|(count,)| count)
+        .into_report()
+        .change_context(RouteError("graphql"))
+        .attach("Failed to count financial data on Postgres database.")
+        .map_err(|report| internal_error(&report))
+    }
+}
@@ -0,0 +1,10 @@
+use error_stack::{Context, Report};
+
+/// Logs the report, along with the request ID, and hides it behind an internal error.
+pub fn internal_error<C: Context>(report: &Report<C>) -> async_graphql::Error {
+    match crate::telemetry::current_request_id() {
+        Some(id) => log::error!("{:?}\nRequest ID: `{}`.", report, id),
+        None => log::error!("{:?}", report),
+    }
+    async_graphql::Error::new("The request could not be processed.")
+}
@@ -0,0 +1,49 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::Loader;
+use error_stack::{IntoReport, Report, ResultExt};
+
+use crate::{error::RouteError, metrics, model::FinancialDataReport};
+
+const LATEST_QUERY: &str = r#"
+    SELECT DISTINCT ON (symbol) *
+    FROM financial_data
+    WHERE symbol = ANY(CAST($1 AS BPCHAR[]))
+    ORDER BY symbol, date DESC;
+    "#;
+
+/// Batches the lookups of the latest entry of each symbol into a single query.
+pub struct LatestLoader(pub sqlx::PgPool);
+
+impl Loader<String> for LatestLoader {
+    type Value = FinancialDataReport;
+    type Error = Arc<Report<RouteError>>;
+
+    async fn load(
+        &self,
+        keys: &[String],
+    ) -> Result<HashMap<String, FinancialDataReport>, Self::Error> {
+        log::trace!(
+            "Querying latest entries of {} symbols from database.",
+            keys.len()
+        );
+        let reports = metrics::time_query(
+            "graphql_latest",
+            sqlx::query_as::<_, FinancialDataReport>(LATEST_QUERY)
+                .bind(keys)
+                .fetch_all(&self.0),
+        )
+        .await
+        .into_report()
+        .change_context(RouteError("graphql"))
+        .attach("Failed to query latest financial data on Postgres database.")?;
+
+        Ok(reports
+            .into_iter()
+            .map(|mut report| {
+                report.symbol = report.symbol.trim().into();
+                (report.symbol.clone(), report)
+            })
+            .collect())
+    }
+}
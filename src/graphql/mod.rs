@@ -0,0 +1,20 @@
+mod api_schema;
+pub use api_schema::*;
+mod financial_data_cursor;
+pub use financial_data_cursor::*;
+mod financial_data_totals;
+pub use financial_data_totals::*;
+mod internal_error;
+pub use internal_error::*;
+mod latest_loader;
+pub use latest_loader::*;
+mod query_root;
+pub use query_root::*;
+mod scope_guard;
+pub use scope_guard::*;
+mod statistics_loader;
+pub use statistics_loader::*;
+mod summary_loader;
+pub use summary_loader::*;
+mod symbol;
+pub use symbol::*;
@@ -0,0 +1,271 @@
+use async_graphql::{
+    connection::{self, Connection, Edge, OpaqueCursor},
+    dataloader::DataLoader,
+    Context, Object,
+};
+use error_stack::{IntoReport, ResultExt};
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::{
+    error::RouteError,
+    metrics,
+    model::{FinancialDataFilters, FinancialDataReport, Scope, StatisticsReport},
+};
+
+use super::{
+    internal_error, FinancialDataCursor, FinancialDataTotals, ScopeGuard, StatisticsKey,
+    StatisticsLoader, SummaryLoader, Symbol,
+};
+
+/// Number of nodes of a connection when neither `first` nor `last` is passed.
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// Most nodes a connection returns at once.
+const MAX_PAGE_SIZE: usize = 100;
+
+const SYMBOLS_QUERY: &str = r#"
+    SELECT symbol
+    FROM (SELECT DISTINCT TRIM(symbol) AS symbol FROM financial_data) AS symbols
+    WHERE symbol > COALESCE($1, '')
+    ORDER BY symbol
+    LIMIT $2;
+    "#;
+
+/// Number of nodes a connection returns, used to weigh its fields against the complexity limit.
+fn page_size(first: Option<i32>, last: Option<i32>) -> usize {
+    first
+        .or(last)
+        .and_then(|size| usize::try_from(size).ok())
+        .unwrap_or(DEFAULT_PAGE_SIZE)
+        .min(MAX_PAGE_SIZE)
+}
+
+/// Rejects pages larger than `MAX_PAGE_SIZE`.
+fn check_page_size(size: Option<usize>) -> async_graphql::Result<usize> {
+    match size {
+        Some(size) if size > MAX_PAGE_SIZE => Err(async_graphql::Error::new(format!(
+            "Connections return at most {} nodes.",
+            MAX_PAGE_SIZE
+        ))),
+        size => Ok(size.unwrap_or(DEFAULT_PAGE_SIZE)),
+    }
+}
+
+/// Loads the statistics of a symbol over a date range, batched with the other statistics of the request.
+pub(super) async fn statistics(
+    loader: &DataLoader<StatisticsLoader>,
+    symbol: String,
+    start_date: time::Date,
+    end_date: time::Date,
+) -> async_graphql::Result<Option<StatisticsReport>> {
+    if start_date > end_date {
+        return Err(async_graphql::Error::new(
+            "Start date must not be later than end date.",
+        ));
+    }
+    loader
+        .load_one(StatisticsKey {
+            symbol,
+            start_date,
+            end_date,
+        })
+        .await
+        .map_err(|report| internal_error(&report))
+}
+
+/// Entry point of the queries of `/graphql`.
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Entries of the time series, latest first, with the same filters as the `financial_data` endpoint.
+    /// Needs the `read:data` scope.
+    #[graphql(complexity = "page_size(first, last) * child_complexity")]
+    #[allow(clippy::too_many_arguments)]
+    async fn financial_data(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Global equity to query, every equity if not set.")] symbol: Option<
+            String,
+        >,
+        #[graphql(desc = "Filters out dates earlier than this date.")] start_date: Option<
+            time::Date,
+        >,
+        #[graphql(desc = "Filters out dates later than this date.")] end_date: Option<time::Date>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> async_graphql::Result<
+        Connection<OpaqueCursor<FinancialDataCursor>, FinancialDataReport, FinancialDataTotals>,
+    > {
+        let pool = ctx.data_unchecked::<sqlx::PgPool>();
+        let symbol = symbol.map(|symbol| symbol.trim().to_string());
+        connection::query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<OpaqueCursor<FinancialDataCursor>>,
+             before: Option<OpaqueCursor<FinancialDataCursor>>,
+             first,
+             last| async move {
+                if first.is_some() && last.is_some() {
+                    return Err(async_graphql::Error::new(
+                        "Pass either `first` or `last`, not both.",
+                    ));
+                }
+                let backward = last.is_some();
+                let size = check_page_size(first.or(last))?;
+
+                log::trace!("Querying a page of financial data from database.");
+                // Entries after the `after` cursor and before the `before` cursor, latest first,
+                // or earliest first to page backwards from the `before` cursor.
+                let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM financial_data");
+                FinancialDataFilters::new(symbol.clone(), start_date, end_date)
+                    .push_where(&mut query);
+                if let Some(after) = &after {
+                    query
+                        .push(" AND (date, symbol) < (")
+                        .push_bind(after.date)
+                        .push(", CAST(")
+                        .push_bind(after.symbol.clone())
+                        .push(" AS BPCHAR))");
+                }
+                if let Some(before) = &before {
+                    query
+                        .push(" AND (date, symbol) > (")
+                        .push_bind(before.date)
+                        .push(", CAST(")
+                        .push_bind(before.symbol.clone())
+                        .push(" AS BPCHAR))");
+                }
+                query
+                    .push(match backward {
+                        true => " ORDER BY date ASC, symbol ASC",
+                        false => " ORDER BY date DESC, symbol DESC",
+                    })
+                    .push(" LIMIT ")
+                    .push_bind(size as i64 + 1);
+                let mut reports = metrics::time_query(
+                    "graphql_financial_data",
+                    query
+                        .build_query_as::<FinancialDataReport>()
+                        .fetch_all(pool),
+                )
+                .await
+                .into_report()
+                .change_context(RouteError("graphql"))
+                .attach("Failed to query financial data on Postgres database.")
+                .map_err(|report| internal_error(&report))?;
+
+                let has_more = reports.len() > size;
+                reports.truncate(size);
+                let (has_previous_page, has_next_page) = match backward {
+                    true => {
+                        reports.reverse();
+                        (has_more, before.is_some())
+                    }
+                    false => (after.is_some(), has_more),
+                };
+
+                let mut connection = Connection::with_additional_fields(
+                    has_previous_page,
+                    has_next_page,
+                    FinancialDataTotals {
+                        symbol,
+                        start_date,
+                        end_date,
+                    },
+                );
+                connection
+                    .edges
+                    .extend(reports.into_iter().map(|mut report| {
+                        report.symbol = report.symbol.trim().into();
+                        Edge::new(OpaqueCursor(FinancialDataCursor::from(&report)), report)
+                    }));
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Global equities with entries on the time series, in alphabetical order. Needs the `read:data` scope.
+    #[graphql(complexity = "page_size(first, None) * child_complexity")]
+    async fn symbols(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        first: Option<i32>,
+    ) -> async_graphql::Result<Connection<String, Symbol>> {
+        let pool = ctx.data_unchecked::<sqlx::PgPool>();
+        connection::query(
+            after,
+            None,
+            first,
+            None,
+            |after: Option<String>, _: Option<String>, first, _| async move {
+                let size = check_page_size(first)?;
+
+                log::trace!("Querying a page of symbols from database.");
+                let mut symbols = metrics::time_query(
+                    "graphql_symbols",
+                    sqlx::query_scalar::<_, String>(SYMBOLS_QUERY)
+                        .bind(&after)
+                        .bind(size as i64 + 1)
+                        .fetch_all(pool),
+                )
+                .await
+                .into_report()
+                .change_context(RouteError("graphql"))
+                .attach("Failed to query symbols on Postgres database.")
+                .map_err(|report| internal_error(&report))?;
+
+                let has_next_page = symbols.len() > size;
+                symbols.truncate(size);
+                let mut connection = Connection::new(after.is_some(), has_next_page);
+                connection.edges.extend(
+                    symbols
+                        .into_iter()
+                        .map(|symbol| Edge::new(symbol.clone(), Symbol { symbol })),
+                );
+                Ok::<_, async_graphql::Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// A global equity, `null` if it has no entries. Needs the `read:data` scope.
+    async fn symbol(
+        &self,
+        ctx: &Context<'_>,
+        symbol: String,
+    ) -> async_graphql::Result<Option<Symbol>> {
+        let symbol = symbol.trim().to_string();
+        let summary = ctx
+            .data_unchecked::<DataLoader<SummaryLoader>>()
+            .load_one(symbol.clone())
+            .await
+            .map_err(|report| internal_error(&report))?;
+        Ok(summary.map(|_| Symbol { symbol }))
+    }
+
+    /// Averages of a global equity over a date range, `null` without entries in the range.
+    /// Needs the `read:analytics` scope.
+    #[graphql(guard = "ScopeGuard(Scope::ReadAnalytics)")]
+    async fn statistics(
+        &self,
+        ctx: &Context<'_>,
+        symbol: String,
+        start_date: time::Date,
+        end_date: time::Date,
+    ) -> async_graphql::Result<Option<StatisticsReport>> {
+        statistics(
+            ctx.data_unchecked::<DataLoader<StatisticsLoader>>(),
+            symbol.trim().to_string(),
+            start_date,
+            end_date,
+        )
+        .await
+    }
+}
@@ -0,0 +1,28 @@
+use async_graphql::{Context, Guard};
+
+use crate::model::{ApiKey, Scope};
+
+/// Denies a field to API keys which weren't granted `scope`.
+///
+/// Requests without an `ApiKey` are only let through `/graphql` when authentication is disabled,
+/// so they are granted every scope.
+pub struct ScopeGuard(pub Scope);
+
+impl Guard for ScopeGuard {
+    async fn check(&self, ctx: &Context<'_>) -> async_graphql::Result<()> {
+        match ctx.data_opt::<ApiKey>() {
+            Some(key) if !key.has_scope(self.0) => {
+                log::warn!(
+                    "API key `{}` was denied the `{}` scope.",
+                    key.prefix,
+                    self.0
+                );
+                Err(async_graphql::Error::new(format!(
+                    "The API key was not granted the `{}` scope.",
+                    self.0
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::Loader;
+use error_stack::{IntoReport, Report, ResultExt};
+
+use crate::{error::RouteError, metrics, model::StatisticsReport};
+
+const STATISTICS_QUERY: &str = r#"
+    SELECT
+        keys.symbol,
+        keys.start_date,
+        keys.end_date,
+        AVG(open_price) AS average_daily_open_price,
+        AVG(close_price) AS average_daily_close_price,
+        CAST(AVG(volume) AS FLOAT8) AS average_daily_volume
+    FROM UNNEST($1::BPCHAR[], $2::DATE[], $3::DATE[]) AS keys(symbol, start_date, end_date)
+    JOIN financial_data
+        ON financial_data.symbol = keys.symbol
+        AND financial_data.date BETWEEN keys.start_date AND keys.end_date
+    GROUP BY keys.symbol, keys.start_date, keys.end_date;
+    "#;
+
+/// Symbol and date range of a `StatisticsReport`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct StatisticsKey {
+    pub symbol: String,
+    pub start_date: time::Date,
+    pub end_date: time::Date,
+}
+
+/// Batches the statistics of every symbol and date range requested into a single query.
+pub struct StatisticsLoader(pub sqlx::PgPool);
+
+impl Loader<StatisticsKey> for StatisticsLoader {
+    type Value = StatisticsReport;
+    type Error = Arc<Report<RouteError>>;
+
+    async fn load(
+        &self,
+        keys: &[StatisticsKey],
+    ) -> Result<HashMap<StatisticsKey, StatisticsReport>, Self::Error> {
+        log::trace!("Querying {} statistics from database.", keys.len());
+        let symbols: Vec<&str> = keys.iter().map(|key| key.symbol.as_str()).collect();
+        let start_dates: Vec<time::Date> = keys.iter().map(|key| key.start_date).collect();
+        let end_dates: Vec<time::Date> = keys.iter().map(|key| key.end_date).collect();
+        let reports = metrics::time_query(
+            "graphql_statistics",
+            sqlx::query_as::<_, StatisticsReport>(STATISTICS_QUERY)
+                .bind(symbols)
+                .bind(start_dates)
+                .bind(end_dates)
+                .fetch_all(&self.0),
+        )
+        .await
+        .into_report()
+        .change_context(RouteError("graphql"))
+        .attach("Failed to query statistics on Postgres database.")?;
+
+        Ok(reports
+            .into_iter()
+            .map(|report| {
+                let key = StatisticsKey {
+                    symbol: report.symbol.clone(),
+                    start_date: report.start_date,
+                    end_date: report.end_date,
+                };
+                (key, report)
+            })
+            .collect())
+    }
+}
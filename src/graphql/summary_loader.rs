@@ -0,0 +1,43 @@
+use std::{collections::HashMap, sync::Arc};
+
+use async_graphql::dataloader::Loader;
+use error_stack::{IntoReport, Report, ResultExt};
+
+use crate::{error::RouteError, metrics, model::SymbolSummary};
+
+const SUMMARY_QUERY: &str = r#"
+    SELECT TRIM(symbol) AS symbol, MIN(date) AS first_date, MAX(date) AS last_date, COUNT(*) AS entries
+    FROM financial_data
+    WHERE symbol = ANY(CAST($1 AS BPCHAR[]))
+    GROUP BY symbol;
+    "#;
+
+/// Batches the lookups of the extent of the time series of each symbol into a single query.
+pub struct SummaryLoader(pub sqlx::PgPool);
+
+impl Loader<String> for SummaryLoader {
+    type Value = SymbolSummary;
+    type Error = Arc<Report<RouteError>>;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, SymbolSummary>, Self::Error> {
+        log::trace!(
+            "Querying summaries of {} symbols from database.",
+            keys.len()
+        );
+        let summaries = metrics::time_query(
+            "graphql_summaries",
+            sqlx::query_as::<_, SymbolSummary>(SUMMARY_QUERY)
+                .bind(keys)
+                .fetch_all(&self.0),
+        )
+        .await
+        .into_report()
+        .change_context(RouteError("graphql"))
+        .attach("Failed to query symbol summaries on Postgres database.")?;
+
+        Ok(summaries
+            .into_iter()
+            .map(|summary| (summary.symbol.clone(), summary))
+            .collect())
+    }
+}
@@ -0,0 +1,77 @@
+use async_graphql::{dataloader::DataLoader, Context, Object};
+
+use crate::model::{FinancialDataReport, Scope, StatisticsReport, SymbolSummary};
+
+use super::{
+    internal_error, statistics, LatestLoader, ScopeGuard, StatisticsLoader, SummaryLoader,
+};
+
+/// Global equity with entries on the time series.
+///
+/// Nested fields are loaded in batches, with one query per field for every symbol of a response.
+pub struct Symbol {
+    pub symbol: String,
+}
+
+impl Symbol {
+    async fn summary(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<SymbolSummary>> {
+        ctx.data_unchecked::<DataLoader<SummaryLoader>>()
+            .load_one(self.symbol.clone())
+            .await
+            .map_err(|report| internal_error(&report))
+    }
+}
+
+#[Object]
+impl Symbol {
+    /// Name of the equity.
+    async fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Date of the earliest entry.
+    async fn first_date(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<time::Date>> {
+        Ok(self.summary(ctx).await?.map(|summary| summary.first_date))
+    }
+
+    /// Date of the latest entry.
+    async fn last_date(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<time::Date>> {
+        Ok(self.summary(ctx).await?.map(|summary| summary.last_date))
+    }
+
+    /// Number of entries.
+    async fn entries(&self, ctx: &Context<'_>) -> async_graphql::Result<i64> {
+        Ok(self
+            .summary(ctx)
+            .await?
+            .map_or(0, |summary| summary.entries))
+    }
+
+    /// Latest entry.
+    async fn latest(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<FinancialDataReport>> {
+        ctx.data_unchecked::<DataLoader<LatestLoader>>()
+            .load_one(self.symbol.clone())
+            .await
+            .map_err(|report| internal_error(&report))
+    }
+
+    /// Averages over a date range, `null` without entries in the range. Needs the `read:analytics` scope.
+    #[graphql(guard = "ScopeGuard(Scope::ReadAnalytics)")]
+    async fn statistics(
+        &self,
+        ctx: &Context<'_>,
+        start_date: time::Date,
+        end_date: time::Date,
+    ) -> async_graphql::Result<Option<StatisticsReport>> {
+        statistics(
+            ctx.data_unchecked::<DataLoader<StatisticsLoader>>(),
+            self.symbol.clone(),
+            start_date,
+            end_date,
+        )
+        .await
+    }
+}
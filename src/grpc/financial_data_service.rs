@@ -0,0 +1,218 @@
+use error_stack::{IntoReport, Report, ResultExt};
+use futures::TryStreamExt;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{metadata::MetadataMap, Request, Response, Status};
+
+use crate::{
+    auth::{self, API_KEY_HEADER},
+    error::{AuthError, RouteError},
+    metrics,
+    model::{Scope, SymbolSummary},
+    routes::{fetch_financial_data, financial_data_query, query_statistics},
+};
+
+use super::{
+    parse_date,
+    proto::{
+        financial_data_server::FinancialData, Bar, GetBarsRequest, GetStatisticsRequest,
+        ListSymbolsRequest, ListSymbolsResponse, Statistics,
+    },
+};
+
+/// Number of bars buffered between the database cursor and the gRPC stream.
+const STREAM_BUFFER: usize = 64;
+
+const SYMBOLS_QUERY: &str = r#"
+    SELECT TRIM(symbol) AS symbol, MIN(date) AS first_date, MAX(date) AS last_date, COUNT(*) AS entries
+    FROM financial_data
+    WHERE symbol IS NOT NULL
+    GROUP BY symbol
+    ORDER BY symbol;
+    "#;
+
+/// Gets the API key sent with a call, from the `authorization` or the `x-api-key` metadata.
+fn presented_key(metadata: &MetadataMap) -> Option<&str> {
+    metadata
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| {
+            metadata
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+        })
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// Logs the report, and hides it behind an `INTERNAL` status.
+fn internal(report: Report<RouteError>) -> Status {
+    log::error!("{:?}", report);
+    Status::internal("The request could not be processed.")
+}
+
+/// Counts a call, by method and status code.
+fn record<T>(method: &str, result: &Result<T, Status>) {
+    let code = match result {
+        Ok(_) => tonic::Code::Ok,
+        Err(status) => status.code(),
+    };
+    metrics::GRPC_REQUESTS
+        .with_label_values(&[method, &format!("{:?}", code)])
+        .inc();
+}
+
+/// Implementation of the `FinancialData` gRPC service, sharing its queries with the HTTP endpoints.
+pub struct FinancialDataService {
+    pool: sqlx::PgPool,
+    auth_enabled: bool,
+}
+
+impl FinancialDataService {
+    pub fn new(pool: sqlx::PgPool, auth_enabled: bool) -> Self {
+        FinancialDataService { pool, auth_enabled }
+    }
+
+    /// Rejects calls without a valid API key granted `scope`, unless authentication is disabled.
+    async fn authorize<T>(&self, request: &Request<T>, scope: Scope) -> Result<(), Status> {
+        if !self.auth_enabled {
+            return Ok(());
+        }
+        auth::authorize_key(&self.pool, presented_key(request.metadata()), scope)
+            .await
+            .map(|_| ())
+            .map_err(AuthError::into_status)
+    }
+
+    async fn bars(
+        &self,
+        request: Request<GetBarsRequest>,
+    ) -> Result<Response<ReceiverStream<Result<Bar, Status>>>, Status> {
+        self.authorize(&request, Scope::ReadData).await?;
+        let GetBarsRequest {
+            symbol,
+            start_date,
+            end_date,
+        } = request.into_inner();
+        let symbol = symbol.map(|symbol| symbol.trim().to_string());
+        let start_date = start_date
+            .map(|date| parse_date(date, "start_date"))
+            .transpose()?;
+        let end_date = end_date
+            .map(|date| parse_date(date, "end_date"))
+            .transpose()?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(STREAM_BUFFER);
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            log::trace!("Streaming bars from database.");
+            let mut query = financial_data_query(symbol, start_date, end_date, None);
+            let mut rows = fetch_financial_data(&pool, &mut query);
+            loop {
+                let bar = match rows.try_next().await {
+                    Ok(Some(row)) => Ok(Bar::from(row)),
+                    Ok(None) => break,
+                    Err(err) => {
+                        log::error!("Failed to stream bars from database: {}", err);
+                        Err(Status::internal("The request could not be processed."))
+                    }
+                };
+                let failed = bar.is_err();
+                // The client cancelled the call, or the stream has failed and must be aborted.
+                if sender.send(bar).await.is_err() || failed {
+                    break;
+                }
+            }
+            log::trace!("Finished streaming bars.");
+        });
+        Ok(Response::new(ReceiverStream::new(receiver)))
+    }
+
+    async fn statistics(
+        &self,
+        request: Request<GetStatisticsRequest>,
+    ) -> Result<Response<Statistics>, Status> {
+        self.authorize(&request, Scope::ReadAnalytics).await?;
+        let GetStatisticsRequest {
+            symbol,
+            start_date,
+            end_date,
+        } = request.into_inner();
+        let required = |date: Option<_>, field| {
+            date.ok_or_else(|| Status::invalid_argument(format!("`{}` is required.", field)))
+                .and_then(|date| parse_date(date, field))
+        };
+        let start_date = required(start_date, "start_date")?;
+        let end_date = required(end_date, "end_date")?;
+        if start_date > end_date {
+            return Err(Status::invalid_argument(
+                "Start date must not be later than end date.",
+            ));
+        }
+
+        query_statistics(&self.pool, symbol.trim().into(), start_date, end_date)
+            .await
+            .map_err(internal)?
+            .map(|report| Response::new(Statistics::from(report)))
+            .ok_or_else(|| {
+                Status::not_found("The equity has no entries in the date range, verify the symbol.")
+            })
+    }
+
+    async fn symbols(
+        &self,
+        request: Request<ListSymbolsRequest>,
+    ) -> Result<Response<ListSymbolsResponse>, Status> {
+        self.authorize(&request, Scope::ReadData).await?;
+
+        log::trace!("Querying symbols from database.");
+        let summaries = metrics::time_query(
+            "grpc_symbols",
+            sqlx::query_as::<_, SymbolSummary>(SYMBOLS_QUERY).fetch_all(&self.pool),
+        )
+        .await
+        .into_report()
+        .change_context(RouteError("grpc"))
+        .attach("Failed to query symbols on Postgres database.")
+        .map_err(internal)?;
+
+        Ok(Response::new(ListSymbolsResponse {
+            symbols: summaries.into_iter().map(Into::into).collect(),
+        }))
+    }
+}
+
+#[tonic::async_trait]
+impl FinancialData for FinancialDataService {
+    type GetBarsStream = ReceiverStream<Result<Bar, Status>>;
+
+    async fn get_bars(
+        &self,
+        request: Request<GetBarsRequest>,
+    ) -> Result<Response<Self::GetBarsStream>, Status> {
+        log::trace!("Received `GetBars` call.");
+        let response = self.bars(request).await;
+        record("GetBars", &response);
+        response
+    }
+
+    async fn get_statistics(
+        &self,
+        request: Request<GetStatisticsRequest>,
+    ) -> Result<Response<Statistics>, Status> {
+        log::trace!("Received `GetStatistics` call.");
+        let response = self.statistics(request).await;
+        record("GetStatistics", &response);
+        response
+    }
+
+    async fn list_symbols(
+        &self,
+        request: Request<ListSymbolsRequest>,
+    ) -> Result<Response<ListSymbolsResponse>, Status> {
+        log::trace!("Received `ListSymbols` call.");
+        let response = self.symbols(request).await;
+        record("ListSymbols", &response);
+        response
+    }
+}
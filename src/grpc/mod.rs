@@ -0,0 +1,10 @@
+/// Messages and service generated from `proto/financial_data.proto`.
+#[allow(clippy::all)]
+pub mod proto {
+    tonic::include_proto!("financial_data.v1");
+}
+
+mod financial_data_service;
+pub use financial_data_service::*;
+mod proto_conversions;
+pub use proto_conversions::*;
@@ -0,0 +1,64 @@
+use tonic::Status;
+
+use crate::model::{FinancialDataReport, StatisticsReport, SymbolSummary};
+
+use super::proto;
+
+impl From<time::Date> for proto::Date {
+    fn from(date: time::Date) -> Self {
+        proto::Date {
+            year: date.year(),
+            month: u8::from(date.month()).into(),
+            day: date.day().into(),
+        }
+    }
+}
+
+/// Reads a date of a request, answering `INVALID_ARGUMENT` if it doesn't exist.
+pub fn parse_date(date: proto::Date, field: &str) -> Result<time::Date, Status> {
+    let month = u8::try_from(date.month)
+        .ok()
+        .and_then(|month| time::Month::try_from(month).ok());
+    let day = u8::try_from(date.day).ok();
+    match (month, day) {
+        (Some(month), Some(day)) => time::Date::from_calendar_date(date.year, month, day).ok(),
+        _ => None,
+    }
+    .ok_or_else(|| Status::invalid_argument(format!("`{}` is not a valid date.", field)))
+}
+
+impl From<FinancialDataReport> for proto::Bar {
+    fn from(report: FinancialDataReport) -> Self {
+        proto::Bar {
+            symbol: report.symbol,
+            date: Some(report.date.into()),
+            open_price: report.open_price,
+            close_price: report.close_price,
+            volume: report.volume,
+        }
+    }
+}
+
+impl From<StatisticsReport> for proto::Statistics {
+    fn from(report: StatisticsReport) -> Self {
+        proto::Statistics {
+            symbol: report.symbol,
+            start_date: Some(report.start_date.into()),
+            end_date: Some(report.end_date.into()),
+            average_daily_open_price: report.average_daily_open_price,
+            average_daily_close_price: report.average_daily_close_price,
+            average_daily_volume: report.average_daily_volume,
+        }
+    }
+}
+
+impl From<SymbolSummary> for proto::SymbolSummary {
+    fn from(summary: SymbolSummary) -> Self {
+        proto::SymbolSummary {
+            symbol: summary.symbol,
+            first_date: Some(summary.first_date.into()),
+            last_date: Some(summary.last_date.into()),
+            entries: summary.entries,
+        }
+    }
+}
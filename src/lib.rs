@@ -1,4 +1,16 @@
+pub mod auth;
+pub mod cache;
+pub mod calendar;
+pub mod cli;
+pub mod config;
 pub mod error;
+pub mod graphql;
+pub mod grpc;
+pub mod metrics;
 pub mod model;
+pub mod openapi;
+pub mod rate_limit;
 pub mod routes;
-pub mod tasks;
\ No newline at end of file
+pub mod tasks;
+pub mod telemetry;
+pub mod versioning;
@@ -1,47 +1,101 @@
+use clap::Parser;
 use error_stack::{IntoReport, Result, ResultExt};
+use tokio_util::sync::CancellationToken;
 
+mod auth;
+mod cache;
+mod calendar;
+mod cli;
+use cli::{Cli, Command};
+mod config;
+use config::Config;
 mod error;
-use error::ServerError;
+use error::{CommandError, ServerError};
+mod graphql;
+mod grpc;
+mod metrics;
 mod model;
+mod openapi;
+mod rate_limit;
 mod routes;
 mod tasks;
+mod telemetry;
+mod versioning;
 
-async fn run_server() -> Result<(), ServerError> {
-    log::trace!("Getting environment variables");
-    let database_url = std::env::var("DATABASE_URL")
-        .into_report()
-        .change_context(ServerError)
-        .attach(
-            "Failed to get environment variable `DATABASE_URL`, needed to connect to database.",
-        )?;
-    let api_key = std::env::var("ALPHA_VANTAGE_API_KEY")
-        .into_report()
-        .change_context(ServerError)
-        .attach("Environment variable `ALPHA_VANTAGE_API_KEY` is not set")?;
+/// Number of updates buffered for each client of the `stream` endpoint.
+const UPDATES_CAPACITY: usize = 1024;
+
+async fn run_server(config: Config) -> Result<(), ServerError> {
+    let api_key = config.api_key().change_context(ServerError)?.to_string();
 
     log::trace!("Connecting to database");
-    let pool = tasks::connect_to_database(&database_url)
+    let pool = tasks::connect_to_database(&config.database)
         .await
         .change_context(ServerError)?;
 
     log::trace!("Creating table");
-    tasks::create_table_if_not_exists(pool.clone())
+    tasks::create_table_if_not_exists(pool.clone(), &config.database.schema_path)
         .await
         .change_context(ServerError)
         .attach("Failed to create table on Postgres database.")?;
 
+    log::trace!("Listening for shutdown signals");
+    let shutdown = CancellationToken::new();
+    tokio::spawn(tasks::wait_for_shutdown_signal(shutdown.clone()));
+
     log::trace!("Creating recurring task");
-    let (task_send, task_recv) = tokio::sync::oneshot::channel::<()>();
-    let upsert_task = tokio::spawn(tasks::recurring_get_raw_data(
+    let upsert_task = spawn_until_shutdown(
+        shutdown.clone(),
+        tasks::recurring_get_raw_data(
+            pool.clone(),
+            shutdown.clone(),
+            api_key,
+            config.ingestion.interval_days,
+            config.calendar.clone(),
+            config.ingestion.gap_fill_days,
+        ),
+    );
+    log::trace!("Creating update listener task");
+    let (updates, _) = tokio::sync::broadcast::channel(UPDATES_CAPACITY);
+    let listen_task = spawn_until_shutdown(
+        shutdown.clone(),
+        tasks::listen_for_updates(pool.clone(), updates.clone(), shutdown.clone()),
+    );
+    log::trace!("Creating aggregate refresh task");
+    tokio::spawn(tasks::refresh_aggregates_on_updates(
         pool.clone(),
-        task_recv,
-        api_key,
-        1,
+        updates.subscribe(),
+        shutdown.clone(),
     ));
+    if config.retention.enabled {
+        log::trace!("Creating retention task");
+        tokio::spawn(tasks::recurring_retention(
+            pool.clone(),
+            config.retention.clone(),
+            shutdown.clone(),
+        ));
+    }
+    log::trace!("Starting up gRPC server.");
+    let grpc_task = config.grpc.enabled.then(|| {
+        spawn_until_shutdown(
+            shutdown.clone(),
+            tasks::grpc_startup(config.clone(), pool.clone(), shutdown.clone()),
+        )
+    });
     log::trace!("Starting up server.");
-    let server_task = tokio::spawn(tasks::server_startup(pool.clone(), task_send));
+    let server_task = spawn_until_shutdown(
+        shutdown.clone(),
+        tasks::server_startup(config, pool.clone(), shutdown.clone(), updates),
+    );
+
+    let (upsert_res, listen_res, server_res) = tokio::join!(upsert_task, listen_task, server_task);
+    let grpc_res = match grpc_task {
+        Some(grpc_task) => Some(grpc_task.await),
+        None => None,
+    };
 
-    let (upsert_res, server_res) = tokio::join!(upsert_task, server_task);
+    log::trace!("Closing database connections.");
+    pool.close().await;
 
     upsert_res
         .into_report()
@@ -49,21 +103,71 @@ async fn run_server() -> Result<(), ServerError> {
         .attach("Failed to join Upsert task.")?
         .change_context(ServerError)
         .attach("Upsert task returned with an error.")?;
-    server_res
+    listen_res
         .into_report()
         .change_context(ServerError)
-        .attach("Failed to join Upsert task.")?
+        .attach("Failed to join update listener task.")?
         .change_context(ServerError)
+        .attach("Update listener task returned with an error.")?;
+    server_res
+        .into_report()
+        .change_context(ServerError)
+        .attach("Failed to join server task.")?
+        .change_context(ServerError)?;
+    if let Some(grpc_res) = grpc_res {
+        grpc_res
+            .into_report()
+            .change_context(ServerError)
+            .attach("Failed to join gRPC server task.")?
+            .change_context(ServerError)?;
+    }
+
+    log::info!("Shut down cleanly.");
+    Ok(())
+}
+
+/// Spawns a task that cancels `shutdown` when it exits, so that the failure of one task stops the others.
+fn spawn_until_shutdown<F>(
+    shutdown: CancellationToken,
+    task: F,
+) -> tokio::task::JoinHandle<F::Output>
+where
+    F: std::future::Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(async move {
+        let _guard = shutdown.drop_guard();
+        task.await
+    })
 }
 
-fn main() -> Result<(), ServerError> {
-    pretty_env_logger::init();
+fn main() -> Result<(), CommandError> {
+    let cli = Cli::parse();
     let runtime = tokio::runtime::Builder::new_multi_thread()
         .enable_all()
         .build()
         .into_report()
-        .change_context(ServerError)
+        .change_context(CommandError("startup"))
         .attach("Failed to build Tokio runtime.")?;
+    let config = Config::load(&cli.config).change_context(CommandError("startup"))?;
+    let telemetry = {
+        let _runtime = runtime.enter();
+        telemetry::init_telemetry(&config.logging).change_context(CommandError("startup"))?
+    };
 
-    runtime.block_on(run_server())
+    let result = match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => runtime
+            .block_on(run_server(config))
+            .change_context(CommandError("serve")),
+        Command::Import(args) => runtime.block_on(cli::run_import(config, args)),
+        Command::Export(args) => runtime.block_on(cli::run_export(config, args)),
+        Command::ExportDb(args) => runtime.block_on(cli::run_export_db(config, args)),
+        Command::ImportDb(args) => runtime.block_on(cli::run_import_db(config, args)),
+        Command::Config(command) => cli::run_config(config, command),
+        Command::ApiKeys(command) => runtime.block_on(cli::run_api_keys(config, command)),
+        Command::Retention(args) => runtime.block_on(cli::run_retention(config, args)),
+        Command::Healthcheck(args) => runtime.block_on(cli::run_healthcheck(config, args)),
+    };
+    telemetry.shutdown();
+    result
 }
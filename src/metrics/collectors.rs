@@ -0,0 +1,213 @@
+use std::{future::Future, sync::LazyLock, time::Instant};
+
+use prometheus::{
+    HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+};
+use tracing::Instrument;
+
+/// Registry of every metric exposed on the `metrics` endpoint.
+pub static REGISTRY: LazyLock<Registry> = LazyLock::new(Registry::new);
+
+/// Buckets, in seconds, of the latency histograms.
+const LATENCY_BUCKETS: [f64; 12] = [
+    0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1., 2.5, 10.,
+];
+
+/// Buckets, in seconds, of the ingestion run histogram.
+const INGESTION_BUCKETS: [f64; 8] = [0.5, 1., 2.5, 5., 10., 30., 60., 300.];
+
+/// Registers a collector, which only fails if a metric with the same name was already registered.
+fn register<T: prometheus::core::Collector + Clone + 'static>(collector: T) -> T {
+    REGISTRY
+        .register(Box::new(collector.clone()))
+        .expect("Metric names must be unique.");
+    collector
+}
+
+pub static HTTP_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled."),
+            &["method", "route", "status"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static HTTP_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to produce HTTP responses.",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+            &["method", "route", "status"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static GRPC_REQUESTS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new("grpc_requests_total", "gRPC calls handled."),
+            &["method", "code"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static DB_POOL_CONNECTIONS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Connections of the database pool, by state.",
+            ),
+            &["state"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static DB_POOL_MAX_CONNECTIONS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register(
+        IntGauge::new(
+            "db_pool_max_connections",
+            "Maximum number of connections of the database pool.",
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static DB_QUERY_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "db_query_duration_seconds",
+                "Time taken by database queries.",
+            )
+            .buckets(LATENCY_BUCKETS.to_vec()),
+            &["query"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static INGESTION_RUN_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register(
+        HistogramVec::new(
+            HistogramOpts::new(
+                "ingestion_run_duration_seconds",
+                "Time taken by runs of the recurring task, by outcome.",
+            )
+            .buckets(INGESTION_BUCKETS.to_vec()),
+            &["outcome"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static ROWS_UPSERTED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "ingestion_rows_upserted_total",
+                "New or changed rows saved into the database.",
+            ),
+            &["symbol"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static MISSING_TRADING_DAYS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "ingestion_missing_trading_days",
+                "Trading days of the gap fill window that Alpha Vantage did not provide on the last run.",
+            ),
+            &["symbol"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static PROVIDER_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "provider_errors_total",
+                "Failed queries of Alpha Vantage, by kind.",
+            ),
+            &["kind"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static DATA_FRESHNESS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register(
+        IntGaugeVec::new(
+            Opts::new(
+                "data_freshness_seconds",
+                "Time since the latest date saved for each symbol.",
+            ),
+            &["symbol"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static RATE_LIMITED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "rate_limited_requests_total",
+                "Requests rejected by the rate limits, by reason.",
+            ),
+            &["reason"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static CACHE_LOOKUPS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "cache_lookups_total",
+                "Lookups of the response cache, by endpoint and result.",
+            ),
+            &["endpoint", "result"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+pub static ROWS_PRUNED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register(
+        IntCounterVec::new(
+            Opts::new(
+                "retention_rows_pruned_total",
+                "Rows deleted by the retention policies.",
+            ),
+            &["symbol"],
+        )
+        .expect("Metric options must be valid."),
+    )
+});
+
+/// Runs a database query inside a `db.query` span, recording the time it took under the `query` label.
+pub async fn time_query<F: Future>(query: &str, future: F) -> F::Output {
+    let started = Instant::now();
+    let output = future
+        .instrument(tracing::info_span!("db.query", query))
+        .await;
+    DB_QUERY_DURATION
+        .with_label_values(&[query])
+        .observe(started.elapsed().as_secs_f64());
+    output
+}
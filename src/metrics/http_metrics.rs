@@ -0,0 +1,29 @@
+use std::time::Instant;
+
+use axum::{extract::MatchedPath, http::Request, middleware::Next, response::Response};
+
+use super::{HTTP_REQUESTS, HTTP_REQUEST_DURATION};
+
+/// Middleware recording the count and latency of HTTP requests, by method, route and status.
+///
+/// Must be added with `Router::route_layer`, so that the matched route is known.
+/// Labels use the route rather than the path, so that path parameters don't create new series.
+pub async fn track_http_metrics<B>(request: Request<B>, next: Next<B>) -> Response {
+    let started = Instant::now();
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+
+    let response = next.run(request).await;
+
+    let status = response.status().as_u16().to_string();
+    let labels = [method.as_str(), route.as_str(), status.as_str()];
+    HTTP_REQUESTS.with_label_values(&labels).inc();
+    HTTP_REQUEST_DURATION
+        .with_label_values(&labels)
+        .observe(started.elapsed().as_secs_f64());
+    response
+}
@@ -0,0 +1,4 @@
+mod collectors;
+pub use collectors::*;
+mod http_metrics;
+pub use http_metrics::*;
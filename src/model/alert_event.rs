@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// Trigger of an alert rule by an entry of the time series.
+///
+/// `value` is the price, percentage, or volume ratio that triggered the rule.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AlertEvent {
+    pub id: i32,
+    pub rule_id: i32,
+    pub symbol: String,
+    pub date: time::Date,
+    pub value: f64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub triggered_at: time::OffsetDateTime,
+}
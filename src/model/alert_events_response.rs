@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{AlertEvent, ResponseInfo};
+
+/// Response returned from the endpoint listing the events of an alert rule.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AlertEventsResponse {
+    pub data: Vec<AlertEvent>,
+    pub info: ResponseInfo,
+}
@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Condition that triggers an alert rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type, ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+pub enum AlertKind {
+    /// Closing price crosses the threshold price, in either direction, since the previous close.
+    PriceCross,
+    /// Closing price moves more than the threshold percentage since the previous close.
+    DailyMove,
+    /// Volume is over the threshold times the average volume of the previous 20 entries.
+    VolumeSpike,
+}
@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+use super::AlertKind;
+
+/// Alert rule evaluated against new or corrected entries of a global equity.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AlertRule {
+    pub id: i32,
+    pub symbol: String,
+    pub kind: AlertKind,
+    pub threshold: f64,
+    pub webhook_url: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: time::OffsetDateTime,
+}
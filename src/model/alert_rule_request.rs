@@ -0,0 +1,120 @@
+use std::net::IpAddr;
+
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::AlertKind;
+
+/// Body of requests creating or replacing an alert rule.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AlertRuleRequest {
+    pub symbol: String,
+    pub kind: AlertKind,
+    pub threshold: f64,
+    pub webhook_url: String,
+}
+
+impl AlertRuleRequest {
+    /// Verifies the values of the rule, returning a message describing the first invalid value.
+    pub fn validate(&self) -> Option<String> {
+        if self.symbol.trim().is_empty() || self.symbol.trim().len() > 8 {
+            return Some("Symbol must have between 1 and 8 characters.".into());
+        }
+        if !self.threshold.is_finite() || self.threshold <= 0. {
+            return Some("Threshold must be a positive number.".into());
+        }
+        let url = match reqwest::Url::parse(&self.webhook_url) {
+            Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+            _ => return Some("Webhook URL must be a valid HTTP or HTTPS URL.".into()),
+        };
+        let host = url.host_str().unwrap_or_default();
+        // IPv6 hosts are written within brackets.
+        let public = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+            Ok(ip) => is_public_address(ip),
+            Err(_) => {
+                let domain = host.trim_end_matches('.').to_ascii_lowercase();
+                !domain.is_empty() && domain != "localhost" && !domain.ends_with(".localhost")
+            }
+        };
+        (!public).then(|| {
+            "Webhook URL must not target a loopback, private or link-local address.".into()
+        })
+    }
+}
+
+/// Checks that an address is reachable on the internet, rather than on the host or its private networks,
+/// so that webhooks can't be used to reach internal services.
+pub(crate) fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || first == 0
+                // Shared address space of carrier-grade NATs, 100.64.0.0/10.
+                || (first == 100 && (64..128).contains(&second)))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_address(ip.into()),
+            None => {
+                !(ip.is_unspecified()
+                    || ip.is_loopback()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(webhook_url: &str) -> AlertRuleRequest {
+        AlertRuleRequest {
+            symbol: "IBM".into(),
+            kind: AlertKind::DailyMove,
+            threshold: 5.,
+            webhook_url: webhook_url.into(),
+        }
+    }
+
+    #[test]
+    fn public_webhooks_are_accepted() {
+        for url in [
+            "https://example.com/hook",
+            "http://93.184.216.34:8080/hook",
+            "https://[2606:2800:220:1:248:1893:25c8:1946]/hook",
+        ] {
+            assert_eq!(request(url).validate(), None, "{}", url);
+        }
+    }
+
+    #[test]
+    fn internal_webhooks_are_refused() {
+        for url in [
+            "ftp://example.com/hook",
+            "http://localhost:8080/hook",
+            "http://api.localhost./hook",
+            "http://127.0.0.1/hook",
+            "http://0.0.0.0/hook",
+            "http://10.1.2.3/hook",
+            "http://172.16.0.1/hook",
+            "http://192.168.1.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/hook",
+            "http://[::1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+        ] {
+            assert!(request(url).validate().is_some(), "{}", url);
+        }
+    }
+}
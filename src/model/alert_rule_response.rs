@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{AlertRule, ResponseInfo};
+
+/// Response returned from the endpoints handling a single alert rule.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AlertRuleResponse {
+    pub data: Option<AlertRule>,
+    pub info: ResponseInfo,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{AlertRule, ResponseInfo};
+
+/// Response returned from the endpoint listing alert rules.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AlertRulesResponse {
+    pub data: Vec<AlertRule>,
+    pub info: ResponseInfo,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Reason a request to a `v2` endpoint failed.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiErrorDetail {
+    /// Stable identifier of the error, e.g. `not_found`, for clients to match on.
+    pub code: String,
+    /// Human readable description of the error.
+    pub message: String,
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::ApiErrorDetail;
+
+/// Response returned when a request to a `v2` endpoint fails.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ApiErrorResponse {
+    pub error: ApiErrorDetail,
+}
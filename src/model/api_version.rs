@@ -0,0 +1,20 @@
+/// Version of the API a request was routed to, added to the extensions of the request by the versioned routers.
+///
+/// Requests outside of a versioned router are answered like `V1`, which is the shape of the unversioned `/api` routes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ApiVersion {
+    #[default]
+    V1,
+    V2,
+}
+
+impl ApiVersion {
+    /// Version of the router that matched a request.
+    pub fn of<B>(request: &axum::http::Request<B>) -> ApiVersion {
+        request
+            .extensions()
+            .get::<ApiVersion>()
+            .copied()
+            .unwrap_or_default()
+    }
+}
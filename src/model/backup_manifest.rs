@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+
+use super::BackupTable;
+
+/// `manifest.json` of the archives written by the `export-db` command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    /// Layout of the archive, only restored by the versions of the application that write the same one.
+    pub format_version: u32,
+    /// SHA-256 of the `schema.sql` applied to the backed up database.
+    pub schema_version: String,
+    /// Version of the application that wrote the archive.
+    pub app_version: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: time::OffsetDateTime,
+    /// Tables of the archive, in the order they are restored.
+    pub tables: Vec<BackupTable>,
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+
+/// Table saved on an archive of the `export-db` command, as a file holding a JSON object per row.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupTable {
+    pub name: String,
+    /// Name of the file of the archive holding the rows.
+    pub file: String,
+    /// Columns of the table when it was backed up, which must exist on the restored table.
+    pub columns: Vec<String>,
+    pub rows: u64,
+}
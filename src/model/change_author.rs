@@ -0,0 +1,25 @@
+use super::ApiKey;
+
+/// Who changed the time series, recorded along each change on the `financial_data_audit` table.
+#[derive(Debug, Clone)]
+pub struct ChangeAuthor {
+    /// Key used for the change, `None` when authentication is disabled.
+    pub api_key_id: Option<i32>,
+    pub name: String,
+}
+
+impl ChangeAuthor {
+    /// Identifies the author from the API key of the request, named by the key name and prefix.
+    pub fn from_api_key(api_key: Option<&ApiKey>) -> Self {
+        match api_key {
+            Some(key) => ChangeAuthor {
+                api_key_id: Some(key.id),
+                name: format!("{} ({})", key.name, key.prefix),
+            },
+            None => ChangeAuthor {
+                api_key_id: None,
+                name: "anonymous".into(),
+            },
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{
+    AlertEvent, AlertRule, FinancialDataReport, StatisticsReport, StatusReport, SymbolGaps,
+};
+
+/// Successful response of the `v2` endpoints. Errors are answered with an `ApiErrorResponse` instead.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[aliases(
+    FinancialDataEntryData = DataResponse<FinancialDataReport>,
+    FinancialDataEntriesData = DataResponse<Vec<FinancialDataReport>>,
+    StatisticsData = DataResponse<StatisticsReport>,
+    AlertRuleData = DataResponse<AlertRule>,
+    AlertRulesData = DataResponse<Vec<AlertRule>>,
+    AlertEventsData = DataResponse<Vec<AlertEvent>>,
+    StatusData = DataResponse<StatusReport>,
+    SymbolGapsData = DataResponse<SymbolGaps>,
+)]
+pub struct DataResponse<T> {
+    pub data: T,
+}
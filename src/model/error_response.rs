@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::ResponseInfo;
+
+/// Type representing the response returned when a request is rejected before reaching an endpoint.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub info: ResponseInfo,
+}
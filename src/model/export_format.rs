@@ -0,0 +1,30 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+/// Columnar format of the `financial_data` exports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, clap::ValueEnum, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// Apache Arrow IPC stream.
+    Arrow,
+    /// Apache Parquet file.
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Media type of the format.
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Arrow => "application/vnd.apache.arrow.stream",
+            ExportFormat::Parquet => "application/vnd.apache.parquet",
+        }
+    }
+
+    /// File extension of the format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Arrow => "arrows",
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
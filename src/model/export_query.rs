@@ -0,0 +1,25 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use super::{split_symbols, ExportFormat};
+
+/// Values extracted from the URL query of the `export` endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    /// Comma separated global equities to export, every equity if not set.
+    pub symbols: Option<String>,
+    /// Filters out dates earlier than this date.
+    pub start_date: Option<time::Date>,
+    /// Filters out dates later than this date.
+    pub end_date: Option<time::Date>,
+    /// Format of the file. Defaults to `arrow`.
+    pub format: Option<ExportFormat>,
+}
+
+impl ExportQuery {
+    /// Splits the comma separated `symbols` parameter.
+    pub fn symbol_list(&self) -> Option<Vec<String>> {
+        self.symbols.as_deref().map(split_symbols)
+    }
+}
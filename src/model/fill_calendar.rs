@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+/// Dates expected on a filled time series response.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FillCalendar {
+    /// Days the exchange is open, following the trading calendar.
+    #[default]
+    Trading,
+    /// Every calendar day.
+    All,
+}
@@ -0,0 +1,15 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+/// How the dates missing from a time series response are filled.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum FillMode {
+    /// Only the saved entries are returned.
+    #[default]
+    None,
+    /// Missing dates repeat the last close as open and close prices, with a volume of 0.
+    Forward,
+    /// Missing dates have `null` prices and volume.
+    Null,
+}
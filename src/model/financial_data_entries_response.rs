@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{FinancialDataReport, ResponseInfo};
+
+/// Response returned from the endpoint writing a batch of entries, with the new or changed entries.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FinancialDataEntriesResponse {
+    pub data: Vec<FinancialDataReport>,
+    pub info: ResponseInfo,
+}
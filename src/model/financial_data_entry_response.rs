@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{FinancialDataReport, ResponseInfo};
+
+/// Response returned from the endpoints writing or deleting a single entry.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FinancialDataEntryResponse {
+    pub data: Option<FinancialDataReport>,
+    pub info: ResponseInfo,
+}
@@ -0,0 +1,54 @@
+use sqlx::{Postgres, QueryBuilder};
+
+/// Filters of a query on `financial_data` or `financial_data_revisions`, `None` when not applied.
+#[derive(Debug, Clone, Default)]
+pub struct FinancialDataFilters {
+    pub symbols: Option<Vec<String>>,
+    pub start_date: Option<time::Date>,
+    pub end_date: Option<time::Date>,
+}
+
+impl FinancialDataFilters {
+    /// Filters on a single global equity, or on every equity if `symbol` is not set.
+    pub fn new(
+        symbol: Option<String>,
+        start_date: Option<time::Date>,
+        end_date: Option<time::Date>,
+    ) -> Self {
+        FinancialDataFilters {
+            symbols: symbol.map(|symbol| vec![symbol]),
+            start_date,
+            end_date,
+        }
+    }
+
+    /// Appends a `WHERE` clause holding only the filters that are set, to which further conditions can be
+    /// appended with `AND`.
+    ///
+    /// Columns are compared as they are stored, so that Postgres can use the `(symbol, date)` and `(date, symbol)`
+    /// indexes and skip the yearly partitions out of the date range.
+    pub fn push_where(self, query: &mut QueryBuilder<'_, Postgres>) {
+        query.push(" WHERE TRUE");
+        match self.symbols {
+            Some(mut symbols) if symbols.len() == 1 => {
+                query
+                    .push(" AND symbol = CAST(")
+                    .push_bind(symbols.remove(0))
+                    .push(" AS BPCHAR)");
+            }
+            Some(symbols) => {
+                query
+                    .push(" AND symbol = ANY(CAST(")
+                    .push_bind(symbols)
+                    .push(" AS BPCHAR[]))");
+            }
+            None => {}
+        }
+        if let Some(start_date) = self.start_date {
+            query.push(" AND date >= ").push_bind(start_date);
+        }
+        if let Some(end_date) = self.end_date {
+            query.push(" AND date <= ").push_bind(end_date);
+        }
+    }
+}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{FinancialDataPoint, Pagination};
+
+/// Response returned from the `v2` `financial_data` endpoint.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct FinancialDataPage {
+    pub data: Vec<FinancialDataPoint>,
+    /// `page` starts at 1, and `pages` counts the last partial page.
+    pub pagination: Pagination,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::FinancialDataReport;
+
+/// Entry of a time series response, either saved or made up to fill a missing date.
+///
+/// Values are only `null` on entries filled with the `null` mode,
+/// or with the `forward` mode before the first known close.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FinancialDataPoint {
+    pub symbol: String,
+    pub date: time::Date,
+    pub open_price: Option<f64>,
+    pub close_price: Option<f64>,
+    pub volume: Option<i32>,
+    /// Set on the entries made up by the `fill` option, omitted on saved entries.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub filled: bool,
+}
+
+impl From<FinancialDataReport> for FinancialDataPoint {
+    fn from(value: FinancialDataReport) -> Self {
+        FinancialDataPoint {
+            symbol: value.symbol,
+            date: value.date,
+            open_price: Some(value.open_price),
+            close_price: Some(value.close_price),
+            volume: Some(value.volume),
+            filled: false,
+        }
+    }
+}
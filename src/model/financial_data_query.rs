@@ -1,11 +1,51 @@
 use serde::Deserialize;
+use utoipa::IntoParams;
+
+use super::{FillCalendar, FillMode, ResponseFormat};
+
+/// Longest range of dates that can be filled, about 10 years.
+const MAX_FILLED_DAYS: i64 = 3660;
 
 /// Values extracted from the URL query of the `financial_data` endpoint
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct FinancialDataQuery {
+    /// Global equity to query, every equity if not set.
     pub symbol: Option<String>,
+    /// Filters out dates earlier than this date.
     pub start_date: Option<time::Date>,
+    /// Filters out dates later than this date.
     pub end_date: Option<time::Date>,
+    /// RFC 3339 timestamp, returning the revisions of the entries known to the database at that time,
+    /// instead of their latest revision.
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub as_of: Option<time::OffsetDateTime>,
+    /// Page of the response, starting at 1. Defaults to 1.
     pub page: Option<usize>,
+    /// Number of entries per page. Defaults to 5.
     pub limit: Option<usize>,
+    /// Produces an entry for every expected date of the range, `none` by default.
+    /// Only applies to `json` responses, and needs both `start_date` and `end_date`.
+    pub fill: Option<FillMode>,
+    /// Dates expected when filling, `trading` by default.
+    pub calendar: Option<FillCalendar>,
+    /// Format of the response, falling back to the `Accept` header, then to `json`.
+    /// `csv` and `ndjson` stream every entry and ignore `limit` and `page`.
+    pub format: Option<ResponseFormat>,
+}
+
+impl FinancialDataQuery {
+    /// Verifies the range of a filled query, returning a message describing the problem.
+    pub fn validate_fill(&self) -> Option<String> {
+        if self.fill.unwrap_or_default() == FillMode::None {
+            return None;
+        }
+        match (self.start_date, self.end_date) {
+            (Some(start), Some(end)) if (end - start).whole_days() > MAX_FILLED_DAYS => Some(
+                format!("Filled ranges must span at most {} days.", MAX_FILLED_DAYS),
+            ),
+            (Some(_), Some(_)) => None,
+            _ => Some("Filling needs both `start_date` and `end_date`.".into()),
+        }
+    }
 }
@@ -1,8 +1,10 @@
-use serde::{Serialize, Deserialize};
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 
 /// Single entry on the time series.
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
 pub struct FinancialDataReport {
     pub symbol: String,
     pub date: time::Date,
@@ -10,3 +12,25 @@ pub struct FinancialDataReport {
     pub close_price: f64,
     pub volume: i32,
 }
+
+impl FinancialDataReport {
+    /// Verifies the values of the entry, returning a message describing the first invalid value.
+    pub fn validate(&self) -> Option<String> {
+        if self.symbol.trim().is_empty() || self.symbol.trim().len() > 8 {
+            return Some("Symbol must have between 1 and 8 characters.".into());
+        }
+        if self.date > time::OffsetDateTime::now_utc().date() {
+            return Some("Date must not be in the future.".into());
+        }
+        if !self.open_price.is_finite() || self.open_price < 0. {
+            return Some("Open price must be a non-negative number.".into());
+        }
+        if !self.close_price.is_finite() || self.close_price < 0. {
+            return Some("Close price must be a non-negative number.".into());
+        }
+        if self.volume < 0 {
+            return Some("Volume must not be negative.".into());
+        }
+        None
+    }
+}
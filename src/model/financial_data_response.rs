@@ -1,11 +1,12 @@
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use super::{FinancialDataReport, Pagination, ResponseInfo};
+use super::{FinancialDataPoint, Pagination, ResponseInfo};
 
 /// Response returned from `financial_data` endpoint.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct FinancialDataResponse {
-    pub data: Vec<FinancialDataReport>,
+    pub data: Vec<FinancialDataPoint>,
     pub pagination: Pagination,
     pub info: ResponseInfo,
 }
@@ -0,0 +1,24 @@
+use super::{FinancialDataReport, PrunedSeries};
+
+/// Change committed to the time series, forwarded from the Postgres notifications.
+#[derive(Debug, Clone)]
+pub enum FinancialDataUpdate {
+    /// New or corrected entry.
+    Upserted(FinancialDataReport),
+    /// Removed entry, along with its last values.
+    Deleted(FinancialDataReport),
+    /// Entries of a symbol removed by the retention policies.
+    Pruned(PrunedSeries),
+}
+
+impl FinancialDataUpdate {
+    /// Symbol whose time series was changed.
+    pub fn symbol(&self) -> &str {
+        match self {
+            FinancialDataUpdate::Upserted(report) | FinancialDataUpdate::Deleted(report) => {
+                &report.symbol
+            }
+            FinancialDataUpdate::Pruned(pruned) => &pruned.symbol,
+        }
+    }
+}
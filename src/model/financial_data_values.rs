@@ -0,0 +1,25 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use super::FinancialDataReport;
+
+/// Body of requests writing a single entry, whose symbol and date are given by the path.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct FinancialDataValues {
+    pub open_price: f64,
+    pub close_price: f64,
+    pub volume: i32,
+}
+
+impl FinancialDataValues {
+    /// Builds the entry of `symbol` on `date`. Symbols are saved in uppercase, like the `import` command does.
+    pub fn into_report(self, symbol: &str, date: time::Date) -> FinancialDataReport {
+        FinancialDataReport {
+            symbol: symbol.trim().to_uppercase(),
+            date,
+            open_price: self.open_price,
+            close_price: self.close_price,
+            volume: self.volume,
+        }
+    }
+}
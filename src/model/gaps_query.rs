@@ -0,0 +1,13 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+/// Values extracted from the URL query of the `symbols/{symbol}/gaps` endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GapsQuery {
+    /// First date checked. Defaults to the first date saved for the symbol, and is never before the first day kept
+    /// by the retention policies.
+    pub start_date: Option<time::Date>,
+    /// Last date checked. Defaults to yesterday, as the entry of today may not be published yet.
+    pub end_date: Option<time::Date>,
+}
@@ -1,7 +1,8 @@
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 /// Extra info for endpoint responses.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ResponseInfo {
     pub error: String,
 }
@@ -1,9 +1,51 @@
+mod alert_event;
+pub use alert_event::*;
+mod alert_events_response;
+pub use alert_events_response::*;
+mod alert_kind;
+pub use alert_kind::*;
+mod alert_rule;
+pub use alert_rule::*;
+mod alert_rule_request;
+pub use alert_rule_request::*;
+mod alert_rule_response;
+pub use alert_rule_response::*;
+mod alert_rules_response;
+pub use alert_rules_response::*;
+
+mod api_key;
+pub use api_key::*;
+mod change_author;
+pub use change_author::*;
+mod scope;
+pub use scope::*;
+
+mod financial_data_entries_response;
+pub use financial_data_entries_response::*;
+mod financial_data_entry_response;
+pub use financial_data_entry_response::*;
+mod financial_data_filters;
+pub use financial_data_filters::*;
+mod financial_data_page;
+pub use financial_data_page::*;
+mod financial_data_point;
+pub use financial_data_point::*;
 mod financial_data_query;
 pub use financial_data_query::*;
+mod fill_calendar;
+pub use fill_calendar::*;
+mod fill_mode;
+pub use fill_mode::*;
 mod financial_data_report;
 pub use financial_data_report::*;
 mod financial_data_response;
 pub use financial_data_response::*;
+mod financial_data_update;
+pub use financial_data_update::*;
+mod financial_data_values;
+pub use financial_data_values::*;
+mod pruned_series;
+pub use pruned_series::*;
 
 mod statistics_query;
 pub use statistics_query::*;
@@ -12,7 +54,56 @@ pub use statistics_report::*;
 mod statistics_response;
 pub use statistics_response::*;
 
+mod gaps_query;
+pub use gaps_query::*;
+mod symbol_gaps;
+pub use symbol_gaps::*;
+mod symbol_gaps_response;
+pub use symbol_gaps_response::*;
+mod symbol_summary;
+pub use symbol_summary::*;
+
+mod export_format;
+pub use export_format::*;
+mod export_query;
+pub use export_query::*;
+
+mod backup_manifest;
+pub use backup_manifest::*;
+mod backup_table;
+pub use backup_table::*;
+mod health_check;
+pub use health_check::*;
+mod ingestion_run;
+pub use ingestion_run::*;
+mod pool_status;
+pub use pool_status::*;
+mod provider_status;
+pub use provider_status::*;
+mod readiness_report;
+pub use readiness_report::*;
+mod status_report;
+pub use status_report::*;
+mod status_response;
+pub use status_response::*;
+
+mod api_error_detail;
+pub use api_error_detail::*;
+mod api_error_response;
+pub use api_error_response::*;
+mod api_version;
+pub use api_version::*;
+mod data_response;
+pub use data_response::*;
+mod error_response;
+pub use error_response::*;
 mod info;
 pub use info::*;
 mod pagination;
 pub use pagination::*;
+mod response_format;
+pub use response_format::*;
+mod stream_query;
+pub use stream_query::*;
+mod symbol_list;
+pub use symbol_list::*;
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Connections of the database pool.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct PoolStatus {
+    pub size: u32,
+    pub idle: usize,
+    pub max_connections: u32,
+}
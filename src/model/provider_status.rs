@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Reachability of the Alpha Vantage API.
+///
+/// `latency_ms` is the time taken to receive the response, if any. Probes are reused for 30 seconds.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProviderStatus {
+    pub reachable: bool,
+    pub latency_ms: Option<u128>,
+    pub error: Option<String>,
+}
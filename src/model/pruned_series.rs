@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// Entries of a symbol deleted by the retention policies, all of them before `pruned_before`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrunedSeries {
+    pub symbol: String,
+    pub pruned_before: time::Date,
+}
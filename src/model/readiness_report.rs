@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::HealthCheck;
+
+/// Type representing the response returned from `readyz` endpoint.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub checks: Vec<HealthCheck>,
+}
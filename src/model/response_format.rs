@@ -0,0 +1,71 @@
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+/// Format of the body returned from list endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ResponseFormat {
+    Json,
+    Csv,
+    Ndjson,
+}
+
+impl ResponseFormat {
+    /// Picks the supported format of highest weight (`q`) from the value of an `Accept` header,
+    /// the first listed on ties, defaulting to JSON. Media types of weight `0` are not acceptable.
+    pub fn from_accept(accept: &str) -> Self {
+        accept
+            .split(',')
+            .filter_map(|media| {
+                let mut params = media.split(';');
+                let format = match params.next().unwrap_or("").trim() {
+                    "text/csv" => ResponseFormat::Csv,
+                    "application/x-ndjson" | "application/ndjson" => ResponseFormat::Ndjson,
+                    "application/json" => ResponseFormat::Json,
+                    _ => return None,
+                };
+                let weight = params
+                    .filter_map(|param| param.split_once('='))
+                    .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                    .map_or(Some(1.), |(_, value)| value.trim().parse::<f32>().ok())?;
+                (weight > 0.).then_some((format, weight))
+            })
+            .fold(
+                None,
+                |best: Option<(Self, f32)>, (format, weight)| match best {
+                    Some((_, best_weight)) if best_weight >= weight => best,
+                    _ => Some((format, weight)),
+                },
+            )
+            .map_or(ResponseFormat::Json, |(format, _)| format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_picks_the_highest_weight() {
+        let cases = [
+            ("text/csv", ResponseFormat::Csv),
+            ("text/csv;q=0.1, application/json", ResponseFormat::Json),
+            (
+                "application/json;q=0.5, application/x-ndjson;q=0.9",
+                ResponseFormat::Ndjson,
+            ),
+            ("text/csv, application/json", ResponseFormat::Csv),
+            ("text/csv; Q=0, text/html", ResponseFormat::Json),
+            ("text/csv;q=0", ResponseFormat::Json),
+            (
+                "text/csv;q=oops, application/x-ndjson;q=0.2",
+                ResponseFormat::Ndjson,
+            ),
+            ("*/*", ResponseFormat::Json),
+            ("", ResponseFormat::Json),
+        ];
+        for (accept, format) in cases {
+            assert_eq!(ResponseFormat::from_accept(accept), format, "{}", accept);
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Permission granted to an API key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+pub enum Scope {
+    /// Read the time series, through `financial_data`, `export`, `stream` and `symbols/{symbol}/gaps`.
+    #[serde(rename = "read:data")]
+    #[value(name = "read:data")]
+    ReadData,
+    /// Read the aggregates of the time series, through `statistics`.
+    #[serde(rename = "read:analytics")]
+    #[value(name = "read:analytics")]
+    ReadAnalytics,
+    /// Change the time series, through `PUT`, `POST` and `DELETE` `financial_data`.
+    #[serde(rename = "admin:ingest")]
+    #[value(name = "admin:ingest")]
+    AdminIngest,
+    /// Manage the alert rules, through `alerts`.
+    #[serde(rename = "admin:alerts")]
+    #[value(name = "admin:alerts")]
+    AdminAlerts,
+}
+
+impl Scope {
+    /// Name of the scope, as stored on the `api_keys` table.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::ReadData => "read:data",
+            Scope::ReadAnalytics => "read:analytics",
+            Scope::AdminIngest => "admin:ingest",
+            Scope::AdminAlerts => "admin:alerts",
+        }
+    }
+}
+
+impl std::fmt::Display for Scope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
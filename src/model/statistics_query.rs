@@ -1,9 +1,14 @@
 use serde::Deserialize;
+use utoipa::IntoParams;
 
 /// Values extracted from the URL query of the `statistics` endpoint
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct StatisticsQuery {
+    /// Global equity to query.
     pub symbol: String,
+    /// Filters out dates earlier than this date.
     pub start_date: time::Date,
+    /// Filters out dates later than this date.
     pub end_date: time::Date,
 }
@@ -1,8 +1,10 @@
-use serde::{Serialize, Deserialize};
+use async_graphql::SimpleObject;
+use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 
 /// Statistics from a global equity within a date range.
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema, SimpleObject)]
 pub struct StatisticsReport {
     pub symbol: String,
     pub start_date: time::Date,
@@ -1,9 +1,10 @@
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use super::{ResponseInfo, StatisticsReport};
 
 /// Type representing the response returned from `financial_data` endpoint.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct StatisticsResponse {
     pub data: Option<StatisticsReport>,
     pub info: ResponseInfo,
@@ -0,0 +1,18 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{HealthCheck, IngestionRun, PoolStatus, ProviderStatus};
+
+/// Detailed status of the server.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StatusReport {
+    pub version: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: time::OffsetDateTime,
+    pub uptime_secs: u64,
+    pub ready: bool,
+    pub checks: Vec<HealthCheck>,
+    pub pool: PoolStatus,
+    pub provider: ProviderStatus,
+    pub last_ingestion: Option<IngestionRun>,
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{ResponseInfo, StatusReport};
+
+/// Type representing the response returned from `status` endpoint.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct StatusResponse {
+    pub data: StatusReport,
+    pub info: ResponseInfo,
+}
@@ -0,0 +1,19 @@
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use super::split_symbols;
+
+/// Values extracted from the URL query of the `stream` endpoint
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StreamQuery {
+    /// Comma separated global equities to follow, every equity if not set.
+    pub symbols: Option<String>,
+}
+
+impl StreamQuery {
+    /// Splits the comma separated `symbols` parameter.
+    pub fn symbol_list(&self) -> Option<Vec<String>> {
+        self.symbols.as_deref().map(split_symbols)
+    }
+}
@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Trading days of a date range on which the time series of a global equity has no entry.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SymbolGaps {
+    pub symbol: String,
+    pub start_date: time::Date,
+    pub end_date: time::Date,
+    /// Number of days the exchange was open within the range.
+    pub trading_days: usize,
+    pub missing: Vec<time::Date>,
+}
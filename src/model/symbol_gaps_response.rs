@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::{ResponseInfo, SymbolGaps};
+
+/// Response returned from the `symbols/{symbol}/gaps` endpoint.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SymbolGapsResponse {
+    pub data: Option<SymbolGaps>,
+    pub info: ResponseInfo,
+}
@@ -0,0 +1,8 @@
+/// Splits a comma separated list of symbols, as passed on URL queries.
+pub fn split_symbols(symbols: &str) -> Vec<String> {
+    symbols
+        .split(',')
+        .map(|symbol| symbol.trim().to_string())
+        .filter(|symbol| !symbol.is_empty())
+        .collect()
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Extent of the time series of a global equity.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SymbolSummary {
+    pub symbol: String,
+    pub first_date: time::Date,
+    pub last_date: time::Date,
+    pub entries: i64,
+}
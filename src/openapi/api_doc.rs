@@ -0,0 +1,121 @@
+use utoipa::{
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::{auth::API_KEY_HEADER, model, routes};
+
+/// OpenAPI document of the endpoints, generated from the handlers and the types of `model`.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Financial Data API",
+        description = "Daily time series of global equities, their statistics and alerts.\n\n\
+            `/api/v1` is deprecated in favour of `/api/v2`, and `/api` is an alias of `/api/v1`."
+    ),
+    paths(
+        routes::financial_data,
+        routes::put_financial_data,
+        routes::post_financial_data,
+        routes::delete_financial_data,
+        routes::statistics,
+        routes::export,
+        routes::stream,
+        routes::gaps,
+        routes::list_alerts,
+        routes::create_alert,
+        routes::get_alert,
+        routes::update_alert,
+        routes::delete_alert,
+        routes::alert_events,
+        routes::status,
+        routes::v2::financial_data,
+        routes::v2::put_financial_data,
+        routes::v2::post_financial_data,
+        routes::v2::delete_financial_data,
+        routes::v2::statistics,
+        routes::v2::export,
+        routes::v2::stream,
+        routes::v2::gaps,
+        routes::v2::list_alerts,
+        routes::v2::create_alert,
+        routes::v2::get_alert,
+        routes::v2::update_alert,
+        routes::v2::delete_alert,
+        routes::v2::alert_events,
+        routes::v2::status,
+        routes::healthz,
+        routes::readyz,
+        routes::metrics,
+    ),
+    components(schemas(
+        model::AlertEvent,
+        model::AlertEventsData,
+        model::AlertEventsResponse,
+        model::AlertKind,
+        model::AlertRule,
+        model::AlertRuleData,
+        model::AlertRuleRequest,
+        model::AlertRuleResponse,
+        model::AlertRulesData,
+        model::AlertRulesResponse,
+        model::ApiErrorDetail,
+        model::ApiErrorResponse,
+        model::ErrorResponse,
+        model::ExportFormat,
+        model::FillCalendar,
+        model::FillMode,
+        model::FinancialDataEntriesData,
+        model::FinancialDataEntriesResponse,
+        model::FinancialDataEntryData,
+        model::FinancialDataEntryResponse,
+        model::FinancialDataPage,
+        model::FinancialDataPoint,
+        model::FinancialDataReport,
+        model::FinancialDataResponse,
+        model::FinancialDataValues,
+        model::HealthCheck,
+        model::IngestionRun,
+        model::Pagination,
+        model::PoolStatus,
+        model::ProviderStatus,
+        model::ReadinessReport,
+        model::ResponseFormat,
+        model::ResponseInfo,
+        model::StatisticsData,
+        model::StatisticsReport,
+        model::StatisticsResponse,
+        model::StatusData,
+        model::StatusReport,
+        model::StatusResponse,
+        model::SymbolGaps,
+        model::SymbolGapsData,
+        model::SymbolGapsResponse,
+    )),
+    modifiers(&SecuritySchemes),
+    tags(
+        (name = "data", description = "Time series, needs the `read:data` scope."),
+        (name = "ingest", description = "Changes of the time series, recorded on an audit table, needs the `admin:ingest` scope."),
+        (name = "analytics", description = "Aggregates of the time series, needs the `read:analytics` scope."),
+        (name = "alerts", description = "Alert rules, needs the `admin:alerts` scope."),
+        (name = "health", description = "Probes and status, open to every client."),
+    )
+)]
+pub struct ApiDoc;
+
+/// Declares the two ways of sending an API key.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new(API_KEY_HEADER))),
+        );
+    }
+}
@@ -0,0 +1,2 @@
+mod api_doc;
+pub use api_doc::*;
@@ -0,0 +1,128 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+
+use crate::{error::ApiError, metrics, model::ApiVersion};
+
+use super::{RateLimitDecision, RateLimiter};
+
+/// Adds the `X-RateLimit-*` headers describing the state of the bucket and quota of the client.
+fn insert_headers(headers: &mut HeaderMap, decision: &RateLimitDecision) {
+    let mut insert = |name: &'static str, value: u64| {
+        headers.insert(name, HeaderValue::from(value));
+    };
+    insert("x-ratelimit-limit", u64::from(decision.limit));
+    insert("x-ratelimit-remaining", u64::from(decision.remaining));
+    insert("x-ratelimit-reset", decision.reset_secs);
+    if let (Some(limit), Some(remaining)) = (decision.quota_limit, decision.quota_remaining) {
+        insert("x-ratelimit-quota-limit", limit);
+        insert("x-ratelimit-quota-remaining", remaining);
+    }
+}
+
+/// Middleware rejecting requests with `429 Too Many Requests` once the client has used up its bucket
+/// or its daily quota.
+///
+/// Must be added inside the authentication layer, so that clients are identified by their API key.
+/// Requests are let through if the rate limit can't be checked.
+pub async fn limit_requests<B>(
+    State(limiter): State<RateLimiter>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let version = ApiVersion::of(&request);
+    let client = limiter.client_id(&request);
+    let decision = match limiter.check(&client).await {
+        Ok(decision) => decision,
+        Err(err) => {
+            log::error!("{:?}", err);
+            return next.run(request).await;
+        }
+    };
+
+    let mut response = match decision.allowed {
+        true => next.run(request).await,
+        false => rejection(version, &client, &decision),
+    };
+    insert_headers(response.headers_mut(), &decision);
+    response
+}
+
+/// `429 Too Many Requests` response of a client that has used up its bucket or its daily quota.
+fn rejection(version: ApiVersion, client: &str, decision: &RateLimitDecision) -> Response {
+    let (reason, code, message) = match decision.quota_exhausted {
+        true => ("quota", "quota_exceeded", "The daily quota is used up."),
+        false => ("rate", "rate_limited", "Too many requests, slow down."),
+    };
+    log::warn!("Client `{}` was rate limited, `{}`.", client, reason);
+    metrics::RATE_LIMITED.with_label_values(&[reason]).inc();
+    let mut response =
+        ApiError::new(StatusCode::TOO_MANY_REQUESTS, code, message).versioned(version);
+    if let Some(retry_after) = decision.retry_after_secs {
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+    response
+}
+
+/// Middleware limiting the requests rejected by the authentication layer, per client IP.
+///
+/// Must be added outside the authentication layer, so that requests with missing, invalid or unauthorized keys,
+/// which never reach `limit_requests`, can't look up keys endlessly. Each rejected request takes a token
+/// from the bucket of the IP, and every request of the IP is rejected before authentication while it is empty.
+/// Accepted requests take no token from it, so they are only limited by the bucket of their key.
+pub async fn limit_failed_authentication<B>(
+    State(limiter): State<RateLimiter>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let version = ApiVersion::of(&request);
+    let client = limiter.client_ip(&request);
+    match limiter.peek(&client).await {
+        Ok(decision) if !decision.allowed => {
+            let mut response = rejection(version, &client, &decision);
+            insert_headers(response.headers_mut(), &decision);
+            return response;
+        }
+        Ok(_) => {}
+        Err(err) => log::error!("{:?}", err),
+    }
+
+    let response = next.run(request).await;
+    if matches!(
+        response.status(),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    ) {
+        if let Err(err) = limiter.check(&client).await {
+            log::error!("{:?}", err);
+        }
+    }
+    response
+}
+
+/// Limits the rate of requests to every route of `router`, unless rate limiting is disabled.
+pub fn with_rate_limit(router: Router, limiter: Option<RateLimiter>) -> Router {
+    match limiter {
+        Some(limiter) => {
+            router.route_layer(middleware::from_fn_with_state(limiter, limit_requests))
+        }
+        None => router,
+    }
+}
+
+/// Limits the requests of every route of `router` rejected by the authentication layer,
+/// unless rate limiting or authentication is disabled.
+pub fn with_failed_authentication_limit(router: Router, limiter: Option<RateLimiter>) -> Router {
+    match limiter {
+        Some(limiter) => router.route_layer(middleware::from_fn_with_state(
+            limiter,
+            limit_failed_authentication,
+        )),
+        None => router,
+    }
+}
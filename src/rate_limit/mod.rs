@@ -0,0 +1,6 @@
+mod limit_requests;
+pub use limit_requests::*;
+mod rate_limiter;
+pub use rate_limiter::*;
+mod token_bucket;
+pub use token_bucket::*;
@@ -0,0 +1,306 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+
+use axum::{extract::ConnectInfo, http::Request};
+use error_stack::{IntoReport, Result, ResultExt};
+
+use crate::{
+    config::{RateLimitBackend, RateLimitConfig},
+    error::RateLimitError,
+    model::ApiKey,
+};
+
+use super::{BucketState, RateLimitDecision};
+
+/// Number of clients tracked in memory above which the idle ones are forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10_000;
+
+/// Number of clients left tracked in memory once some were forgotten, so that forgetting clients,
+/// which goes through every bucket, only happens once every `MAX_TRACKED_CLIENTS - RETAINED_CLIENTS` new clients.
+const RETAINED_CLIENTS: usize = MAX_TRACKED_CLIENTS * 9 / 10;
+
+/// Rate limits of the clients, kept in memory or on Postgres.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    config: Arc<RateLimitConfig>,
+    buckets: Arc<Mutex<HashMap<String, BucketState>>>,
+    pool: sqlx::PgPool,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig, pool: sqlx::PgPool) -> RateLimiter {
+        RateLimiter {
+            config: Arc::new(config),
+            buckets: Default::default(),
+            pool,
+        }
+    }
+
+    /// Identifies the client making a request, by its API key, or by its IP when it has none.
+    pub fn client_id<B>(&self, request: &Request<B>) -> String {
+        match request.extensions().get::<ApiKey>() {
+            Some(api_key) => format!("key:{}", api_key.id),
+            None => self.client_ip(request),
+        }
+    }
+
+    /// Identifies the client making a request by its IP.
+    ///
+    /// Behind `trusted_proxies` proxies, the IP is the entry appended by the outermost one to `X-Forwarded-For`,
+    /// since the entries before it are sent by the client and can't be trusted.
+    /// Falls back to the address of the connection if the header has fewer entries.
+    pub fn client_ip<B>(&self, request: &Request<B>) -> String {
+        let trusted_proxies = self.config.trusted_proxies;
+        let forwarded = (trusted_proxies > 0)
+            .then(|| request.headers().get_all("x-forwarded-for"))
+            .and_then(|values| {
+                let hops: Vec<&str> = values
+                    .iter()
+                    .filter_map(|value| value.to_str().ok())
+                    .flat_map(|value| value.split(','))
+                    .map(str::trim)
+                    .collect();
+                hops.len()
+                    .checked_sub(trusted_proxies)
+                    .map(|index| hops[index].to_string())
+            });
+        let peer = || {
+            request
+                .extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(address)| address.ip().to_string())
+        };
+        match forwarded.or_else(peer) {
+            Some(ip) => format!("ip:{}", ip),
+            None => "unknown".into(),
+        }
+    }
+
+    /// Takes a token from the bucket of `client`.
+    pub async fn check(&self, client: &str) -> Result<RateLimitDecision, RateLimitError> {
+        let now = time::OffsetDateTime::now_utc();
+        match self.config.backend {
+            RateLimitBackend::Memory => Ok(self.check_in_memory(client, now)),
+            RateLimitBackend::Postgres => self.check_in_postgres(client, now).await,
+        }
+    }
+
+    /// Tells whether a token is left in the bucket of `client`, without taking it.
+    pub async fn peek(&self, client: &str) -> Result<RateLimitDecision, RateLimitError> {
+        let query = r#"
+        SELECT tokens, updated_at, day, used_today
+        FROM rate_limits
+        WHERE client = $1;"#;
+
+        let now = time::OffsetDateTime::now_utc();
+        let bucket = match self.config.backend {
+            RateLimitBackend::Memory => self
+                .buckets
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .get(client)
+                .cloned(),
+            RateLimitBackend::Postgres => sqlx::query_as::<_, BucketState>(query)
+                .bind(client)
+                .fetch_optional(&self.pool)
+                .await
+                .into_report()
+                .change_context(RateLimitError)
+                .attach("Failed to query rate limit on Postgres database.")?,
+        };
+        let bucket = bucket.unwrap_or_else(|| BucketState::full(&self.config, now));
+        Ok(bucket.peek(&self.config, now))
+    }
+
+    fn check_in_memory(&self, client: &str, now: time::OffsetDateTime) -> RateLimitDecision {
+        let config = &self.config;
+        let mut buckets = self
+            .buckets
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if buckets.len() >= MAX_TRACKED_CLIENTS && !buckets.contains_key(client) {
+            forget_clients(&mut buckets, config, now);
+        }
+        buckets
+            .entry(client.to_string())
+            .or_insert_with(|| BucketState::full(config, now))
+            .take(config, now)
+    }
+
+    async fn check_in_postgres(
+        &self,
+        client: &str,
+        now: time::OffsetDateTime,
+    ) -> Result<RateLimitDecision, RateLimitError> {
+        let insert_query = r#"
+        INSERT INTO rate_limits (client, tokens, updated_at, day, used_today)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (client) DO NOTHING;"#;
+        let select_query = r#"
+        SELECT tokens, updated_at, day, used_today
+        FROM rate_limits
+        WHERE client = $1
+        FOR UPDATE;"#;
+        let update_query = r#"
+        UPDATE rate_limits
+        SET tokens = $2, updated_at = $3, day = $4, used_today = $5
+        WHERE client = $1;"#;
+
+        let mut trans = self
+            .pool
+            .begin()
+            .await
+            .into_report()
+            .change_context(RateLimitError)
+            .attach("Failed to create transaction on Postgres database.")?;
+        let full = BucketState::full(&self.config, now);
+        sqlx::query(insert_query)
+            .bind(client)
+            .bind(full.tokens)
+            .bind(full.updated_at)
+            .bind(full.day)
+            .bind(full.used_today)
+            .execute(&mut trans)
+            .await
+            .into_report()
+            .change_context(RateLimitError)
+            .attach("Failed to insert rate limit on Postgres database.")?;
+        let mut bucket = sqlx::query_as::<_, BucketState>(select_query)
+            .bind(client)
+            .fetch_one(&mut trans)
+            .await
+            .into_report()
+            .change_context(RateLimitError)
+            .attach("Failed to lock rate limit on Postgres database.")?;
+
+        let decision = bucket.take(&self.config, now);
+        sqlx::query(update_query)
+            .bind(client)
+            .bind(bucket.tokens)
+            .bind(bucket.updated_at)
+            .bind(bucket.day)
+            .bind(bucket.used_today)
+            .execute(&mut trans)
+            .await
+            .into_report()
+            .change_context(RateLimitError)
+            .attach("Failed to update rate limit on Postgres database.")?;
+        trans
+            .commit()
+            .await
+            .into_report()
+            .change_context(RateLimitError)
+            .attach("Failed to commit transaction on Postgres database.")?;
+        Ok(decision)
+    }
+}
+
+/// Forgets clients until at most `RETAINED_CLIENTS` are tracked.
+///
+/// Clients are first forgotten when it is harmless, i.e. once their bucket is full again and, with a quota,
+/// once their usage is from a previous day. Then the least recently seen ones are forgotten,
+/// which resets their quota, so that the memory stays bounded. The `postgres` backend keeps every quota.
+fn forget_clients(
+    buckets: &mut HashMap<String, BucketState>,
+    config: &RateLimitConfig,
+    now: time::OffsetDateTime,
+) {
+    buckets.retain(|_, bucket| {
+        let elapsed = (now - bucket.updated_at).as_seconds_f64();
+        let refilled =
+            bucket.tokens + elapsed * config.requests_per_second >= f64::from(config.burst);
+        let used_today = config.daily_quota.is_some() && bucket.day == now.date();
+        !refilled || used_today
+    });
+    if buckets.len() <= RETAINED_CLIENTS {
+        return;
+    }
+    let mut seen: Vec<(time::OffsetDateTime, String)> = buckets
+        .iter()
+        .map(|(client, bucket)| (bucket.updated_at, client.clone()))
+        .collect();
+    let forgotten = seen.len() - RETAINED_CLIENTS;
+    seen.select_nth_unstable(forgotten - 1);
+    for (_, client) in &seen[..forgotten] {
+        buckets.remove(client);
+    }
+    log::warn!(
+        "Forgot the rate limits of the {} least recently seen clients, over {} tracked clients.",
+        forgotten,
+        MAX_TRACKED_CLIENTS
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter_config(trusted_proxies: usize) -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            requests_per_second: 1.,
+            burst: 1,
+            daily_quota: None,
+            backend: RateLimitBackend::Memory,
+            trusted_proxies,
+        }
+    }
+
+    fn limiter(trusted_proxies: usize) -> RateLimiter {
+        let config = limiter_config(trusted_proxies);
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .connect_lazy("postgres://localhost/unused")
+            .unwrap();
+        RateLimiter::new(config, pool)
+    }
+
+    fn request(forwarded_for: &[&str]) -> Request<()> {
+        let mut request = Request::new(());
+        for value in forwarded_for {
+            request
+                .headers_mut()
+                .append("x-forwarded-for", value.parse().unwrap());
+        }
+        let peer: SocketAddr = "10.0.0.1:1234".parse().unwrap();
+        request.extensions_mut().insert(ConnectInfo(peer));
+        request
+    }
+
+    #[tokio::test]
+    async fn client_ip_skips_entries_sent_by_the_client() {
+        let request = request(&["1.1.1.1, 2.2.2.2", "3.3.3.3"]);
+        assert_eq!(limiter(0).client_ip(&request), "ip:10.0.0.1");
+        assert_eq!(limiter(1).client_ip(&request), "ip:3.3.3.3");
+        assert_eq!(limiter(2).client_ip(&request), "ip:2.2.2.2");
+        assert_eq!(limiter(4).client_ip(&request), "ip:10.0.0.1");
+        assert_eq!(limiter(1).client_ip(&self::request(&[])), "ip:10.0.0.1");
+    }
+
+    #[test]
+    fn tracked_clients_are_bounded() {
+        let config = RateLimitConfig {
+            daily_quota: Some(100),
+            ..limiter_config(0)
+        };
+        let start = time::macros::datetime!(2024-03-01 12:00 UTC);
+        let mut buckets = HashMap::new();
+        for i in 0..MAX_TRACKED_CLIENTS {
+            let now = start + time::Duration::milliseconds(i as i64);
+            let mut bucket = BucketState::full(&config, now);
+            bucket.take(&config, now);
+            buckets.insert(format!("ip:{}", i), bucket);
+        }
+
+        forget_clients(&mut buckets, &config, start + time::Duration::seconds(20));
+        assert_eq!(buckets.len(), RETAINED_CLIENTS);
+        assert!(!buckets.contains_key("ip:0"));
+        assert!(buckets.contains_key(&format!("ip:{}", MAX_TRACKED_CLIENTS - 1)));
+
+        // Buckets refilled since a previous day are forgotten first.
+        forget_clients(&mut buckets, &config, start + time::Duration::days(1));
+        assert!(buckets.is_empty());
+    }
+}
@@ -0,0 +1,99 @@
+use sqlx::FromRow;
+
+use crate::config::RateLimitConfig;
+
+/// Token bucket and daily usage of a client.
+#[derive(Debug, Clone, FromRow)]
+pub struct BucketState {
+    pub tokens: f64,
+    pub updated_at: time::OffsetDateTime,
+    pub day: time::Date,
+    pub used_today: i64,
+}
+
+/// Outcome of taking a token for a request.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    /// The request was rejected because the daily quota is used up, rather than the bucket.
+    pub quota_exhausted: bool,
+    /// Size of the bucket.
+    pub limit: u32,
+    /// Requests left in the bucket.
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset_secs: u64,
+    /// Seconds until a rejected request can be retried.
+    pub retry_after_secs: Option<u64>,
+    pub quota_limit: Option<u64>,
+    pub quota_remaining: Option<u64>,
+}
+
+impl BucketState {
+    /// Bucket of a client seen for the first time.
+    pub fn full(config: &RateLimitConfig, now: time::OffsetDateTime) -> BucketState {
+        BucketState {
+            tokens: f64::from(config.burst),
+            updated_at: now,
+            day: now.date(),
+            used_today: 0,
+        }
+    }
+
+    /// Decision `take` would make, without taking a token.
+    pub fn peek(&self, config: &RateLimitConfig, now: time::OffsetDateTime) -> RateLimitDecision {
+        self.clone().take(config, now)
+    }
+
+    /// Refills the bucket for the time elapsed since the last request,
+    /// then takes a token if there is one left and the daily quota isn't used up.
+    pub fn take(
+        &mut self,
+        config: &RateLimitConfig,
+        now: time::OffsetDateTime,
+    ) -> RateLimitDecision {
+        let burst = f64::from(config.burst);
+        let elapsed = (now - self.updated_at).as_seconds_f64().max(0.);
+        self.tokens = (self.tokens + elapsed * config.requests_per_second).min(burst);
+        self.updated_at = now;
+        if self.day != now.date() {
+            self.day = now.date();
+            self.used_today = 0;
+        }
+
+        let quota_exhausted = config
+            .daily_quota
+            .is_some_and(|quota| self.used_today as u64 >= quota);
+        let allowed = !quota_exhausted && self.tokens >= 1.;
+        if allowed {
+            self.tokens -= 1.;
+            self.used_today += 1;
+        }
+
+        let retry_after_secs = match (allowed, quota_exhausted) {
+            (true, _) => None,
+            (false, true) => Some(seconds_until_next_day(now)),
+            (false, false) => Some(((1. - self.tokens) / config.requests_per_second).ceil() as u64),
+        };
+        RateLimitDecision {
+            allowed,
+            quota_exhausted,
+            limit: config.burst,
+            remaining: self.tokens.floor() as u32,
+            reset_secs: ((burst - self.tokens) / config.requests_per_second).ceil() as u64,
+            retry_after_secs,
+            quota_limit: config.daily_quota,
+            quota_remaining: config
+                .daily_quota
+                .map(|quota| quota.saturating_sub(self.used_today as u64)),
+        }
+    }
+}
+
+/// Seconds until the daily quotas are reset, at midnight UTC.
+fn seconds_until_next_day(now: time::OffsetDateTime) -> u64 {
+    now.date()
+        .next_day()
+        .map(|day| (day.midnight().assume_utc() - now).as_seconds_f64().ceil() as u64)
+        .unwrap_or_default()
+}
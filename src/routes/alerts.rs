@@ -0,0 +1,372 @@
+use axum::{extract::Path, Json};
+use error_stack::{IntoReport, Report, ResultExt};
+
+use crate::{
+    error::{ResponseError, RouteError},
+    model::{
+        AlertEvent, AlertEventsResponse, AlertRule, AlertRuleRequest, AlertRuleResponse,
+        AlertRulesResponse, ResponseInfo,
+    },
+};
+
+const NOT_FOUND: &str = "There is no alert rule with this id.";
+
+/// Builds the response for endpoints returning a single alert rule.
+fn rule_response(rule: Option<AlertRule>) -> Json<AlertRuleResponse> {
+    let error = match &rule {
+        Some(_) => "".into(),
+        None => NOT_FOUND.into(),
+    };
+    Json(AlertRuleResponse {
+        data: rule,
+        info: ResponseInfo { error },
+    })
+}
+
+/// Builds the response for endpoints rejecting an invalid alert rule.
+fn invalid_response(error: String) -> Json<AlertRuleResponse> {
+    Json(AlertRuleResponse {
+        data: None,
+        info: ResponseInfo { error },
+    })
+}
+
+/// Trims the padding of the symbol of an alert rule.
+fn trim_rule(mut rule: AlertRule) -> AlertRule {
+    rule.symbol = rule.symbol.trim().into();
+    rule
+}
+
+/// Queries every alert rule. The internals of the alert rule endpoints are shared by every version of the API.
+pub(crate) async fn query_alert_rules(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+) -> Result<Vec<AlertRule>, Report<RouteError>> {
+    let query_str = r#"
+    SELECT *
+    FROM alert_rules
+    ORDER BY id;
+    "#;
+
+    log::trace!("Querying alert rules from database.");
+    Ok(sqlx::query_as::<_, AlertRule>(query_str)
+        .fetch_all(db)
+        .await
+        .into_report()
+        .change_context(RouteError("alerts"))
+        .attach("Failed to query alert rules on Postgres database.")?
+        .into_iter()
+        .map(trim_rule)
+        .collect())
+}
+
+/// Inserts a validated alert rule.
+pub(crate) async fn insert_alert_rule(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    rule: AlertRuleRequest,
+) -> Result<AlertRule, Report<RouteError>> {
+    let query_str = r#"
+    INSERT INTO alert_rules (symbol, kind, threshold, webhook_url)
+    VALUES ($1, $2, $3, $4)
+    RETURNING *;
+    "#;
+
+    log::trace!("Inserting alert rule into database.");
+    sqlx::query_as::<_, AlertRule>(query_str)
+        .bind(rule.symbol.trim())
+        .bind(rule.kind)
+        .bind(rule.threshold)
+        .bind(rule.webhook_url)
+        .fetch_one(db)
+        .await
+        .into_report()
+        .change_context(RouteError("alerts"))
+        .attach("Failed to insert alert rule on Postgres database.")
+        .map(trim_rule)
+}
+
+/// Queries an alert rule, `None` if it doesn't exist.
+pub(crate) async fn query_alert_rule(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    id: i32,
+) -> Result<Option<AlertRule>, Report<RouteError>> {
+    let query_str = r#"
+    SELECT *
+    FROM alert_rules
+    WHERE id = $1;
+    "#;
+
+    log::trace!("Querying alert rule from database.");
+    Ok(sqlx::query_as::<_, AlertRule>(query_str)
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .into_report()
+        .change_context(RouteError("alerts"))
+        .attach("Failed to query alert rule on Postgres database.")?
+        .map(trim_rule))
+}
+
+/// Replaces an alert rule with a validated one, `None` if it doesn't exist.
+pub(crate) async fn replace_alert_rule(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    id: i32,
+    rule: AlertRuleRequest,
+) -> Result<Option<AlertRule>, Report<RouteError>> {
+    let query_str = r#"
+    UPDATE alert_rules
+    SET symbol = $2, kind = $3, threshold = $4, webhook_url = $5
+    WHERE id = $1
+    RETURNING *;
+    "#;
+
+    log::trace!("Updating alert rule on database.");
+    Ok(sqlx::query_as::<_, AlertRule>(query_str)
+        .bind(id)
+        .bind(rule.symbol.trim())
+        .bind(rule.kind)
+        .bind(rule.threshold)
+        .bind(rule.webhook_url)
+        .fetch_optional(db)
+        .await
+        .into_report()
+        .change_context(RouteError("alerts"))
+        .attach("Failed to update alert rule on Postgres database.")?
+        .map(trim_rule))
+}
+
+/// Deletes an alert rule, along with its events, `None` if it doesn't exist.
+pub(crate) async fn remove_alert_rule(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    id: i32,
+) -> Result<Option<AlertRule>, Report<RouteError>> {
+    let query_str = r#"
+    DELETE FROM alert_rules
+    WHERE id = $1
+    RETURNING *;
+    "#;
+
+    log::trace!("Deleting alert rule from database.");
+    Ok(sqlx::query_as::<_, AlertRule>(query_str)
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .into_report()
+        .change_context(RouteError("alerts"))
+        .attach("Failed to delete alert rule on Postgres database.")?
+        .map(trim_rule))
+}
+
+/// Queries the past triggers of an alert rule, latest first.
+pub(crate) async fn query_alert_events(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    id: i32,
+) -> Result<Vec<AlertEvent>, Report<RouteError>> {
+    let query_str = r#"
+    SELECT *
+    FROM alert_events
+    WHERE rule_id = $1
+    ORDER BY date DESC;
+    "#;
+
+    log::trace!("Querying alert events from database.");
+    Ok(sqlx::query_as::<_, AlertEvent>(query_str)
+        .bind(id)
+        .fetch_all(db)
+        .await
+        .into_report()
+        .change_context(RouteError("alerts"))
+        .attach("Failed to query alert events on Postgres database.")?
+        .into_iter()
+        .map(|mut event| {
+            event.symbol = event.symbol.trim().into();
+            event
+        })
+        .collect())
+}
+
+/// `GET alerts` endpoint.
+///
+/// Returns every alert rule.
+#[utoipa::path(
+    get,
+    path = "/api/v1/alerts",
+    tag = "alerts",
+    responses(
+        (status = 200, description = "Every alert rule.", body = AlertRulesResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn list_alerts(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+) -> Result<Json<AlertRulesResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to list `alerts`.");
+    let data = query_alert_rules(&mut db).await?;
+
+    log::trace!("Responding from `alerts` endpoint.");
+    Ok(Json(AlertRulesResponse {
+        data,
+        info: ResponseInfo { error: "".into() },
+    }))
+}
+
+/// `POST alerts` endpoint.
+///
+/// Creates an alert rule, evaluated against the new or corrected entries of each ingestion.
+///
+/// # Body
+/// * `symbol` => Which global equity to watch.
+/// * `kind` => `price_cross`, `daily_move` or `volume_spike`.
+/// * `threshold` => Price crossed, percentage moved since the previous close, or multiple of the average volume.
+/// * `webhook_url` => URL that triggered events are posted to.
+#[utoipa::path(
+    post,
+    path = "/api/v1/alerts",
+    tag = "alerts",
+    request_body = AlertRuleRequest,
+    responses(
+        (status = 200, description = "Created rule, or the reason it is invalid in `info.error`.", body = AlertRuleResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn create_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Json(rule): Json<AlertRuleRequest>,
+) -> Result<Json<AlertRuleResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to create `alerts`.");
+
+    if let Some(error) = rule.validate() {
+        return Ok(invalid_response(error));
+    }
+    let data = insert_alert_rule(&mut db, rule).await?;
+
+    log::trace!("Responding from `alerts` endpoint.");
+    Ok(rule_response(Some(data)))
+}
+
+/// `GET alerts/{id}` endpoint.
+///
+/// Returns a single alert rule.
+#[utoipa::path(
+    get,
+    path = "/api/v1/alerts/{id}",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    responses(
+        (status = 200, description = "Alert rule, or `null` if it does not exist.", body = AlertRuleResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn get_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+) -> Result<Json<AlertRuleResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to get `alerts`.");
+    let data = query_alert_rule(&mut db, id).await?;
+
+    log::trace!("Responding from `alerts` endpoint.");
+    Ok(rule_response(data))
+}
+
+/// `PUT alerts/{id}` endpoint.
+///
+/// Replaces an alert rule. Takes the same body as `POST alerts`.
+#[utoipa::path(
+    put,
+    path = "/api/v1/alerts/{id}",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    request_body = AlertRuleRequest,
+    responses(
+        (status = 200, description = "Replaced rule, or the reason it was not replaced in `info.error`.", body = AlertRuleResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn update_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+    Json(rule): Json<AlertRuleRequest>,
+) -> Result<Json<AlertRuleResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to update `alerts`.");
+
+    if let Some(error) = rule.validate() {
+        return Ok(invalid_response(error));
+    }
+    let data = replace_alert_rule(&mut db, id, rule).await?;
+
+    log::trace!("Responding from `alerts` endpoint.");
+    Ok(rule_response(data))
+}
+
+/// `DELETE alerts/{id}` endpoint.
+///
+/// Deletes an alert rule, along with its events, returning the deleted rule.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/alerts/{id}",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    responses(
+        (status = 200, description = "Deleted rule, or `null` if it does not exist.", body = AlertRuleResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn delete_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+) -> Result<Json<AlertRuleResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to delete `alerts`.");
+    let data = remove_alert_rule(&mut db, id).await?;
+
+    log::trace!("Responding from `alerts` endpoint.");
+    Ok(rule_response(data))
+}
+
+/// `GET alerts/{id}/events` endpoint.
+///
+/// Returns the past triggers of an alert rule, latest first.
+#[utoipa::path(
+    get,
+    path = "/api/v1/alerts/{id}/events",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    responses(
+        (status = 200, description = "Events triggered by the alert rule.", body = AlertEventsResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn alert_events(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+) -> Result<Json<AlertEventsResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to `alerts` events.");
+    let data = query_alert_events(&mut db, id).await?;
+
+    let error = match data.is_empty() {
+        false => "".into(),
+        true => "The alert rule has not been triggered, or does not exist.".into(),
+    };
+
+    log::trace!("Responding from `alerts` endpoint.");
+    Ok(Json(AlertEventsResponse {
+        data,
+        info: ResponseInfo { error },
+    }))
+}
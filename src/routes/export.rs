@@ -0,0 +1,95 @@
+use crate::{
+    error::{ResponseError, RouteError},
+    model::{ExportFormat, ExportQuery, FinancialDataFilters},
+    tasks,
+};
+use axum::{
+    body::{Bytes, StreamBody},
+    extract::Query,
+    http::header,
+    response::{IntoResponse, Response},
+    Extension,
+};
+
+/// `export` endpoint.  
+///
+/// Returns the time series entries for a set of global equities within a date range
+/// as an Apache Arrow IPC stream or an Apache Parquet file, with typed columns, streamed as it is written.
+///
+/// # Query arguments
+/// * `symbols`: Optional => Comma separated global equities to export. `None` for all equities.
+/// * `start_date`: Optional => Filters out dates earlier than this date.
+/// * `end_date`: Optional => Filters out dates later than this date.
+/// * `format`: Optional, Default=`arrow` => `arrow` or `parquet`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/export",
+    tag = "data",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "Entries as a file attachment.", content(
+            ("application/vnd.apache.arrow.stream" = String),
+            ("application/vnd.apache.parquet" = String),
+        )),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn export(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ResponseError<RouteError>> {
+    log::trace!("Received request to `export`.");
+    let response = export_response(&pool, query).await?;
+
+    log::trace!("Responding from `export` endpoint.");
+    Ok(response)
+}
+
+/// Number of encoded chunks buffered between the export and the response body.
+const EXPORT_BUFFER: usize = 4;
+
+/// Streams the entries matching `query` as a file attachment, shared by every version of the API.
+///
+/// The file is sent a record batch at a time as it is written. A failed export aborts the response body.
+pub(crate) async fn export_response(
+    pool: &sqlx::PgPool,
+    query: ExportQuery,
+) -> error_stack::Result<Response, RouteError> {
+    let format = query.format.unwrap_or(ExportFormat::Arrow);
+    let filters = FinancialDataFilters {
+        symbols: query.symbol_list(),
+        start_date: query.start_date,
+        end_date: query.end_date,
+    };
+    let (sender, receiver) =
+        tokio::sync::mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(EXPORT_BUFFER);
+
+    let pool = pool.clone();
+    tokio::spawn(async move {
+        match tasks::stream_financial_data_export(&pool, filters, format, &sender).await {
+            Ok(count) => log::trace!("Exported {} rows.", count),
+            // The client disconnected, or the export has failed and the stream must be aborted.
+            Err(_) if sender.is_closed() => log::trace!("Export was dropped by the client."),
+            Err(e) => {
+                log::error!("{:?}", e);
+                let _ = sender.send(Err(std::io::Error::other(e.to_string()))).await;
+            }
+        }
+    });
+
+    let disposition = format!(
+        "attachment; filename=\"financial_data.{}\"",
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        StreamBody::new(tokio_stream::wrappers::ReceiverStream::new(receiver)),
+    )
+        .into_response())
+}
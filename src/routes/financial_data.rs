@@ -1,13 +1,164 @@
-use axum::{extract::Query, Json};
-use error_stack::{IntoReport, ResultExt};
+use axum::{
+    body::{Bytes, StreamBody},
+    extract::{FromRequestParts, Query},
+    http::{header, request::Parts, HeaderMap},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use error_stack::{IntoReport, Report, ResultExt};
+use futures::{Stream, TryStreamExt};
+use sqlx::{Postgres, QueryBuilder};
 
 use crate::{
+    cache::{CacheKey, ResponseCache},
+    config::CalendarConfig,
     error::{ResponseError, RouteError},
+    metrics,
     model::{
-        FinancialDataQuery, FinancialDataReport, FinancialDataResponse, Pagination, ResponseInfo,
+        ApiVersion, FinancialDataFilters, FinancialDataQuery, FinancialDataReport,
+        FinancialDataResponse, Pagination, ResponseFormat, ResponseInfo,
     },
+    routes::query_financial_series,
 };
 
+/// Starts a query on the entries matching `filters`, on their latest revision, or on the revision known at `as_of`.
+///
+/// Further conditions can be appended with `AND`. Shared by the `financial_data` queries and the fill of its gaps.
+pub(crate) fn select_financial_data(
+    columns: &str,
+    filters: FinancialDataFilters,
+    as_of: Option<time::OffsetDateTime>,
+) -> QueryBuilder<'static, Postgres> {
+    let table = match as_of {
+        Some(_) => "financial_data_revisions",
+        None => "financial_data",
+    };
+    let mut query = QueryBuilder::new(format!("SELECT {columns} FROM {table}"));
+    filters.push_where(&mut query);
+    if let Some(as_of) = as_of {
+        query
+            .push(" AND recorded_at <= ")
+            .push_bind(as_of)
+            .push(" AND (superseded_at IS NULL OR superseded_at > ")
+            .push_bind(as_of)
+            .push(")");
+    }
+    query
+}
+
+/// Builds the query of the entries matching the filters, latest first, on their latest revision,
+/// or on the revision known at `as_of`.
+pub(crate) fn financial_data_query(
+    symbol: Option<String>,
+    start_date: Option<time::Date>,
+    end_date: Option<time::Date>,
+    as_of: Option<time::OffsetDateTime>,
+) -> QueryBuilder<'static, Postgres> {
+    let mut query = select_financial_data(
+        "symbol, date, open_price, close_price, volume",
+        FinancialDataFilters::new(symbol, start_date, end_date),
+        as_of,
+    );
+    query.push(" ORDER BY date DESC");
+    query
+}
+
+/// Number of encoded rows buffered between the database cursor and the response body.
+const STREAM_BUFFER: usize = 64;
+
+/// Encodes a single row of a streamed response.
+fn encode_row(
+    format: ResponseFormat,
+    row: &FinancialDataReport,
+) -> std::result::Result<Vec<u8>, std::io::Error> {
+    match format {
+        ResponseFormat::Csv => {
+            let mut writer = csv::WriterBuilder::new()
+                .has_headers(false)
+                .from_writer(vec![]);
+            writer.serialize(row)?;
+            writer
+                .into_inner()
+                .map_err(|err| std::io::Error::other(err.to_string()))
+        }
+        _ => {
+            let mut line = serde_json::to_vec(row)?;
+            line.push(b'\n');
+            Ok(line)
+        }
+    }
+}
+
+/// Fetches every entry matching a query built by `financial_data_query`, row by row from a database cursor.
+///
+/// Shared by the streamed responses of the `financial_data` endpoint, and by the gRPC service.
+pub(crate) fn fetch_financial_data<'a>(
+    pool: &'a sqlx::PgPool,
+    query: &'a mut QueryBuilder<'static, Postgres>,
+) -> impl Stream<Item = Result<FinancialDataReport, sqlx::Error>> + Send + 'a {
+    query
+        .build_query_as::<FinancialDataReport>()
+        .fetch(pool)
+        .map_ok(|mut row| {
+            row.symbol = row.symbol.trim().into();
+            row
+        })
+}
+
+/// Streams every entry matching the filters, row by row from a database cursor, as CSV or NDJSON.
+///
+/// Pagination is ignored so that a full history can be exported without buffering it in memory.
+pub(crate) fn stream_financial_data(
+    pool: sqlx::PgPool,
+    format: ResponseFormat,
+    symbol: Option<String>,
+    start_date: Option<time::Date>,
+    end_date: Option<time::Date>,
+    as_of: Option<time::OffsetDateTime>,
+) -> Response {
+    let (sender, receiver) =
+        tokio::sync::mpsc::channel::<std::result::Result<Bytes, std::io::Error>>(STREAM_BUFFER);
+
+    tokio::spawn(async move {
+        if format == ResponseFormat::Csv {
+            let header = Bytes::from_static(b"symbol,date,open_price,close_price,volume\n");
+            if sender.send(Ok(header)).await.is_err() {
+                return;
+            }
+        }
+
+        log::trace!("Streaming time series entries from database.");
+        let mut query = financial_data_query(symbol, start_date, end_date, as_of);
+        let mut rows = fetch_financial_data(&pool, &mut query);
+        loop {
+            let chunk = match rows.try_next().await {
+                Ok(Some(row)) => encode_row(format, &row).map(Bytes::from),
+                Ok(None) => break,
+                Err(err) => {
+                    log::error!("Failed to stream financial data from database: {}", err);
+                    Err(std::io::Error::other(err))
+                }
+            };
+            let failed = chunk.is_err();
+            // The client disconnected, or the stream has failed and must be aborted.
+            if sender.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+        log::trace!("Finished streaming `financial_data`.");
+    });
+
+    let content_type = match format {
+        ResponseFormat::Csv => "text/csv; charset=utf-8",
+        _ => "application/x-ndjson",
+    };
+    (
+        [(header::CONTENT_TYPE, content_type)],
+        StreamBody::new(tokio_stream::wrappers::ReceiverStream::new(receiver)),
+    )
+        .into_response()
+}
+
 /// `financial_data` endpoint.  
 ///
 /// Returns a list of entries of the time series for a global equity within a date range.
@@ -18,48 +169,177 @@ use crate::{
 /// * `symbol`: Optional => Which global equity to query. `None` for all equities.
 /// * `start_date`: Optional => Filters out dates earlier than this date.
 /// * `end_date`: Optional => Filters out dates later than this date.
+/// * `as_of`: Optional => RFC 3339 timestamp. Returns the entries as the database knew them at that time,
+///   instead of their latest revision.
 /// * `limit`: Optional, Default=5 => Limits the number of entries per response.
 /// * `page`: Optional, Default=1 => Page of the response, for when the number of entries is larger than the limit.
+/// * `fill`: Optional, Default=`none` => `none`, `forward` or `null`. Produces an entry for every expected date
+///   between `start_date` and `end_date`, repeating the last close with a volume of 0, or with `null` values.
+///   Filled entries are marked with `filled: true`.
+/// * `calendar`: Optional, Default=`trading` => `trading` or `all`. Dates expected when filling.
+/// * `format`: Optional => `json`, `csv` or `ndjson`. Falls back to the `Accept` header, then to `json`.
+///   `csv` and `ndjson` stream every entry and ignore `limit` and `page`.
+///
+/// JSON pages are cached until the time series of the equity is updated.
+#[utoipa::path(
+    get,
+    path = "/api/v1/financial_data",
+    tag = "data",
+    params(FinancialDataQuery),
+    responses(
+        (status = 200, description = "Page of entries, or every entry when streamed.", content(
+            ("application/json" = FinancialDataResponse),
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+        )),
+        (status = 304, description = "The `If-None-Match` header matches the `ETag` of the response."),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
 pub async fn financial_data(
-    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
-    Query(FinancialDataQuery {
-        symbol,
-        start_date,
-        end_date,
-        page,
-        limit,
-    }): Query<FinancialDataQuery>,
-) -> Result<Json<FinancialDataResponse>, ResponseError<RouteError>> {
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(cache): Extension<ResponseCache>,
+    Extension(calendar): Extension<CalendarConfig>,
+    headers: HeaderMap,
+    Query(mut query): Query<FinancialDataQuery>,
+    mut parts: Parts,
+) -> Result<Response, ResponseError<RouteError>> {
     log::trace!("Received request to `financial_data`.");
+    // Symbols are stored in uppercase, and cached responses are shared by every spelling.
+    query.symbol = query.symbol.map(|symbol| symbol.trim().to_uppercase());
 
-    let query_str = r#"
-    SELECT *
-    FROM financial_data
-    WHERE symbol = COALESCE($1, symbol) AND date BETWEEN COALESCE($2, date) AND COALESCE($3, date)
-    ORDER BY date DESC;
-    "#;
+    let format = response_format(query.format, &headers);
+    if format != ResponseFormat::Json {
+        return Ok(stream_financial_data(
+            pool,
+            format,
+            query.symbol,
+            query.start_date,
+            query.end_date,
+            query.as_of,
+        ));
+    }
 
-    log::trace!(
-        "Querying time series entries from database for a given global equity and date range."
+    let mut db = begin_transaction(&mut parts).await?;
+    let limit = query.limit.unwrap_or(5);
+    let page = query.page.unwrap_or(1);
+    let key = CacheKey::new(
+        "financial_data",
+        query.symbol.as_deref(),
+        (
+            ApiVersion::V1,
+            query.start_date,
+            query.end_date,
+            query.as_of,
+            query.fill,
+            query.calendar,
+            page,
+            limit,
+        ),
     );
-    let qresult = sqlx::query_as::<_, FinancialDataReport>(query_str)
-        .bind(symbol)
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_all(&mut db)
+    cache
+        .respond(
+            key,
+            &headers,
+            financial_data_page(&mut db, &calendar, &query, page, limit),
+        )
+        .await
+}
+
+/// Begins the transaction of a request, for handlers that only query through it on some of their paths.
+///
+/// Streamed responses read through a connection of their own, so extracting `Tx` up front would hold a second one.
+pub(crate) async fn begin_transaction(
+    parts: &mut Parts,
+) -> Result<axum_sqlx_tx::Tx<sqlx::Postgres>, Report<RouteError>> {
+    axum_sqlx_tx::Tx::from_request_parts(parts, &())
         .await
         .into_report()
         .change_context(RouteError("financial_data"))
-        .attach("Failed to query financial data on PostgreSQL database.")?;
+        .attach("Failed to start transaction on PostgreSQL database.")
+}
+
+/// Picks the format of a response from the `format` parameter, then from the `Accept` header.
+pub(crate) fn response_format(
+    format: Option<ResponseFormat>,
+    headers: &HeaderMap,
+) -> ResponseFormat {
+    format.unwrap_or_else(|| {
+        headers
+            .get(header::ACCEPT)
+            .and_then(|accept| accept.to_str().ok())
+            .map(ResponseFormat::from_accept)
+            .unwrap_or(ResponseFormat::Json)
+    })
+}
+
+/// Queries every entry of the time series matching the filters, latest first.
+///
+/// Shared by every version of the `financial_data` endpoint.
+pub(crate) async fn query_financial_data(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    symbol: Option<String>,
+    start_date: Option<time::Date>,
+    end_date: Option<time::Date>,
+    as_of: Option<time::OffsetDateTime>,
+) -> Result<Vec<FinancialDataReport>, Report<RouteError>> {
+    log::trace!(
+        "Querying time series entries from database for a given global equity and date range."
+    );
+    let rows = metrics::time_query(
+        "financial_data",
+        financial_data_query(symbol, start_date, end_date, as_of)
+            .build_query_as::<FinancialDataReport>()
+            .fetch_all(db),
+    )
+    .await
+    .into_report()
+    .change_context(RouteError("financial_data"))
+    .attach("Failed to query financial data on PostgreSQL database.")?;
+
+    log::trace!("Trimming symbol.");
+    Ok(rows
+        .into_iter()
+        .map(|mut r| {
+            r.symbol = r.symbol.trim().into();
+            r
+        })
+        .collect())
+}
+
+/// Builds a page of the response of the `financial_data` endpoint,
+/// with the reason for an empty response in `info.error`.
+async fn financial_data_page(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    calendar: &CalendarConfig,
+    query: &FinancialDataQuery,
+    page: usize,
+    limit: usize,
+) -> Result<FinancialDataResponse, ResponseError<RouteError>> {
+    if let Some(error) = query.validate_fill() {
+        return Ok(FinancialDataResponse {
+            data: vec![],
+            pagination: Pagination {
+                count: 0,
+                page: 0,
+                limit: 0,
+                pages: 0,
+            },
+            info: ResponseInfo { error },
+        });
+    }
+    let qresult = query_financial_series(db, calendar, query).await?;
 
     log::trace!("Setting up variables for filtering.");
     let count = qresult.len();
-    let limit = limit.unwrap_or(5);
     // client-side is 1-indexed, server-side is 0-indexed
-    let page = match page.unwrap_or(1).checked_sub(1) {
+    let page = match page.checked_sub(1) {
         Some(p) => p,
         None => {
-            return Ok(Json(FinancialDataResponse {
+            return Ok(FinancialDataResponse {
                 data: vec![],
                 pagination: Pagination {
                     count: 0,
@@ -70,14 +350,14 @@ pub async fn financial_data(
                 info: ResponseInfo {
                     error: "Page must be a positive number bigger than 0.".into(),
                 },
-            }))
+            })
         }
     };
     let offset = limit * page;
     let pages = match count.checked_div(limit) {
         Some(p) => p,
         None => {
-            return Ok(Json(FinancialDataResponse {
+            return Ok(FinancialDataResponse {
                 data: vec![],
                 pagination: Pagination {
                     count: 0,
@@ -88,7 +368,7 @@ pub async fn financial_data(
                 info: ResponseInfo {
                     error: "Limit must be a positive number bigger than 0.".into(),
                 },
-            }))
+            })
         }
     };
 
@@ -102,19 +382,11 @@ pub async fn financial_data(
         }
     };
 
-    log::trace!("Filtering database response and trimming symbol.");
-    let data = qresult
-        .into_iter()
-        .skip(offset)
-        .take(limit)
-        .map(|mut r| {
-            r.symbol = r.symbol.trim().into();
-            r
-        })
-        .collect();
+    log::trace!("Filtering database response.");
+    let data = qresult.into_iter().skip(offset).take(limit).collect();
 
     log::trace!("Responding from `financial_data` endpoint.");
-    Ok(Json(FinancialDataResponse {
+    Ok(FinancialDataResponse {
         data,
         pagination: Pagination {
             count,
@@ -123,5 +395,5 @@ pub async fn financial_data(
             pages,
         },
         info: ResponseInfo { error: msg },
-    }))
+    })
 }
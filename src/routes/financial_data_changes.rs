@@ -0,0 +1,194 @@
+use axum::{extract::Path, Extension, Json};
+use error_stack::{Report, ResultExt};
+
+use crate::{
+    error::{ResponseError, RouteError},
+    model::{
+        ApiKey, ChangeAuthor, FinancialDataEntriesResponse, FinancialDataEntryResponse,
+        FinancialDataReport, FinancialDataValues, ResponseInfo,
+    },
+    tasks,
+};
+
+/// Largest number of entries accepted by a single batch.
+pub(crate) const MAX_BATCH_SIZE: usize = 1000;
+
+pub(crate) const ENTRY_NOT_FOUND: &str = "There is no entry for this symbol and date.";
+
+/// Verifies every entry of a batch, returning a message describing the first invalid entry.
+pub(crate) fn validate_batch(rows: &[FinancialDataReport]) -> Option<String> {
+    if rows.is_empty() || rows.len() > MAX_BATCH_SIZE {
+        return Some(format!(
+            "Batch must have between 1 and {} entries.",
+            MAX_BATCH_SIZE
+        ));
+    }
+    rows.iter().enumerate().find_map(|(i, row)| {
+        row.validate()
+            .map(|error| format!("Entry {}: {}", i, error))
+    })
+}
+
+/// Upserts validated entries through `upsert_audited`, with their symbols in uppercase, then evaluates the alert rules against the new or
+/// changed entries, which are returned. The internals of the write endpoints are shared by every version of the API.
+pub(crate) async fn write_entries(
+    pool: sqlx::PgPool,
+    rows: Vec<FinancialDataReport>,
+    api_key: Option<&ApiKey>,
+) -> Result<Vec<FinancialDataReport>, Report<RouteError>> {
+    let rows = rows
+        .into_iter()
+        .map(|mut row| {
+            row.symbol = row.symbol.trim().to_uppercase();
+            row
+        })
+        .collect();
+    let author = ChangeAuthor::from_api_key(api_key);
+    let updated = tasks::upsert_audited(pool.clone(), rows, &author)
+        .await
+        .change_context(RouteError("financial_data"))?;
+
+    // The values are already saved, so a failure here must not fail the request.
+    if let Err(err) = tasks::evaluate_alerts(pool, &updated).await {
+        log::error!("{:?}", err);
+    }
+    Ok(updated)
+}
+
+/// Deletes an entry through `delete_from_database`, `None` if it doesn't exist. `symbol` is case-insensitive.
+pub(crate) async fn remove_entry(
+    pool: sqlx::PgPool,
+    symbol: &str,
+    date: time::Date,
+    api_key: Option<&ApiKey>,
+) -> Result<Option<FinancialDataReport>, Report<RouteError>> {
+    let author = ChangeAuthor::from_api_key(api_key);
+    tasks::delete_from_database(pool, &symbol.trim().to_uppercase(), date, &author)
+        .await
+        .change_context(RouteError("financial_data"))
+}
+
+/// Builds the response for endpoints handling a single entry.
+fn entry_response(
+    entry: Option<FinancialDataReport>,
+    error: &str,
+) -> Json<FinancialDataEntryResponse> {
+    let error = match &entry {
+        Some(_) => "".into(),
+        None => error.into(),
+    };
+    Json(FinancialDataEntryResponse {
+        data: entry,
+        info: ResponseInfo { error },
+    })
+}
+
+/// `PUT financial_data/{symbol}/{date}` endpoint.
+///
+/// Inserts or corrects the entry of a global equity on a date, recording the change on the audit table.
+///
+/// # Body
+/// * `open_price`, `close_price` => Non-negative prices.
+/// * `volume` => Non-negative volume.
+#[utoipa::path(
+    put,
+    path = "/api/v1/financial_data/{symbol}/{date}",
+    tag = "ingest",
+    params(
+        ("symbol" = String, Path, description = "Global equity of the entry."),
+        ("date" = String, Path, description = "Date of the entry, as `YYYY-MM-DD`."),
+    ),
+    request_body = FinancialDataValues,
+    responses(
+        (status = 200, description = "Written entry, or the reason it is invalid in `info.error`.", body = FinancialDataEntryResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:ingest`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn put_financial_data(
+    Extension(pool): Extension<sqlx::PgPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Path((symbol, date)): Path<(String, time::Date)>,
+    Json(values): Json<FinancialDataValues>,
+) -> Result<Json<FinancialDataEntryResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to put `financial_data`.");
+    let entry = values.into_report(&symbol, date);
+    if let Some(error) = entry.validate() {
+        return Ok(entry_response(None, &error));
+    }
+    write_entries(pool, vec![entry.clone()], api_key.as_deref()).await?;
+
+    log::trace!("Responding from `financial_data` endpoint.");
+    Ok(entry_response(Some(entry), ""))
+}
+
+/// `POST financial_data` endpoint.
+///
+/// Inserts or corrects a batch of entries within a single transaction, recording each change on the audit table.
+/// Returns the entries that were new or changed.
+#[utoipa::path(
+    post,
+    path = "/api/v1/financial_data",
+    tag = "ingest",
+    request_body = Vec<FinancialDataReport>,
+    responses(
+        (status = 200, description = "New or changed entries, or the reason the batch is invalid in `info.error`.", body = FinancialDataEntriesResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:ingest`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn post_financial_data(
+    Extension(pool): Extension<sqlx::PgPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Json(rows): Json<Vec<FinancialDataReport>>,
+) -> Result<Json<FinancialDataEntriesResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to post `financial_data`.");
+    if let Some(error) = validate_batch(&rows) {
+        return Ok(Json(FinancialDataEntriesResponse {
+            data: vec![],
+            info: ResponseInfo { error },
+        }));
+    }
+    let data = write_entries(pool, rows, api_key.as_deref()).await?;
+
+    log::trace!("Responding from `financial_data` endpoint.");
+    Ok(Json(FinancialDataEntriesResponse {
+        data,
+        info: ResponseInfo { error: "".into() },
+    }))
+}
+
+/// `DELETE financial_data/{symbol}/{date}` endpoint.
+///
+/// Deletes the entry of a global equity on a date, recording its last values on the audit table.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/financial_data/{symbol}/{date}",
+    tag = "ingest",
+    params(
+        ("symbol" = String, Path, description = "Global equity of the entry."),
+        ("date" = String, Path, description = "Date of the entry, as `YYYY-MM-DD`."),
+    ),
+    responses(
+        (status = 200, description = "Deleted entry, or `null` if it does not exist.", body = FinancialDataEntryResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:ingest`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn delete_financial_data(
+    Extension(pool): Extension<sqlx::PgPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Path((symbol, date)): Path<(String, time::Date)>,
+) -> Result<Json<FinancialDataEntryResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to delete `financial_data`.");
+    let data = remove_entry(pool, &symbol, date, api_key.as_deref()).await?;
+
+    log::trace!("Responding from `financial_data` endpoint.");
+    Ok(entry_response(data, ENTRY_NOT_FOUND))
+}
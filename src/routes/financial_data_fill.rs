@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use error_stack::{IntoReport, Report, ResultExt};
+use time::Date;
+
+use crate::{
+    calendar::TradingCalendar,
+    config::CalendarConfig,
+    error::RouteError,
+    metrics,
+    model::{
+        FillCalendar, FillMode, FinancialDataFilters, FinancialDataPoint, FinancialDataQuery,
+        FinancialDataReport,
+    },
+};
+
+use super::{query_financial_data, select_financial_data};
+
+/// Queries the last entry of each symbol before `date`, from which the `forward` mode fills the first missing dates.
+async fn query_previous_closes(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    symbol: Option<String>,
+    date: Date,
+    as_of: Option<time::OffsetDateTime>,
+) -> Result<Vec<FinancialDataReport>, Report<RouteError>> {
+    let mut query = select_financial_data(
+        "DISTINCT ON (symbol) symbol, date, open_price, close_price, volume",
+        FinancialDataFilters::new(symbol, None, None),
+        as_of,
+    );
+    query
+        .push(" AND date < ")
+        .push_bind(date)
+        .push(" ORDER BY symbol, date DESC");
+    let rows = metrics::time_query(
+        "financial_data_previous_close",
+        query.build_query_as::<FinancialDataReport>().fetch_all(db),
+    )
+    .await
+    .into_report()
+    .change_context(RouteError("financial_data"))
+    .attach("Failed to query previous closes on PostgreSQL database.")?;
+    Ok(rows
+        .into_iter()
+        .map(|mut r| {
+            r.symbol = r.symbol.trim().into();
+            r
+        })
+        .collect())
+}
+
+/// Queries the entries matching `query`, latest first, then fills the missing dates following its `fill` and
+/// `calendar` options. The query must have passed `validate_fill`.
+///
+/// Shared by every version of the `financial_data` endpoint.
+pub(crate) async fn query_financial_series(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    calendar: &CalendarConfig,
+    query: &FinancialDataQuery,
+) -> Result<Vec<FinancialDataPoint>, Report<RouteError>> {
+    let rows = query_financial_data(
+        db,
+        query.symbol.clone(),
+        query.start_date,
+        query.end_date,
+        query.as_of,
+    )
+    .await?;
+    let fill = query.fill.unwrap_or_default();
+    let (Some(start), Some(end)) = (query.start_date, query.end_date) else {
+        return Ok(rows.into_iter().map(FinancialDataPoint::from).collect());
+    };
+    if fill == FillMode::None {
+        return Ok(rows.into_iter().map(FinancialDataPoint::from).collect());
+    }
+
+    log::trace!("Listing the expected dates of the filled time series.");
+    let dates = match query.calendar.unwrap_or_default() {
+        FillCalendar::Trading => TradingCalendar::load(&mut *db, calendar)
+            .await
+            .change_context(RouteError("financial_data"))?
+            .trading_days(start, end)
+            .collect(),
+        FillCalendar::All => std::iter::successors(Some(start), |date| date.next_day())
+            .take_while(|date| *date <= end)
+            .collect(),
+    };
+    let previous = match fill {
+        FillMode::Forward => {
+            query_previous_closes(db, query.symbol.clone(), start, query.as_of).await?
+        }
+        _ => vec![],
+    };
+    Ok(fill_series(
+        query.symbol.as_deref(),
+        rows,
+        previous,
+        &dates,
+        fill,
+    ))
+}
+
+/// Builds the series of every symbol with an entry, or a previous close, on every date of `dates`,
+/// latest first, then by symbol. Saved entries are kept even when their date isn't expected.
+///
+/// The series of the requested `symbol` is built even without any entry or previous close, with empty points.
+fn fill_series(
+    symbol: Option<&str>,
+    rows: Vec<FinancialDataReport>,
+    previous: Vec<FinancialDataReport>,
+    dates: &BTreeSet<Date>,
+    fill: FillMode,
+) -> Vec<FinancialDataPoint> {
+    let mut series = BTreeMap::<String, (Option<f64>, BTreeMap<Date, FinancialDataReport>)>::new();
+    if let Some(symbol) = symbol {
+        series.entry(symbol.trim().to_string()).or_default();
+    }
+    for row in previous {
+        series.entry(row.symbol.clone()).or_default().0 = Some(row.close_price);
+    }
+    for row in rows {
+        series
+            .entry(row.symbol.clone())
+            .or_default()
+            .1
+            .insert(row.date, row);
+    }
+
+    let mut points = vec![];
+    for (symbol, (mut close, mut saved)) in series {
+        let symbol_dates = dates
+            .iter()
+            .chain(saved.keys())
+            .copied()
+            .collect::<BTreeSet<_>>();
+        for date in symbol_dates {
+            match saved.remove(&date) {
+                Some(row) => {
+                    close = Some(row.close_price);
+                    points.push(FinancialDataPoint::from(row));
+                }
+                None => {
+                    let close = close.filter(|_| fill == FillMode::Forward);
+                    points.push(FinancialDataPoint {
+                        symbol: symbol.clone(),
+                        date,
+                        open_price: close,
+                        close_price: close,
+                        volume: close.map(|_| 0),
+                        filled: true,
+                    });
+                }
+            }
+        }
+    }
+    points.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| a.symbol.cmp(&b.symbol)));
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::date;
+
+    use super::*;
+
+    fn row(symbol: &str, date: Date, close_price: f64) -> FinancialDataReport {
+        FinancialDataReport {
+            symbol: symbol.into(),
+            date,
+            open_price: close_price,
+            close_price,
+            volume: 1,
+        }
+    }
+
+    fn dates(start: Date, end: Date) -> BTreeSet<Date> {
+        std::iter::successors(Some(start), |date| date.next_day())
+            .take_while(|date| *date <= end)
+            .collect()
+    }
+
+    #[test]
+    fn requested_symbol_without_rows_is_filled_with_nulls() {
+        let dates = dates(date!(2023 - 03 - 01), date!(2023 - 03 - 03));
+        let points = fill_series(Some("IBM"), vec![], vec![], &dates, FillMode::Null);
+        assert_eq!(points.len(), 3);
+        assert_eq!(points[0].date, date!(2023 - 03 - 03));
+        assert!(points.iter().all(|point| point.symbol == "IBM"
+            && point.filled
+            && point.close_price.is_none()
+            && point.volume.is_none()));
+
+        assert!(fill_series(None, vec![], vec![], &dates, FillMode::Null).is_empty());
+    }
+
+    #[test]
+    fn forward_fill_starts_from_the_previous_close() {
+        let dates = dates(date!(2023 - 03 - 01), date!(2023 - 03 - 03));
+        let rows = vec![row("IBM", date!(2023 - 03 - 02), 2.)];
+        let previous = vec![row("IBM", date!(2023 - 02 - 28), 1.)];
+        let points = fill_series(Some("IBM"), rows, previous, &dates, FillMode::Forward);
+        let closes: Vec<(Option<f64>, bool)> = points
+            .iter()
+            .map(|point| (point.close_price, point.filled))
+            .collect();
+        assert_eq!(
+            closes,
+            [(Some(2.), true), (Some(2.), false), (Some(1.), true)]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+use error_stack::{IntoReport, Report, ResultExt};
+
+use crate::{
+    calendar::TradingCalendar,
+    config::CalendarConfig,
+    error::{ResponseError, RouteError},
+    model::{GapsQuery, ResponseInfo, SymbolGaps, SymbolGapsResponse},
+};
+
+pub(crate) const SYMBOL_NOT_FOUND: &str = "There is no entry for this symbol.";
+
+/// Finds the trading days missing from the time series of `symbol`, `None` if the symbol has no entry.
+///
+/// Shared by every version of the `symbols/{symbol}/gaps` endpoint.
+pub(crate) async fn query_symbol_gaps(
+    pool: &sqlx::PgPool,
+    calendar: &CalendarConfig,
+    symbol: &str,
+    start_date: Option<time::Date>,
+    end_date: Option<time::Date>,
+) -> Result<Option<SymbolGaps>, Report<RouteError>> {
+    let first_query = r#"
+    SELECT
+        MIN(date),
+        (
+            SELECT pruned_before
+            FROM financial_data_retention
+            WHERE symbol = CAST($1 AS BPCHAR)
+        )
+    FROM financial_data
+    WHERE symbol = CAST($1 AS BPCHAR);
+    "#;
+
+    let symbol = symbol.trim();
+    log::trace!("Querying first date of `{}` from database.", symbol);
+    let (first, pruned_before) =
+        sqlx::query_as::<_, (Option<time::Date>, Option<time::Date>)>(first_query)
+            .bind(symbol)
+            .fetch_one(pool)
+            .await
+            .into_report()
+            .change_context(RouteError("gaps"))
+            .attach("Failed to query first date on Postgres database.")?;
+    let Some(first) = first else {
+        return Ok(None);
+    };
+
+    // Days pruned by the retention policies are not checked.
+    let start_date = start_date.unwrap_or(first);
+    let start_date =
+        pruned_before.map_or(start_date, |pruned_before| start_date.max(pruned_before));
+    let end_date = end_date.unwrap_or_else(|| {
+        let today = time::OffsetDateTime::now_utc().date();
+        today.previous_day().unwrap_or(today)
+    });
+    let calendar = TradingCalendar::load(pool, calendar)
+        .await
+        .change_context(RouteError("gaps"))?;
+    let missing = calendar
+        .missing_days(pool, symbol, start_date, end_date)
+        .await
+        .change_context(RouteError("gaps"))?;
+    Ok(Some(SymbolGaps {
+        symbol: symbol.into(),
+        start_date,
+        end_date,
+        trading_days: calendar.trading_days(start_date, end_date).count(),
+        missing,
+    }))
+}
+
+/// `symbols/{symbol}/gaps` endpoint.
+///
+/// Lists the trading days of the NYSE calendar on which the time series of a global equity has no entry,
+/// telling missing days apart from weekends and holidays.
+///
+/// # Query arguments
+/// * `start_date`: Optional => First date checked. Defaults to the first date saved for the symbol.
+///   Never before the first day kept by the retention policies.
+/// * `end_date`: Optional => Last date checked. Defaults to yesterday.
+#[utoipa::path(
+    get,
+    path = "/api/v1/symbols/{symbol}/gaps",
+    tag = "data",
+    params(("symbol" = String, Path, description = "Global equity to check."), GapsQuery),
+    responses(
+        (status = 200, description = "Missing trading days, or `null` if the symbol has no entry.", body = SymbolGapsResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn gaps(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(calendar): Extension<CalendarConfig>,
+    Path(symbol): Path<String>,
+    Query(GapsQuery {
+        start_date,
+        end_date,
+    }): Query<GapsQuery>,
+) -> Result<Json<SymbolGapsResponse>, ResponseError<RouteError>> {
+    log::trace!("Received request to `gaps`.");
+    let data = query_symbol_gaps(&pool, &calendar, &symbol, start_date, end_date).await?;
+
+    let error = match &data {
+        Some(_) => "".into(),
+        None => SYMBOL_NOT_FOUND.into(),
+    };
+
+    log::trace!("Responding from `gaps` endpoint.");
+    Ok(Json(SymbolGapsResponse {
+        data,
+        info: ResponseInfo { error },
+    }))
+}
@@ -0,0 +1,36 @@
+use axum::{
+    response::{Html, IntoResponse},
+    Extension, Json,
+};
+
+use crate::{
+    graphql::{prepare_request, ApiSchema},
+    model::ApiKey,
+};
+
+/// `graphql` endpoint.
+///
+/// Runs a GraphQL query over the time series, the symbols and their statistics.
+/// The schema can be introspected, or explored on `/graphql/playground`.
+pub async fn graphql(
+    Extension(schema): Extension<ApiSchema>,
+    Extension(pool): Extension<sqlx::PgPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    log::trace!("Received request to `graphql`.");
+    let request = prepare_request(request, &pool, api_key.map(|Extension(key)| key));
+    let response = schema.execute(request).await;
+
+    log::trace!("Responding from `graphql` endpoint.");
+    Json(response)
+}
+
+/// `graphql/playground` endpoint.
+///
+/// Serves GraphQL Playground, which sends its queries to `graphql`.
+pub async fn graphql_playground() -> impl IntoResponse {
+    Html(async_graphql::http::playground_source(
+        async_graphql::http::GraphQLPlaygroundConfig::new("/graphql"),
+    ))
+}
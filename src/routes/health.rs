@@ -0,0 +1,109 @@
+use axum::{http::StatusCode, Extension, Json};
+
+use crate::{
+    model::{ReadinessReport, ResponseInfo, StatusReport, StatusResponse},
+    tasks::HealthState,
+};
+
+/// Status code answered to probes, so that replicas which are not ready are taken out of load balancing.
+pub(crate) fn probe_status(ready: bool) -> StatusCode {
+    match ready {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+/// `healthz` endpoint.
+///
+/// Liveness probe, answers as long as the process is running.
+#[utoipa::path(
+    get,
+    path = "/healthz",
+    tag = "health",
+    responses((status = 200, description = "The process is running.", body = String, content_type = "text/plain")),
+)]
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// `readyz` endpoint.
+///
+/// Readiness probe, answers `503 Service Unavailable` unless the database can run a query,
+/// the schema is applied, and the last successful ingestion is within the freshness SLO.
+#[utoipa::path(
+    get,
+    path = "/readyz",
+    tag = "health",
+    responses(
+        (status = 200, description = "Every check passed.", body = ReadinessReport),
+        (status = 503, description = "A check failed.", body = ReadinessReport),
+    ),
+)]
+pub async fn readyz(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(health): Extension<HealthState>,
+) -> (StatusCode, Json<ReadinessReport>) {
+    log::trace!("Received request to `readyz`.");
+    let (checks, _) = health.readiness(&pool).await;
+    let ready = checks.iter().all(|check| check.healthy);
+    if !ready {
+        log::warn!("Server is not ready: {:?}", checks);
+    }
+    (probe_status(ready), Json(ReadinessReport { ready, checks }))
+}
+
+/// `status` endpoint.
+///
+/// Returns the readiness checks, the database pool connections, the reachability of Alpha Vantage,
+/// the last successful ingestion, the build version, and the uptime.
+/// Answers `503 Service Unavailable` when the server is not ready, Alpha Vantage being unreachable doesn't count.
+#[utoipa::path(
+    get,
+    path = "/api/v1/status",
+    tag = "health",
+    responses(
+        (status = 200, description = "Detailed status of a ready server.", body = StatusResponse),
+        (status = 503, description = "Detailed status of a server which is not ready.", body = StatusResponse),
+    ),
+)]
+pub async fn status(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(health): Extension<HealthState>,
+) -> (StatusCode, Json<StatusResponse>) {
+    log::trace!("Received request to `status`.");
+    let report = status_report(&pool, &health).await;
+    let error = report
+        .checks
+        .iter()
+        .filter(|check| !check.healthy)
+        .map(|check| format!("Check `{}` has failed: {}", check.name, check.detail))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    log::trace!("Responding from `status` endpoint.");
+    (
+        probe_status(report.ready),
+        Json(StatusResponse {
+            data: report,
+            info: ResponseInfo { error },
+        }),
+    )
+}
+
+/// Runs the readiness checks and gathers the status of the server.
+///
+/// Shared by every version of the `status` endpoint.
+pub(crate) async fn status_report(pool: &sqlx::PgPool, health: &HealthState) -> StatusReport {
+    let ((checks, last_ingestion), provider) =
+        tokio::join!(health.readiness(pool), health.provider_status());
+    StatusReport {
+        version: env!("CARGO_PKG_VERSION").into(),
+        started_at: health.started_at,
+        uptime_secs: health.started.elapsed().as_secs(),
+        ready: checks.iter().all(|check| check.healthy),
+        checks,
+        pool: health.pool_status(pool),
+        provider,
+        last_ingestion,
+    }
+}
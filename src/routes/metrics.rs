@@ -0,0 +1,51 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use prometheus::{Encoder, TextEncoder};
+
+use crate::{metrics, tasks::HealthState};
+
+/// Updates the gauges that are read from the database pool and the latest entries on each scrape.
+async fn update_gauges(pool: &sqlx::PgPool, health: &HealthState) {
+    let size = i64::from(pool.size());
+    let idle = pool.num_idle() as i64;
+    metrics::DB_POOL_CONNECTIONS
+        .with_label_values(&["idle"])
+        .set(idle);
+    metrics::DB_POOL_CONNECTIONS
+        .with_label_values(&["in_use"])
+        .set(size - idle);
+    metrics::DB_POOL_MAX_CONNECTIONS.set(i64::from(health.max_connections));
+    health.latest_entries.update_gauge(pool).await;
+}
+
+/// `metrics` endpoint.
+///
+/// Returns every metric in the Prometheus text format.
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "health",
+    responses((status = 200, description = "Metrics in the Prometheus text format.", body = String, content_type = "text/plain")),
+)]
+pub async fn metrics(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(health): Extension<HealthState>,
+) -> Response {
+    log::trace!("Received request to `metrics`.");
+    update_gauges(&pool, &health).await;
+
+    let encoder = TextEncoder::new();
+    let mut body = vec![];
+    if let Err(err) = encoder.encode(&metrics::REGISTRY.gather(), &mut body) {
+        log::error!("Failed to encode metrics: {}", err);
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    }
+    (
+        [(header::CONTENT_TYPE, encoder.format_type().to_string())],
+        body,
+    )
+        .into_response()
+}
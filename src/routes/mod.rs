@@ -1,5 +1,34 @@
+mod alerts;
+pub use alerts::*;
+
+mod export;
+pub use export::*;
+
+mod health;
+pub use health::*;
+
 mod financial_data;
-pub use financial_data::financial_data;
+pub use financial_data::*;
+
+mod financial_data_fill;
+pub(crate) use financial_data_fill::*;
+
+mod financial_data_changes;
+pub use financial_data_changes::*;
+
+mod gaps;
+pub use gaps::*;
+
+mod graphql;
+pub use graphql::*;
+
+mod metrics;
+pub use metrics::*;
 
 mod statistics;
-pub use statistics::statistics;
+pub use statistics::*;
+
+mod stream;
+pub use stream::*;
+
+pub mod v2;
@@ -1,9 +1,11 @@
-use axum::{extract::Query, Json};
-use error_stack::{IntoReport, ResultExt};
+use axum::{extract::Query, http::HeaderMap, response::Response, Extension};
+use error_stack::{IntoReport, Report, ResultExt};
 
 use crate::{
+    cache::{CacheKey, ResponseCache},
     error::{ResponseError, RouteError},
-    model::{ResponseInfo, StatisticsQuery, StatisticsReport, StatisticsResponse},
+    metrics,
+    model::{ApiVersion, ResponseInfo, StatisticsQuery, StatisticsReport, StatisticsResponse},
 };
 
 /// `statistics` endpoint.  
@@ -14,42 +16,58 @@ use crate::{
 /// * `symbol` => Which global equity to query.
 /// * `start_date` => Filters out dates earlier than this date.
 /// * `end_date` => Filters out dates later than this date.
+///
+/// Responses are cached until the time series of the equity is updated.
+#[utoipa::path(
+    get,
+    path = "/api/v1/statistics",
+    tag = "analytics",
+    params(StatisticsQuery),
+    responses(
+        (status = 200, description = "Averages of the equity over the date range.", body = StatisticsResponse),
+        (status = 304, description = "The `If-None-Match` header matches the `ETag` of the response."),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `read:analytics`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
 pub async fn statistics(
     mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Extension(cache): Extension<ResponseCache>,
+    headers: HeaderMap,
     Query(StatisticsQuery {
         symbol,
         start_date,
         end_date,
     }): Query<StatisticsQuery>,
-) -> Result<Json<StatisticsResponse>, ResponseError<RouteError>> {
+) -> Result<Response, ResponseError<RouteError>> {
     log::trace!("Received request to `statistics`.");
+    // Symbols are stored in uppercase, and cached responses are shared by every spelling.
+    let symbol = symbol.trim().to_uppercase();
 
-    let query_str = r#"
-    SELECT *
-    FROM (
-        SELECT
-            $1 as symbol,
-            $2 as start_date,
-            $3 as end_date,
-            AVG(open_price) as average_daily_open_price,
-            AVG(close_price) as average_daily_close_price,
-            CAST(AVG(volume) as FLOAT8) as average_daily_volume
-        FROM financial_data
-        WHERE symbol = COALESCE($1, symbol) AND date BETWEEN COALESCE($2, date) AND COALESCE($3, date)
-    ) as statistics
-    WHERE average_daily_volume IS NOT NULL;
-    "#;
-
-    log::trace!("Querying statistics from database for a given global equity and date range.");
-    let data = sqlx::query_as::<_, StatisticsReport>(query_str)
-        .bind(symbol)
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_optional(&mut db)
+    let key = CacheKey::new(
+        "statistics",
+        Some(&symbol),
+        (ApiVersion::V1, start_date, end_date),
+    );
+    cache
+        .respond(
+            key,
+            &headers,
+            statistics_response(&mut db, symbol, start_date, end_date),
+        )
         .await
-        .into_report()
-        .change_context(RouteError("statistics"))
-        .attach("Failed to query financial data on Postgres database.")?;
+}
+
+/// Builds the response of the `statistics` endpoint, with the reason for an empty response in `info.error`.
+async fn statistics_response(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    symbol: String,
+    start_date: time::Date,
+    end_date: time::Date,
+) -> Result<StatisticsResponse, ResponseError<RouteError>> {
+    let data = query_statistics(db, symbol, start_date, end_date).await?;
 
     log::trace!(
         "Verifying that a response from the database was returned and writing a matching response."
@@ -62,8 +80,103 @@ pub async fn statistics(
     };
 
     log::trace!("Responding from `statistics` endpoint.");
-    Ok(Json(StatisticsResponse {
+    Ok(StatisticsResponse {
         data,
         info: ResponseInfo { error },
-    }))
+    })
+}
+
+/// Statistics combining the `financial_data_monthly` aggregates of the whole months of the date range
+/// with the entries of the partial months at its edges, and of the months whose aggregates are stale.
+/// Months pruned by the retention policies only have aggregates, so their partial months are left out.
+///
+/// Returns the same reports as averaging every entry of the date range, which `benches/statistics_bench.rs` compares it to.
+pub const AGGREGATED_STATISTICS_QUERY: &str = r#"
+    WITH stale AS (
+        SELECT month
+        FROM financial_data_stale_months
+        WHERE symbol = CAST($1 AS BPCHAR)
+            AND month >= CAST(date_trunc('month', CAST($2 AS DATE) - 1) + INTERVAL '1 month' AS DATE)
+            AND month < CAST(date_trunc('month', CAST($3 AS DATE) + 1) AS DATE)
+            AND NOT EXISTS (
+                SELECT 1
+                FROM financial_data_retention
+                WHERE symbol = CAST($1 AS BPCHAR) AND month < pruned_before
+            )
+    ),
+    months AS (
+        SELECT entries, sum_open_price, sum_close_price, sum_volume
+        FROM financial_data_monthly
+        WHERE symbol = CAST($1 AS BPCHAR)
+            AND month >= CAST(date_trunc('month', CAST($2 AS DATE) - 1) + INTERVAL '1 month' AS DATE)
+            AND month < CAST(date_trunc('month', CAST($3 AS DATE) + 1) AS DATE)
+            AND month NOT IN (SELECT month FROM stale)
+    ),
+    entries AS (
+        SELECT open_price, close_price, volume
+        FROM financial_data
+        WHERE symbol = CAST($1 AS BPCHAR) AND (
+            (
+                date >= $2
+                AND date < LEAST(
+                    CAST(date_trunc('month', CAST($2 AS DATE) - 1) + INTERVAL '1 month' AS DATE),
+                    CAST($3 AS DATE) + 1
+                )
+            )
+            OR (
+                date >= GREATEST(CAST(date_trunc('month', CAST($3 AS DATE) + 1) AS DATE), $2)
+                AND date <= $3
+            )
+        )
+        UNION ALL
+        SELECT open_price, close_price, volume
+        FROM financial_data
+        JOIN stale ON date >= stale.month AND date < CAST(stale.month + INTERVAL '1 month' AS DATE)
+        WHERE symbol = CAST($1 AS BPCHAR)
+    ),
+    totals AS (
+        SELECT
+            SUM(entries) AS entries,
+            SUM(sum_open_price) AS sum_open_price,
+            SUM(sum_close_price) AS sum_close_price,
+            SUM(sum_volume) AS sum_volume
+        FROM (
+            SELECT * FROM months
+            UNION ALL
+            SELECT COUNT(*), SUM(open_price), SUM(close_price), SUM(volume) FROM entries
+        ) as parts
+    )
+    SELECT
+        $1 as symbol,
+        $2 as start_date,
+        $3 as end_date,
+        CAST(sum_open_price / entries AS FLOAT8) as average_daily_open_price,
+        CAST(sum_close_price / entries AS FLOAT8) as average_daily_close_price,
+        CAST(sum_volume / entries AS FLOAT8) as average_daily_volume
+    FROM totals
+    WHERE entries > 0;
+    "#;
+
+/// Computes the statistics of a global equity for a date range, `None` if it has no entries in the range.
+///
+/// Shared by every version of the `statistics` endpoint, and by the gRPC service.
+pub(crate) async fn query_statistics<'c>(
+    db: impl sqlx::PgExecutor<'c>,
+    symbol: String,
+    start_date: time::Date,
+    end_date: time::Date,
+) -> Result<Option<StatisticsReport>, Report<RouteError>> {
+    log::trace!("Querying statistics from database for a given global equity and date range.");
+    metrics::time_query(
+        "statistics",
+        sqlx::query_as::<_, StatisticsReport>(AGGREGATED_STATISTICS_QUERY)
+            .bind(symbol)
+            .bind(start_date)
+            .bind(end_date)
+            .fetch_optional(db),
+    )
+    .await
+    .into_report()
+    .change_context(RouteError("statistics"))
+    .attach("Failed to query financial data on Postgres database.")
 }
@@ -0,0 +1,75 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::Query,
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures::{Stream, StreamExt};
+use tokio::sync::broadcast::Sender;
+use tokio_stream::wrappers::{errors::BroadcastStreamRecvError, BroadcastStream};
+use tokio_util::sync::CancellationToken;
+
+use crate::model::{FinancialDataUpdate, StreamQuery};
+
+/// `stream` endpoint.  
+///
+/// Server-Sent Events stream of new or corrected time series entries, sent as soon as they are committed to the database.
+/// Each entry is sent as a `financial_data` event with a JSON body, and each deleted entry as a `deleted` event.
+/// A `lagged` event is sent when the client is too slow and entries were dropped.
+/// The stream ends when the server shuts down.
+///
+/// # Query arguments
+/// * `symbols`: Optional => Comma separated global equities to subscribe to. `None` for all equities.
+#[utoipa::path(
+    get,
+    path = "/api/v1/stream",
+    tag = "data",
+    params(StreamQuery),
+    responses(
+        (status = 200, description = "`financial_data` events carrying the updated entries, and `deleted` events carrying the deleted entries.", body = FinancialDataReport, content_type = "text/event-stream"),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn stream(
+    Extension(updates): Extension<Sender<FinancialDataUpdate>>,
+    Extension(shutdown): Extension<CancellationToken>,
+    Query(query): Query<StreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    log::trace!("Received request to `stream`.");
+    let symbols = query.symbol_list();
+
+    let events = BroadcastStream::new(updates.subscribe()).filter_map(move |update| {
+        let event = match update {
+            Ok(update)
+                if symbols.as_ref().is_none_or(|symbols| {
+                    symbols.iter().any(|symbol| symbol == update.symbol())
+                }) =>
+            {
+                let (name, report) = match &update {
+                    FinancialDataUpdate::Upserted(report) => ("financial_data", report),
+                    FinancialDataUpdate::Deleted(report) => ("deleted", report),
+                    // Entries pruned by the retention policies are too old to be followed by clients.
+                    FinancialDataUpdate::Pruned(_) => return futures::future::ready(None),
+                };
+                match Event::default().event(name).json_data(report) {
+                    Ok(event) => Some(Ok(event)),
+                    Err(err) => {
+                        log::error!("Failed to serialize `{}` event: {}", name, err);
+                        None
+                    }
+                }
+            }
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(skipped)) => Some(Ok(Event::default()
+                .event("lagged")
+                .data(skipped.to_string()))),
+        };
+        futures::future::ready(event)
+    });
+
+    Sse::new(events.take_until(shutdown.cancelled_owned())).keep_alive(KeepAlive::default())
+}
@@ -0,0 +1,190 @@
+use axum::{extract::Path, http::StatusCode, Json};
+
+use crate::{
+    error::ApiError,
+    model::{AlertEvent, AlertRule, AlertRuleRequest, DataResponse},
+    routes::{
+        insert_alert_rule, query_alert_events, query_alert_rule, query_alert_rules,
+        remove_alert_rule, replace_alert_rule,
+    },
+};
+
+const NOT_FOUND: &str = "There is no alert rule with this id.";
+
+/// `v2` `GET alerts` endpoint.
+///
+/// Returns every alert rule.
+#[utoipa::path(
+    get,
+    path = "/api/v2/alerts",
+    operation_id = "v2_list_alerts",
+    tag = "alerts",
+    responses(
+        (status = 200, description = "Every alert rule.", body = AlertRulesData),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn list_alerts(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+) -> Result<Json<DataResponse<Vec<AlertRule>>>, ApiError> {
+    log::trace!("Received request to list `v2` `alerts`.");
+    let data = query_alert_rules(&mut db).await?;
+    Ok(Json(DataResponse { data }))
+}
+
+/// `v2` `POST alerts` endpoint.
+///
+/// Creates an alert rule, answering `201 Created`. Invalid rules are answered with `400 Bad Request`.
+#[utoipa::path(
+    post,
+    path = "/api/v2/alerts",
+    operation_id = "v2_create_alert",
+    tag = "alerts",
+    request_body = AlertRuleRequest,
+    responses(
+        (status = 201, description = "Created rule.", body = AlertRuleData),
+        (status = 400, description = "Invalid alert rule.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn create_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Json(rule): Json<AlertRuleRequest>,
+) -> Result<(StatusCode, Json<DataResponse<AlertRule>>), ApiError> {
+    log::trace!("Received request to create `v2` `alerts`.");
+    if let Some(error) = rule.validate() {
+        return Err(ApiError::bad_request(error));
+    }
+    let data = insert_alert_rule(&mut db, rule).await?;
+    Ok((StatusCode::CREATED, Json(DataResponse { data })))
+}
+
+/// `v2` `GET alerts/{id}` endpoint.
+///
+/// Returns a single alert rule, or `404 Not Found`.
+#[utoipa::path(
+    get,
+    path = "/api/v2/alerts/{id}",
+    operation_id = "v2_get_alert",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    responses(
+        (status = 200, description = "Alert rule.", body = AlertRuleData),
+        (status = 404, description = "There is no alert rule with this id.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn get_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+) -> Result<Json<DataResponse<AlertRule>>, ApiError> {
+    log::trace!("Received request to get `v2` `alerts`.");
+    let data = query_alert_rule(&mut db, id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(NOT_FOUND))?;
+    Ok(Json(DataResponse { data }))
+}
+
+/// `v2` `PUT alerts/{id}` endpoint.
+///
+/// Replaces an alert rule. Takes the same body as `POST alerts`.
+#[utoipa::path(
+    put,
+    path = "/api/v2/alerts/{id}",
+    operation_id = "v2_update_alert",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    request_body = AlertRuleRequest,
+    responses(
+        (status = 200, description = "Replaced rule.", body = AlertRuleData),
+        (status = 400, description = "Invalid alert rule.", body = ApiErrorResponse),
+        (status = 404, description = "There is no alert rule with this id.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn update_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+    Json(rule): Json<AlertRuleRequest>,
+) -> Result<Json<DataResponse<AlertRule>>, ApiError> {
+    log::trace!("Received request to update `v2` `alerts`.");
+    if let Some(error) = rule.validate() {
+        return Err(ApiError::bad_request(error));
+    }
+    let data = replace_alert_rule(&mut db, id, rule)
+        .await?
+        .ok_or_else(|| ApiError::not_found(NOT_FOUND))?;
+    Ok(Json(DataResponse { data }))
+}
+
+/// `v2` `DELETE alerts/{id}` endpoint.
+///
+/// Deletes an alert rule, along with its events, returning the deleted rule.
+#[utoipa::path(
+    delete,
+    path = "/api/v2/alerts/{id}",
+    operation_id = "v2_delete_alert",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    responses(
+        (status = 200, description = "Deleted rule.", body = AlertRuleData),
+        (status = 404, description = "There is no alert rule with this id.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn delete_alert(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+) -> Result<Json<DataResponse<AlertRule>>, ApiError> {
+    log::trace!("Received request to delete `v2` `alerts`.");
+    let data = remove_alert_rule(&mut db, id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(NOT_FOUND))?;
+    Ok(Json(DataResponse { data }))
+}
+
+/// `v2` `GET alerts/{id}/events` endpoint.
+///
+/// Returns the past triggers of an alert rule, latest first. Unlike `v1`, an alert rule which
+/// doesn't exist is answered with `404 Not Found`, and one never triggered with an empty list.
+#[utoipa::path(
+    get,
+    path = "/api/v2/alerts/{id}/events",
+    operation_id = "v2_alert_events",
+    tag = "alerts",
+    params(("id" = i32, Path, description = "Identifier of the alert rule.")),
+    responses(
+        (status = 200, description = "Events triggered by the alert rule.", body = AlertEventsData),
+        (status = 404, description = "There is no alert rule with this id.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:alerts`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn alert_events(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Path(id): Path<i32>,
+) -> Result<Json<DataResponse<Vec<AlertEvent>>>, ApiError> {
+    log::trace!("Received request to `v2` `alerts` events.");
+    if query_alert_rule(&mut db, id).await?.is_none() {
+        return Err(ApiError::not_found(NOT_FOUND));
+    }
+    let data = query_alert_events(&mut db, id).await?;
+    Ok(Json(DataResponse { data }))
+}
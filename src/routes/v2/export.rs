@@ -0,0 +1,34 @@
+use axum::{extract::Query, response::Response, Extension};
+
+use crate::{error::ApiError, model::ExportQuery, routes::export_response};
+
+/// `v2` `export` endpoint.
+///
+/// Same as `v1`, with errors answered as an `ApiErrorResponse`.
+#[utoipa::path(
+    get,
+    path = "/api/v2/export",
+    operation_id = "v2_export",
+    tag = "data",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "Entries as a file attachment.", content(
+            ("application/vnd.apache.arrow.stream" = String),
+            ("application/vnd.apache.parquet" = String),
+        )),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn export(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, ApiError> {
+    log::trace!("Received request to `v2` `export`.");
+    let response = export_response(&pool, query).await?;
+
+    log::trace!("Responding from `v2` `export` endpoint.");
+    Ok(response)
+}
@@ -0,0 +1,142 @@
+use axum::{
+    extract::Query,
+    http::{request::Parts, HeaderMap},
+    response::Response,
+    Extension,
+};
+
+use crate::{
+    cache::{CacheKey, ResponseCache},
+    config::CalendarConfig,
+    error::ApiError,
+    model::{
+        ApiVersion, FillMode, FinancialDataPage, FinancialDataQuery, Pagination, ResponseFormat,
+    },
+    routes::{begin_transaction, query_financial_series, response_format, stream_financial_data},
+};
+
+/// `v2` `financial_data` endpoint.
+///
+/// Same as `v1`, except that invalid parameters, including `fill` on streamed formats, are answered with `400 Bad Request`,
+/// `pagination.page` starts at 1, and `pagination.pages` counts the last partial page.
+#[utoipa::path(
+    get,
+    path = "/api/v2/financial_data",
+    operation_id = "v2_financial_data",
+    tag = "data",
+    params(FinancialDataQuery),
+    responses(
+        (status = 200, description = "Page of entries, or every entry when streamed.", content(
+            ("application/json" = FinancialDataPage),
+            ("text/csv" = String),
+            ("application/x-ndjson" = String),
+        )),
+        (status = 304, description = "The `If-None-Match` header matches the `ETag` of the response."),
+        (status = 400, description = "Invalid page, limit, date range or fill options.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn financial_data(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(cache): Extension<ResponseCache>,
+    Extension(calendar): Extension<CalendarConfig>,
+    headers: HeaderMap,
+    Query(mut query): Query<FinancialDataQuery>,
+    mut parts: Parts,
+) -> Result<Response, ApiError> {
+    log::trace!("Received request to `v2` `financial_data`.");
+    // Symbols are stored in uppercase, and cached responses are shared by every spelling.
+    query.symbol = query.symbol.map(|symbol| symbol.trim().to_uppercase());
+
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(5);
+    if page == 0 {
+        return Err(ApiError::bad_request(
+            "Page must be a positive number bigger than 0.",
+        ));
+    }
+    if limit == 0 {
+        return Err(ApiError::bad_request(
+            "Limit must be a positive number bigger than 0.",
+        ));
+    }
+    if matches!((query.start_date, query.end_date), (Some(start), Some(end)) if start > end) {
+        return Err(ApiError::bad_request(
+            "Start date must not be later than end date.",
+        ));
+    }
+    if let Some(error) = query.validate_fill() {
+        return Err(ApiError::bad_request(error));
+    }
+
+    let format = response_format(query.format, &headers);
+    if format != ResponseFormat::Json {
+        if query.fill.unwrap_or_default() != FillMode::None {
+            return Err(ApiError::bad_request(
+                "Filling is only available for `json` responses.",
+            ));
+        }
+        return Ok(stream_financial_data(
+            pool,
+            format,
+            query.symbol,
+            query.start_date,
+            query.end_date,
+            query.as_of,
+        ));
+    }
+
+    let mut db = begin_transaction(&mut parts).await?;
+    let key = CacheKey::new(
+        "financial_data",
+        query.symbol.as_deref(),
+        (
+            ApiVersion::V2,
+            query.start_date,
+            query.end_date,
+            query.as_of,
+            query.fill,
+            query.calendar,
+            page,
+            limit,
+        ),
+    );
+    cache
+        .respond(
+            key,
+            &headers,
+            financial_data_page(&mut db, &calendar, &query, page, limit),
+        )
+        .await
+}
+
+/// Builds a page of the response of the `v2` `financial_data` endpoint. `page` starts at 1.
+async fn financial_data_page(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    calendar: &CalendarConfig,
+    query: &FinancialDataQuery,
+    page: usize,
+    limit: usize,
+) -> Result<FinancialDataPage, ApiError> {
+    let rows = query_financial_series(db, calendar, query).await?;
+    let count = rows.len();
+    let data = rows
+        .into_iter()
+        .skip((page - 1) * limit)
+        .take(limit)
+        .collect();
+
+    log::trace!("Responding from `v2` `financial_data` endpoint.");
+    Ok(FinancialDataPage {
+        data,
+        pagination: Pagination {
+            count,
+            page,
+            limit,
+            pages: count.div_ceil(limit),
+        },
+    })
+}
@@ -0,0 +1,109 @@
+use axum::{extract::Path, Extension, Json};
+
+use crate::{
+    error::ApiError,
+    model::{ApiKey, DataResponse, FinancialDataReport, FinancialDataValues},
+    routes::{remove_entry, validate_batch, write_entries, ENTRY_NOT_FOUND},
+};
+
+/// `v2` `PUT financial_data/{symbol}/{date}` endpoint.
+///
+/// Inserts or corrects an entry. Invalid entries are answered with `400 Bad Request`.
+#[utoipa::path(
+    put,
+    path = "/api/v2/financial_data/{symbol}/{date}",
+    operation_id = "v2_put_financial_data",
+    tag = "ingest",
+    params(
+        ("symbol" = String, Path, description = "Global equity of the entry."),
+        ("date" = String, Path, description = "Date of the entry, as `YYYY-MM-DD`."),
+    ),
+    request_body = FinancialDataValues,
+    responses(
+        (status = 200, description = "Written entry.", body = FinancialDataEntryData),
+        (status = 400, description = "Invalid entry.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:ingest`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn put_financial_data(
+    Extension(pool): Extension<sqlx::PgPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Path((symbol, date)): Path<(String, time::Date)>,
+    Json(values): Json<FinancialDataValues>,
+) -> Result<Json<DataResponse<FinancialDataReport>>, ApiError> {
+    log::trace!("Received request to put `v2` `financial_data`.");
+    let entry = values.into_report(&symbol, date);
+    if let Some(error) = entry.validate() {
+        return Err(ApiError::bad_request(error));
+    }
+    write_entries(pool, vec![entry.clone()], api_key.as_deref()).await?;
+    Ok(Json(DataResponse { data: entry }))
+}
+
+/// `v2` `POST financial_data` endpoint.
+///
+/// Inserts or corrects a batch of entries, returning the new or changed ones.
+/// Invalid batches are answered with `400 Bad Request`, without writing any entry.
+#[utoipa::path(
+    post,
+    path = "/api/v2/financial_data",
+    operation_id = "v2_post_financial_data",
+    tag = "ingest",
+    request_body = Vec<FinancialDataReport>,
+    responses(
+        (status = 200, description = "New or changed entries.", body = FinancialDataEntriesData),
+        (status = 400, description = "Invalid batch.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:ingest`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn post_financial_data(
+    Extension(pool): Extension<sqlx::PgPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Json(rows): Json<Vec<FinancialDataReport>>,
+) -> Result<Json<DataResponse<Vec<FinancialDataReport>>>, ApiError> {
+    log::trace!("Received request to post `v2` `financial_data`.");
+    if let Some(error) = validate_batch(&rows) {
+        return Err(ApiError::bad_request(error));
+    }
+    let data = write_entries(pool, rows, api_key.as_deref()).await?;
+    Ok(Json(DataResponse { data }))
+}
+
+/// `v2` `DELETE financial_data/{symbol}/{date}` endpoint.
+///
+/// Deletes an entry, returning its last values, or `404 Not Found`.
+#[utoipa::path(
+    delete,
+    path = "/api/v2/financial_data/{symbol}/{date}",
+    operation_id = "v2_delete_financial_data",
+    tag = "ingest",
+    params(
+        ("symbol" = String, Path, description = "Global equity of the entry."),
+        ("date" = String, Path, description = "Date of the entry, as `YYYY-MM-DD`."),
+    ),
+    responses(
+        (status = 200, description = "Deleted entry.", body = FinancialDataEntryData),
+        (status = 404, description = "There is no entry for this symbol and date.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `admin:ingest`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn delete_financial_data(
+    Extension(pool): Extension<sqlx::PgPool>,
+    api_key: Option<Extension<ApiKey>>,
+    Path((symbol, date)): Path<(String, time::Date)>,
+) -> Result<Json<DataResponse<FinancialDataReport>>, ApiError> {
+    log::trace!("Received request to delete `v2` `financial_data`.");
+    let data = remove_entry(pool, &symbol, date, api_key.as_deref())
+        .await?
+        .ok_or_else(|| ApiError::not_found(ENTRY_NOT_FOUND))?;
+    Ok(Json(DataResponse { data }))
+}
@@ -0,0 +1,52 @@
+use axum::{
+    extract::{Path, Query},
+    Extension, Json,
+};
+
+use crate::{
+    config::CalendarConfig,
+    error::ApiError,
+    model::{DataResponse, GapsQuery, SymbolGaps},
+    routes::{query_symbol_gaps, SYMBOL_NOT_FOUND},
+};
+
+/// `v2` `symbols/{symbol}/gaps` endpoint.
+///
+/// Same as `v1`, except that symbols without entries are answered with `404 Not Found`,
+/// and an inverted date range with `400 Bad Request`.
+#[utoipa::path(
+    get,
+    path = "/api/v2/symbols/{symbol}/gaps",
+    operation_id = "v2_gaps",
+    tag = "data",
+    params(("symbol" = String, Path, description = "Global equity to check."), GapsQuery),
+    responses(
+        (status = 200, description = "Missing trading days.", body = SymbolGapsData),
+        (status = 400, description = "Invalid date range.", body = ApiErrorResponse),
+        (status = 404, description = "There is no entry for this symbol.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn gaps(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(calendar): Extension<CalendarConfig>,
+    Path(symbol): Path<String>,
+    Query(GapsQuery {
+        start_date,
+        end_date,
+    }): Query<GapsQuery>,
+) -> Result<Json<DataResponse<SymbolGaps>>, ApiError> {
+    log::trace!("Received request to `v2` `gaps`.");
+    if matches!((start_date, end_date), (Some(start), Some(end)) if start > end) {
+        return Err(ApiError::bad_request(
+            "Start date must not be later than end date.",
+        ));
+    }
+    let data = query_symbol_gaps(&pool, &calendar, &symbol, start_date, end_date)
+        .await?
+        .ok_or_else(|| ApiError::not_found(SYMBOL_NOT_FOUND))?;
+    Ok(Json(DataResponse { data }))
+}
@@ -0,0 +1,28 @@
+//! Handlers of `/api/v2`.
+//!
+//! Errors are answered with their status code and an `ApiErrorResponse`, and successful responses
+//! with a `data` field, without `info`. The queries are shared with the handlers of `/api/v1`.
+
+mod alerts;
+pub use alerts::*;
+
+mod export;
+pub use export::*;
+
+mod financial_data;
+pub use financial_data::*;
+
+mod financial_data_changes;
+pub use financial_data_changes::*;
+
+mod gaps;
+pub use gaps::*;
+
+mod statistics;
+pub use statistics::*;
+
+mod status;
+pub use status::*;
+
+mod stream;
+pub use stream::*;
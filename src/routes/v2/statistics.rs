@@ -0,0 +1,80 @@
+use axum::{extract::Query, http::HeaderMap, response::Response, Extension};
+
+use crate::{
+    cache::{CacheKey, ResponseCache},
+    error::ApiError,
+    model::{ApiVersion, DataResponse, StatisticsQuery, StatisticsReport},
+    routes::query_statistics,
+};
+
+/// `v2` `statistics` endpoint.
+///
+/// Same as `v1`, except that a date range without entries is answered with `404 Not Found`,
+/// and an inverted date range with `400 Bad Request`.
+#[utoipa::path(
+    get,
+    path = "/api/v2/statistics",
+    operation_id = "v2_statistics",
+    tag = "analytics",
+    params(StatisticsQuery),
+    responses(
+        (status = 200, description = "Averages of the equity over the date range.", body = StatisticsData),
+        (status = 304, description = "The `If-None-Match` header matches the `ETag` of the response."),
+        (status = 400, description = "Start date later than end date.", body = ApiErrorResponse),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `read:analytics`.", body = ApiErrorResponse),
+        (status = 404, description = "The equity has no entries in the date range.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn statistics(
+    mut db: axum_sqlx_tx::Tx<sqlx::Postgres>,
+    Extension(cache): Extension<ResponseCache>,
+    headers: HeaderMap,
+    Query(StatisticsQuery {
+        symbol,
+        start_date,
+        end_date,
+    }): Query<StatisticsQuery>,
+) -> Result<Response, ApiError> {
+    log::trace!("Received request to `v2` `statistics`.");
+    // Symbols are stored in uppercase, and cached responses are shared by every spelling.
+    let symbol = symbol.trim().to_uppercase();
+
+    if start_date > end_date {
+        return Err(ApiError::bad_request(
+            "Start date must not be later than end date.",
+        ));
+    }
+
+    let key = CacheKey::new(
+        "statistics",
+        Some(&symbol),
+        (ApiVersion::V2, start_date, end_date),
+    );
+    cache
+        .respond(
+            key,
+            &headers,
+            statistics_data(&mut db, symbol, start_date, end_date),
+        )
+        .await
+}
+
+/// Builds the response of the `v2` `statistics` endpoint.
+async fn statistics_data(
+    db: &mut axum_sqlx_tx::Tx<sqlx::Postgres>,
+    symbol: String,
+    start_date: time::Date,
+    end_date: time::Date,
+) -> Result<DataResponse<StatisticsReport>, ApiError> {
+    let data = query_statistics(db, symbol, start_date, end_date)
+        .await?
+        .ok_or_else(|| {
+            ApiError::not_found("The equity has no entries in the date range, verify the symbol.")
+        })?;
+
+    log::trace!("Responding from `v2` `statistics` endpoint.");
+    Ok(DataResponse { data })
+}
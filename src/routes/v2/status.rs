@@ -0,0 +1,34 @@
+use axum::{http::StatusCode, Extension, Json};
+
+use crate::{
+    model::{DataResponse, StatusReport},
+    routes::{probe_status, status_report},
+    tasks::HealthState,
+};
+
+/// `v2` `status` endpoint.
+///
+/// Same as `v1`, without the failed checks repeated in `info.error`.
+#[utoipa::path(
+    get,
+    path = "/api/v2/status",
+    operation_id = "v2_status",
+    tag = "health",
+    responses(
+        (status = 200, description = "Detailed status of a ready server.", body = StatusData),
+        (status = 503, description = "Detailed status of a server which is not ready.", body = StatusData),
+    ),
+)]
+pub async fn status(
+    Extension(pool): Extension<sqlx::PgPool>,
+    Extension(health): Extension<HealthState>,
+) -> (StatusCode, Json<DataResponse<StatusReport>>) {
+    log::trace!("Received request to `v2` `status`.");
+    let report = status_report(&pool, &health).await;
+
+    log::trace!("Responding from `v2` `status` endpoint.");
+    (
+        probe_status(report.ready),
+        Json(DataResponse { data: report }),
+    )
+}
@@ -0,0 +1,40 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::Query,
+    response::sse::{Event, Sse},
+    Extension,
+};
+use futures::Stream;
+use tokio::sync::broadcast::Sender;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    model::{FinancialDataUpdate, StreamQuery},
+    routes,
+};
+
+/// `v2` `stream` endpoint.
+///
+/// Same events as `v1`.
+#[utoipa::path(
+    get,
+    path = "/api/v2/stream",
+    operation_id = "v2_stream",
+    tag = "data",
+    params(StreamQuery),
+    responses(
+        (status = 200, description = "`financial_data` events carrying the updated entries, and `deleted` events carrying the deleted entries.", body = FinancialDataReport, content_type = "text/event-stream"),
+        (status = 401, description = "Missing, invalid or revoked API key.", body = ApiErrorResponse),
+        (status = 403, description = "API key missing the scope `read:data`.", body = ApiErrorResponse),
+        (status = 429, description = "Rate limit or daily quota exceeded.", body = ApiErrorResponse),
+    ),
+    security(("bearer" = []), ("api_key" = [])),
+)]
+pub async fn stream(
+    updates: Extension<Sender<FinancialDataUpdate>>,
+    shutdown: Extension<CancellationToken>,
+    query: Query<StreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    routes::stream(updates, shutdown, query).await
+}
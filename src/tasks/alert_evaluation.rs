@@ -0,0 +1,275 @@
+use std::net::SocketAddr;
+
+use error_stack::{IntoReport, Result, ResultExt};
+use serde::Serialize;
+use sqlx::FromRow;
+use tokio::task::JoinHandle;
+
+use crate::{
+    error::AlertError,
+    model::{is_public_address, AlertEvent, AlertKind, AlertRule, FinancialDataReport},
+};
+
+/// Number of times a webhook delivery is attempted before giving up.
+const MAX_DELIVERY_ATTEMPTS: u32 = 4;
+
+/// Timeout of each webhook delivery attempt.
+const DELIVERY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Number of previous entries averaged for `AlertKind::VolumeSpike`.
+const VOLUME_AVERAGE_WINDOW: i64 = 20;
+
+/// Values of the entries preceding the one being evaluated.
+#[derive(Debug, FromRow)]
+struct PreviousEntries {
+    previous_close: Option<f64>,
+    average_volume: Option<f64>,
+}
+
+/// Body of the requests sent to webhooks.
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    rule: &'a AlertRule,
+    event: &'a AlertEvent,
+    financial_data: &'a FinancialDataReport,
+}
+
+/// Checks if `rule` is triggered by `row`, returning the value that triggered it.
+fn trigger_value(
+    rule: &AlertRule,
+    row: &FinancialDataReport,
+    previous: &PreviousEntries,
+) -> Option<f64> {
+    match rule.kind {
+        AlertKind::PriceCross => previous.previous_close.and_then(|previous_close| {
+            let crossed_up = previous_close < rule.threshold && row.close_price >= rule.threshold;
+            let crossed_down = previous_close > rule.threshold && row.close_price <= rule.threshold;
+            (crossed_up || crossed_down).then_some(row.close_price)
+        }),
+        AlertKind::DailyMove => previous
+            .previous_close
+            .filter(|previous_close| *previous_close != 0.)
+            .map(|previous_close| (row.close_price - previous_close) / previous_close * 100.)
+            .filter(|change| change.abs() >= rule.threshold),
+        AlertKind::VolumeSpike => previous
+            .average_volume
+            .filter(|average| *average > 0.)
+            .map(|average| f64::from(row.volume) / average)
+            .filter(|ratio| *ratio >= rule.threshold),
+    }
+}
+
+/// Evaluates the alert rules against new or corrected entries, recording the triggered events
+/// and delivering them concurrently to the webhooks of the rules.
+///
+/// Deliveries run in the background, so that slow webhooks don't hold back the ingestion or the write endpoints.
+/// Returns their handles, only awaited by short-lived processes like the `import` command.
+pub async fn evaluate_alerts(
+    pool: sqlx::PgPool,
+    rows: &[FinancialDataReport],
+) -> Result<Vec<JoinHandle<()>>, AlertError> {
+    let rules_query = r#"
+    SELECT *
+    FROM alert_rules
+    WHERE symbol = ANY($1);"#;
+    let previous_query = r#"
+    SELECT
+        (
+            SELECT close_price
+            FROM financial_data
+            WHERE symbol = CAST($1 AS BPCHAR) AND date < $2
+            ORDER BY date DESC
+            LIMIT 1
+        ) as previous_close,
+        (
+            SELECT CAST(AVG(volume) as FLOAT8)
+            FROM (
+                SELECT volume
+                FROM financial_data
+                WHERE symbol = CAST($1 AS BPCHAR) AND date < $2
+                ORDER BY date DESC
+                LIMIT $3
+            ) as previous
+        ) as average_volume;"#;
+    let event_query = r#"
+    INSERT INTO alert_events (rule_id, symbol, date, value)
+    VALUES ($1, $2, $3, $4)
+    ON CONFLICT (rule_id, date) DO NOTHING
+    RETURNING *;"#;
+
+    if rows.is_empty() {
+        return Ok(vec![]);
+    }
+
+    log::trace!("Querying alert rules for updated symbols.");
+    let symbols: Vec<String> = rows.iter().map(|r| r.symbol.trim().to_string()).collect();
+    let rules = sqlx::query_as::<_, AlertRule>(rules_query)
+        .bind(symbols)
+        .fetch_all(&pool)
+        .await
+        .into_report()
+        .change_context(AlertError)
+        .attach("Failed to query alert rules on Postgres database.")?;
+    if rules.is_empty() {
+        return Ok(vec![]);
+    }
+
+    log::trace!("Evaluating alert rules against updated entries.");
+    let mut deliveries = vec![];
+    for row in rows.iter() {
+        let matching_rules: Vec<&AlertRule> = rules
+            .iter()
+            .filter(|rule| rule.symbol.trim() == row.symbol.trim())
+            .collect();
+        if matching_rules.is_empty() {
+            continue;
+        }
+
+        let previous = sqlx::query_as::<_, PreviousEntries>(previous_query)
+            .bind(row.symbol.trim())
+            .bind(row.date)
+            .bind(VOLUME_AVERAGE_WINDOW)
+            .fetch_one(&pool)
+            .await
+            .into_report()
+            .change_context(AlertError)
+            .attach("Failed to query previous entries on Postgres database.")?;
+
+        for rule in matching_rules {
+            let Some(value) = trigger_value(rule, row, &previous) else {
+                continue;
+            };
+            let event = sqlx::query_as::<_, AlertEvent>(event_query)
+                .bind(rule.id)
+                .bind(row.symbol.trim())
+                .bind(row.date)
+                .bind(value)
+                .fetch_optional(&pool)
+                .await
+                .into_report()
+                .change_context(AlertError)
+                .attach("Failed to record alert event on Postgres database.")?;
+
+            // Already triggered for this date, e.g. by a previous version of the entry.
+            let Some(event) = event else {
+                continue;
+            };
+            log::info!(
+                "Alert rule `{}` was triggered for `{}` on {}.",
+                rule.id,
+                row.symbol.trim(),
+                row.date
+            );
+            deliveries.push(tokio::spawn(deliver_webhook(
+                pool.clone(),
+                rule.clone(),
+                event,
+                row.clone(),
+            )));
+        }
+    }
+
+    log::trace!("Delivering {} alert events.", deliveries.len());
+    Ok(deliveries)
+}
+
+/// Builds a client sending requests to the host of a webhook, pinned to its public addresses.
+///
+/// The host is resolved on every attempt and refused if any of its addresses is loopback, private or link-local,
+/// so that a rule can't reach internal services, even through a DNS record changed after it was created.
+/// Redirects are not followed for the same reason.
+async fn webhook_client(url: &str) -> std::result::Result<reqwest::Client, String> {
+    let url = reqwest::Url::parse(url).map_err(|err| err.to_string())?;
+    let host = url.host_str().ok_or("Webhook URL has no host.")?;
+    let port = url
+        .port_or_known_default()
+        .ok_or("Webhook URL has no port.")?;
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.trim_matches(['[', ']']), port))
+        .await
+        .map_err(|err| format!("Failed to resolve `{}`: {}", host, err))?
+        .collect();
+    if addrs.is_empty() || addrs.iter().any(|addr| !is_public_address(addr.ip())) {
+        return Err(format!(
+            "Webhook host `{}` does not resolve to public addresses only.",
+            host
+        ));
+    }
+    reqwest::Client::builder()
+        .timeout(DELIVERY_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve_to_addrs(host, &addrs)
+        .build()
+        .map_err(|err| err.to_string())
+}
+
+/// Sends an alert event to the webhook of its rule, retrying with exponential backoff.
+///
+/// Every attempt is recorded on the `alert_deliveries` table, including the ones refused by `webhook_client`.
+async fn deliver_webhook(
+    pool: sqlx::PgPool,
+    rule: AlertRule,
+    event: AlertEvent,
+    row: FinancialDataReport,
+) {
+    let delivery_query = r#"
+    INSERT INTO alert_deliveries (event_id, attempt, status_code, error)
+    VALUES ($1, $2, $3, $4);"#;
+
+    let payload = WebhookPayload {
+        rule: &rule,
+        event: &event,
+        financial_data: &row,
+    };
+    let mut backoff = std::time::Duration::from_secs(1);
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        log::trace!(
+            "Delivering alert event `{}`, attempt {}.",
+            event.id,
+            attempt
+        );
+        let (status_code, error) = match webhook_client(&rule.webhook_url).await {
+            Ok(client) => match client.post(&rule.webhook_url).json(&payload).send().await {
+                Ok(resp) if resp.status().is_success() => {
+                    (Some(i32::from(resp.status().as_u16())), None)
+                }
+                Ok(resp) => (
+                    Some(i32::from(resp.status().as_u16())),
+                    Some(format!("Webhook responded with `{}`.", resp.status())),
+                ),
+                Err(err) => (None, Some(err.to_string())),
+            },
+            Err(err) => (None, Some(err)),
+        };
+
+        if let Err(err) = sqlx::query(delivery_query)
+            .bind(event.id)
+            .bind(attempt as i32)
+            .bind(status_code)
+            .bind(&error)
+            .execute(&pool)
+            .await
+        {
+            log::error!(
+                "Failed to record delivery of alert event `{}`: {}",
+                event.id,
+                err
+            );
+        }
+
+        match error {
+            None => return,
+            Some(error) => {
+                log::warn!(
+                    "Delivery of alert event `{}` has failed: {}",
+                    event.id,
+                    error
+                );
+            }
+        }
+        if attempt < MAX_DELIVERY_ATTEMPTS {
+            tokio::time::sleep(backoff).await;
+            backoff *= 2;
+        }
+    }
+    log::error!("Gave up delivering alert event `{}`.", event.id);
+}
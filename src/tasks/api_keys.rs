@@ -0,0 +1,139 @@
+use error_stack::{IntoReport, Result, ResultExt};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::ApiKeyError,
+    model::{ApiKey, Scope},
+};
+
+/// Start of every API key, so that leaked keys are easy to search for.
+const API_KEY_PREFIX: &str = "fdk_";
+
+/// Number of characters of the key stored in clear, to tell keys apart.
+const DISPLAYED_PREFIX_LENGTH: usize = 12;
+
+/// Minimum time between two writes of `last_used_at` for the same key.
+const LAST_USED_INTERVAL: time::Duration = time::Duration::minutes(1);
+
+/// Hashes an API key for storage and lookup.
+///
+/// Keys are random and long, so a fast hash is enough to keep them from being recovered.
+fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// Generates a new API key from 32 random bytes.
+fn generate_api_key() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    format!("{}{}", API_KEY_PREFIX, hex::encode(bytes))
+}
+
+/// Creates an API key, returning it along with the clear key, which is not stored and can't be recovered.
+pub async fn create_api_key(
+    pool: &sqlx::PgPool,
+    name: &str,
+    scopes: &[Scope],
+) -> Result<(ApiKey, String), ApiKeyError> {
+    let query = r#"
+    INSERT INTO api_keys (name, prefix, key_hash, scopes)
+    VALUES ($1, $2, $3, $4)
+    RETURNING id, name, prefix, scopes, created_at, last_used_at, revoked_at;"#;
+
+    let key = generate_api_key();
+    let scopes: Vec<&str> = scopes.iter().map(Scope::as_str).collect();
+    log::trace!("Inserting API key into database.");
+    let api_key = sqlx::query_as::<_, ApiKey>(query)
+        .bind(name)
+        .bind(&key[..DISPLAYED_PREFIX_LENGTH])
+        .bind(hash_api_key(&key))
+        .bind(scopes)
+        .fetch_one(pool)
+        .await
+        .into_report()
+        .change_context(ApiKeyError)
+        .attach("Failed to insert API key on Postgres database.")?;
+    Ok((api_key, key))
+}
+
+/// Lists every API key, including the revoked ones.
+pub async fn list_api_keys(pool: &sqlx::PgPool) -> Result<Vec<ApiKey>, ApiKeyError> {
+    let query = r#"
+    SELECT id, name, prefix, scopes, created_at, last_used_at, revoked_at
+    FROM api_keys
+    ORDER BY id;"#;
+
+    sqlx::query_as::<_, ApiKey>(query)
+        .fetch_all(pool)
+        .await
+        .into_report()
+        .change_context(ApiKeyError)
+        .attach("Failed to query API keys on Postgres database.")
+}
+
+/// Revokes an API key, returning it if it existed and wasn't already revoked.
+pub async fn revoke_api_key(pool: &sqlx::PgPool, id: i32) -> Result<Option<ApiKey>, ApiKeyError> {
+    let query = r#"
+    UPDATE api_keys
+    SET revoked_at = NOW()
+    WHERE id = $1 AND revoked_at IS NULL
+    RETURNING id, name, prefix, scopes, created_at, last_used_at, revoked_at;"#;
+
+    sqlx::query_as::<_, ApiKey>(query)
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .into_report()
+        .change_context(ApiKeyError)
+        .attach("Failed to revoke API key on Postgres database.")
+}
+
+/// Finds the API key matching `key`, unless it was revoked.
+///
+/// Authenticating only reads the key, so that requests don't lock its row.
+/// `last_used_at` is recorded in the background, at most once every `LAST_USED_INTERVAL`.
+pub async fn authenticate_api_key(
+    pool: &sqlx::PgPool,
+    key: &str,
+) -> Result<Option<ApiKey>, ApiKeyError> {
+    let query = r#"
+    SELECT id, name, prefix, scopes, created_at, last_used_at, revoked_at
+    FROM api_keys
+    WHERE key_hash = $1 AND revoked_at IS NULL;"#;
+
+    let api_key = sqlx::query_as::<_, ApiKey>(query)
+        .bind(hash_api_key(key))
+        .fetch_optional(pool)
+        .await
+        .into_report()
+        .change_context(ApiKeyError)
+        .attach("Failed to query API key on Postgres database.")?;
+
+    if let Some(api_key) = &api_key {
+        let recently_used = api_key.last_used_at.is_some_and(|last_used_at| {
+            time::OffsetDateTime::now_utc() - last_used_at < LAST_USED_INTERVAL
+        });
+        if !recently_used {
+            tokio::spawn(record_use(pool.clone(), api_key.id));
+        }
+    }
+    Ok(api_key)
+}
+
+/// Sets `last_used_at` of an API key, unless another request already did within `LAST_USED_INTERVAL`.
+async fn record_use(pool: sqlx::PgPool, id: i32) {
+    let query = r#"
+    UPDATE api_keys
+    SET last_used_at = NOW()
+    WHERE id = $1 AND (last_used_at IS NULL OR last_used_at < NOW() - $2 * INTERVAL '1 second');"#;
+
+    if let Err(err) = sqlx::query(query)
+        .bind(id)
+        .bind(LAST_USED_INTERVAL.whole_seconds() as f64)
+        .execute(&pool)
+        .await
+    {
+        log::error!("Failed to record use of API key `{}`: {}", id, err);
+    }
+}
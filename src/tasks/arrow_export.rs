@@ -0,0 +1,298 @@
+use std::{
+    future::Future,
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use arrow_array::{
+    builder::{Date32Builder, Float64Builder, Int64Builder, StringBuilder},
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use axum::body::Bytes;
+use error_stack::{IntoReport, Report, Result, ResultExt};
+use futures::{Stream, TryStreamExt};
+use parquet::file::properties::WriterProperties;
+use sqlx::{Postgres, QueryBuilder};
+
+use crate::{
+    error::ExportError,
+    model::{ExportFormat, FinancialDataFilters, FinancialDataReport},
+};
+
+/// Number of rows on each record batch.
+const BATCH_SIZE: usize = 8192;
+
+/// Number of rows on each Parquet row group, which is held in memory until it is complete.
+const ROW_GROUP_SIZE: usize = 16 * BATCH_SIZE;
+
+/// Sender of the bytes of a streamed export, or of the error aborting it.
+pub type ExportSender = tokio::sync::mpsc::Sender<std::result::Result<Bytes, std::io::Error>>;
+
+/// Julian day of 1970-01-01, the epoch of Arrow `date32` values.
+const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+/// Schema of the exported `financial_data` rows.
+pub fn financial_data_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("symbol", DataType::Utf8, false),
+        Field::new("date", DataType::Date32, false),
+        Field::new("open_price", DataType::Float64, false),
+        Field::new("close_price", DataType::Float64, false),
+        Field::new("volume", DataType::Int64, false),
+    ]))
+}
+
+/// Builders for each column of a record batch.
+#[derive(Default)]
+struct FinancialDataBuilder {
+    symbol: StringBuilder,
+    date: Date32Builder,
+    open_price: Float64Builder,
+    close_price: Float64Builder,
+    volume: Int64Builder,
+    len: usize,
+}
+
+impl FinancialDataBuilder {
+    fn append(&mut self, row: &FinancialDataReport) {
+        self.symbol.append_value(row.symbol.trim());
+        self.date
+            .append_value(row.date.to_julian_day() - UNIX_EPOCH_JULIAN_DAY);
+        self.open_price.append_value(row.open_price);
+        self.close_price.append_value(row.close_price);
+        self.volume.append_value(row.volume.into());
+        self.len += 1;
+    }
+
+    /// Builds a record batch from the appended rows, resetting the builders.
+    fn finish(&mut self, schema: SchemaRef) -> Result<RecordBatch, ExportError> {
+        self.len = 0;
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.symbol.finish()),
+            Arc::new(self.date.finish()),
+            Arc::new(self.open_price.finish()),
+            Arc::new(self.close_price.finish()),
+            Arc::new(self.volume.finish()),
+        ];
+        RecordBatch::try_new(schema, columns)
+            .into_report()
+            .change_context(ExportError)
+            .attach("Failed to build record batch.")
+    }
+}
+
+/// Writer of one of the supported export formats.
+enum BatchWriter<W: Write + Send> {
+    Arrow(arrow_ipc::writer::StreamWriter<W>),
+    Parquet(parquet::arrow::ArrowWriter<W>),
+}
+
+impl<W: Write + Send> BatchWriter<W> {
+    fn try_new(format: ExportFormat, writer: W, schema: SchemaRef) -> Result<Self, ExportError> {
+        match format {
+            ExportFormat::Arrow => arrow_ipc::writer::StreamWriter::try_new(writer, &schema)
+                .map(BatchWriter::Arrow)
+                .into_report()
+                .change_context(ExportError)
+                .attach("Failed to create Arrow IPC writer."),
+            ExportFormat::Parquet => {
+                let properties = WriterProperties::builder()
+                    .set_max_row_group_size(ROW_GROUP_SIZE)
+                    .build();
+                parquet::arrow::ArrowWriter::try_new(writer, schema, Some(properties))
+                    .map(BatchWriter::Parquet)
+                    .into_report()
+                    .change_context(ExportError)
+                    .attach("Failed to create Parquet writer.")
+            }
+        }
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<(), ExportError> {
+        match self {
+            BatchWriter::Arrow(writer) => writer
+                .write(batch)
+                .into_report()
+                .change_context(ExportError),
+            BatchWriter::Parquet(writer) => writer
+                .write(batch)
+                .into_report()
+                .change_context(ExportError),
+        }
+        .attach("Failed to write record batch.")
+    }
+
+    /// Writes the end of the stream or the footer of the file, returning the inner writer.
+    fn finish(self) -> Result<W, ExportError> {
+        match self {
+            BatchWriter::Arrow(writer) => writer
+                .into_inner()
+                .into_report()
+                .change_context(ExportError),
+            BatchWriter::Parquet(writer) => writer
+                .into_inner()
+                .into_report()
+                .change_context(ExportError),
+        }
+        .attach("Failed to finish export.")
+    }
+}
+
+/// Writes the `financial_data` rows for a set of symbols and a date range as Arrow IPC stream or Parquet.
+///
+/// `None` filters are not applied. Returns the number of rows written.
+pub async fn export_financial_data<W: Write + Send>(
+    pool: &sqlx::PgPool,
+    symbols: Option<Vec<String>>,
+    start_date: Option<time::Date>,
+    end_date: Option<time::Date>,
+    format: ExportFormat,
+    writer: W,
+) -> Result<usize, ExportError> {
+    let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM financial_data");
+    FinancialDataFilters {
+        symbols,
+        start_date,
+        end_date,
+    }
+    .push_where(&mut query);
+    query.push(" ORDER BY symbol, date");
+
+    log::trace!("Streaming time series entries from database into record batches.");
+    let rows = query.build_query_as::<FinancialDataReport>().fetch(pool);
+    let (count, mut writer) = write_financial_data(rows, format, writer).await?;
+    writer
+        .flush()
+        .into_report()
+        .change_context(ExportError)
+        .attach("Failed to flush export.")?;
+    Ok(count)
+}
+
+/// Writes a stream of `financial_data` rows as Arrow IPC stream or Parquet.
+///
+/// Returns the number of rows written and the inner writer, which still needs to be flushed.
+pub(crate) async fn write_financial_data<W, S>(
+    rows: S,
+    format: ExportFormat,
+    writer: W,
+) -> Result<(usize, W), ExportError>
+where
+    W: Write + Send,
+    S: Stream<Item = std::result::Result<FinancialDataReport, sqlx::Error>> + Unpin,
+{
+    let schema = financial_data_schema();
+    let mut writer = BatchWriter::try_new(format, writer, schema.clone())?;
+    let count = write_batches(rows, &mut writer, schema, || async { Ok(()) }).await?;
+
+    log::trace!("Finishing export of {} rows.", count);
+    Ok((count, writer.finish()?))
+}
+
+/// Writes a stream of `financial_data` rows a record batch at a time, awaiting `written` after each batch.
+///
+/// Returns the number of rows written.
+async fn write_batches<W, S, F, Fut>(
+    mut rows: S,
+    writer: &mut BatchWriter<W>,
+    schema: SchemaRef,
+    mut written: F,
+) -> Result<usize, ExportError>
+where
+    W: Write + Send,
+    S: Stream<Item = std::result::Result<FinancialDataReport, sqlx::Error>> + Unpin,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<(), ExportError>>,
+{
+    let mut builder = FinancialDataBuilder::default();
+    let mut count = 0;
+
+    while let Some(row) = rows
+        .try_next()
+        .await
+        .into_report()
+        .change_context(ExportError)
+        .attach("Failed to query financial data on Postgres database.")?
+    {
+        builder.append(&row);
+        count += 1;
+        if builder.len == BATCH_SIZE {
+            writer.write(&builder.finish(schema.clone())?)?;
+            written().await?;
+        }
+    }
+    if builder.len > 0 {
+        writer.write(&builder.finish(schema)?)?;
+        written().await?;
+    }
+    Ok(count)
+}
+
+/// Bytes written by an export, taken by the task sending them as they are produced.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /// Takes the bytes written since the last call.
+    fn take(&self) -> Bytes {
+        let mut buffer = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        Bytes::from(std::mem::take(&mut *buffer))
+    }
+
+    /// Sends the bytes written since the last call, if any.
+    async fn send(&self, sender: &ExportSender) -> Result<(), ExportError> {
+        let bytes = self.take();
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        sender
+            .send(Ok(bytes))
+            .await
+            .map_err(|_| Report::new(ExportError))
+            .attach("Receiver of the export was dropped.")
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut buffer = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        buffer.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Streams the `financial_data` rows matching `filters` as Arrow IPC stream or Parquet, sending the bytes
+/// through `sender` after each record batch, so that only a batch, or a Parquet row group, is held in memory.
+///
+/// Returns the number of rows sent. Stops once the receiver is dropped.
+pub async fn stream_financial_data_export(
+    pool: &sqlx::PgPool,
+    filters: FinancialDataFilters,
+    format: ExportFormat,
+    sender: &ExportSender,
+) -> Result<usize, ExportError> {
+    let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM financial_data");
+    filters.push_where(&mut query);
+    query.push(" ORDER BY symbol, date");
+
+    log::trace!("Streaming time series entries from database into record batches.");
+    let rows = query.build_query_as::<FinancialDataReport>().fetch(pool);
+    let schema = financial_data_schema();
+    let buffer = SharedBuffer::default();
+    let mut writer = BatchWriter::try_new(format, buffer.clone(), schema.clone())?;
+    let count = write_batches(rows, &mut writer, schema, || {
+        let buffer = buffer.clone();
+        async move { buffer.send(sender).await }
+    })
+    .await?;
+    writer.finish()?;
+    buffer.send(sender).await?;
+
+    log::trace!("Finished streaming export of {} rows.", count);
+    Ok(count)
+}
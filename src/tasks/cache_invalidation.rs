@@ -0,0 +1,34 @@
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tokio_util::sync::CancellationToken;
+
+use crate::{cache::ResponseCache, model::FinancialDataUpdate};
+
+/// Invalidates the cached responses of each symbol whose time series was updated.
+///
+/// Updates come from the Postgres notifications sent when `upsert_in_database`, `delete_from_database` or
+/// `enforce_retention` commits, so responses are invalidated whichever replica or `import` command wrote the rows.
+/// Quits once `shutdown` is cancelled.
+pub async fn invalidate_cache_on_updates(
+    cache: ResponseCache,
+    mut updates: Receiver<FinancialDataUpdate>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let update = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            update = updates.recv() => update,
+        };
+        match update {
+            Ok(update) => cache.invalidate_symbol(update.symbol()),
+            Err(RecvError::Lagged(skipped)) => {
+                log::warn!(
+                    "Missed `{}` updates, clearing the whole response cache.",
+                    skipped
+                );
+                cache.clear();
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+    log::trace!("Exited from cache invalidation task.");
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+use tokio_util::sync::CancellationToken;
+
+use crate::{metrics, model::FinancialDataUpdate};
+
+/// Latest date of the time series of each symbol, followed from the updates so that scrapes of the `metrics`
+/// endpoint don't scan `financial_data`. Loaded from the database on the first scrape, and after missed updates.
+#[derive(Debug, Clone, Default)]
+pub struct LatestEntries(Arc<Mutex<Option<HashMap<String, time::Date>>>>);
+
+impl LatestEntries {
+    fn lock(&self) -> MutexGuard<'_, Option<HashMap<String, time::Date>>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Queries the latest date of every symbol, unless they are already known.
+    async fn load(&self, pool: &sqlx::PgPool) -> Result<(), sqlx::Error> {
+        let query = r#"
+        SELECT TRIM(symbol), MAX(date)
+        FROM financial_data
+        GROUP BY symbol;"#;
+
+        if self.lock().is_some() {
+            return Ok(());
+        }
+        let latest = metrics::time_query(
+            "data_freshness",
+            sqlx::query_as::<_, (String, time::Date)>(query).fetch_all(pool),
+        )
+        .await?;
+        self.lock().get_or_insert_with(HashMap::new).extend(latest);
+        Ok(())
+    }
+
+    /// Sets `data_freshness_seconds` to the time since the latest date of each symbol.
+    pub async fn update_gauge(&self, pool: &sqlx::PgPool) {
+        if let Err(err) = self.load(pool).await {
+            log::error!("Failed to query data freshness: {}", err);
+            return;
+        }
+        let now = time::OffsetDateTime::now_utc();
+        for (symbol, date) in self.lock().iter().flatten() {
+            let age = now - date.midnight().assume_utc();
+            metrics::DATA_FRESHNESS
+                .with_label_values(&[symbol])
+                .set(age.whole_seconds());
+        }
+    }
+
+    /// Follows a new or corrected entry.
+    fn upserted(&self, symbol: &str, date: time::Date) {
+        if let Some(latest) = self.lock().as_mut() {
+            let entry = latest.entry(symbol.trim().to_string()).or_insert(date);
+            *entry = (*entry).max(date);
+        }
+    }
+
+    /// Queries the latest date of a symbol again, once its latest entry may have been deleted.
+    async fn reload_symbol(&self, pool: &sqlx::PgPool, symbol: &str) -> Result<(), sqlx::Error> {
+        let query = r#"
+        SELECT MAX(date)
+        FROM financial_data
+        WHERE symbol = CAST($1 AS BPCHAR);"#;
+
+        let date: Option<time::Date> = sqlx::query_scalar(query)
+            .bind(symbol)
+            .fetch_one(pool)
+            .await?;
+        if let (Some(latest), Some(date)) = (self.lock().as_mut(), date) {
+            latest.insert(symbol.trim().to_string(), date);
+        }
+        Ok(())
+    }
+
+    /// Forgets every date, so that they are loaded again on the next scrape.
+    fn clear(&self) {
+        *self.lock() = None;
+    }
+}
+
+/// Follows the latest date of each symbol from the updates of the time series.
+///
+/// Updates come from the Postgres notifications sent when `upsert_in_database` or `delete_from_database` commits,
+/// so the dates follow whichever replica or `import` command wrote the rows. Quits once `shutdown` is cancelled.
+pub async fn track_latest_entries(
+    pool: sqlx::PgPool,
+    latest: LatestEntries,
+    mut updates: Receiver<FinancialDataUpdate>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        let update = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            update = updates.recv() => update,
+        };
+        match update {
+            Ok(FinancialDataUpdate::Upserted(report)) => {
+                latest.upserted(&report.symbol, report.date)
+            }
+            Ok(update @ (FinancialDataUpdate::Deleted(_) | FinancialDataUpdate::Pruned(_))) => {
+                if let Err(err) = latest.reload_symbol(&pool, update.symbol()).await {
+                    log::error!("Failed to query data freshness: {}", err);
+                    latest.clear();
+                }
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                log::warn!(
+                    "Missed `{}` updates, loading the data freshness again.",
+                    skipped
+                );
+                latest.clear();
+            }
+            Err(RecvError::Closed) => break,
+        }
+    }
+    log::trace!("Exited from data freshness task.");
+}
@@ -0,0 +1,156 @@
+use std::io::{Seek, Write};
+
+use error_stack::{IntoReport, Result, ResultExt};
+use futures::TryStreamExt;
+use sha2::{Digest, Sha256};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    error::DatabaseBackupError,
+    model::{BackupManifest, BackupTable},
+};
+
+/// Layout of the archives, bumped whenever older versions of the application can no longer restore them.
+pub(crate) const BACKUP_FORMAT_VERSION: u32 = 1;
+
+/// Name of the manifest inside the archives.
+pub(crate) const MANIFEST_FILE: &str = "manifest.json";
+
+/// Tables of the application, in an order restoring referenced rows before the rows referencing them,
+/// with the columns their rows are sorted by. `rate_limits` only holds short-lived state, so it is left out.
+pub(crate) const BACKUP_TABLES: [(&str, &str); 12] = [
+    ("api_keys", "id"),
+    ("financial_data", "symbol, date"),
+    ("financial_data_revisions", "id"),
+    ("financial_data_audit", "id"),
+    ("financial_data_monthly", "symbol, month"),
+    ("financial_data_stale_months", "symbol, month"),
+    ("financial_data_retention", "symbol"),
+    ("trading_calendar_overrides", "date"),
+    ("alert_rules", "id"),
+    ("alert_events", "id"),
+    ("alert_deliveries", "id"),
+    ("ingestion_runs", "id"),
+];
+
+/// Version of the schema of a database, as the SHA-256 of the `schema.sql` applied to it.
+pub(crate) fn schema_version(schema: &str) -> String {
+    hex::encode(Sha256::digest(schema.as_bytes()))
+}
+
+/// Columns of a table of the application, in the order they were created.
+pub(crate) async fn table_columns<'c>(
+    db: impl sqlx::PgExecutor<'c>,
+    table: &str,
+) -> std::result::Result<Vec<String>, sqlx::Error> {
+    let query_str = r#"
+    SELECT CAST(column_name AS TEXT)
+    FROM information_schema.columns
+    WHERE table_schema = current_schema() AND table_name = $1
+    ORDER BY ordinal_position;"#;
+
+    sqlx::query_scalar(query_str)
+        .bind(table)
+        .fetch_all(db)
+        .await
+}
+
+/// Writes every table of the application into a zip archive, as a deflated file holding a JSON object per row,
+/// followed by a `manifest.json` describing them. Returns the manifest.
+///
+/// The tables are read within a single read only transaction, so that the archive is a consistent snapshot.
+pub async fn export_database<W: Write + Seek>(
+    pool: &sqlx::PgPool,
+    schema: &str,
+    writer: W,
+) -> Result<BackupManifest, DatabaseBackupError> {
+    let mut trans = pool
+        .begin()
+        .await
+        .into_report()
+        .change_context(DatabaseBackupError)
+        .attach("Failed to start transaction.")?;
+    sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY;")
+        .execute(&mut trans)
+        .await
+        .into_report()
+        .change_context(DatabaseBackupError)
+        .attach("Failed to start snapshot of the database.")?;
+
+    let mut archive = ZipWriter::new(writer);
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut tables = vec![];
+    for (table, order) in BACKUP_TABLES {
+        let columns = table_columns(&mut trans, table)
+            .await
+            .into_report()
+            .change_context(DatabaseBackupError)
+            .attach_printable_lazy(|| format!("Failed to read columns of `{}`.", table))?;
+        let file = format!("{}.ndjson", table);
+        archive
+            .start_file(file.as_str(), options)
+            .into_report()
+            .change_context(DatabaseBackupError)
+            .attach_printable_lazy(|| format!("Failed to add `{}` to archive.", file))?;
+
+        log::trace!("Streaming rows of `{}` into archive.", table);
+        let query_str = format!(
+            "SELECT CAST(row_to_json({0}) AS TEXT) FROM {0} ORDER BY {1};",
+            table, order
+        );
+        let mut rows = sqlx::query_scalar::<_, String>(&query_str).fetch(&mut trans);
+        let mut count = 0;
+        while let Some(row) = rows
+            .try_next()
+            .await
+            .into_report()
+            .change_context(DatabaseBackupError)
+            .attach_printable_lazy(|| format!("Failed to query `{}`.", table))?
+        {
+            writeln!(archive, "{}", row)
+                .into_report()
+                .change_context(DatabaseBackupError)
+                .attach_printable_lazy(|| format!("Failed to write `{}`.", file))?;
+            count += 1;
+        }
+        tables.push(BackupTable {
+            name: table.into(),
+            file,
+            columns,
+            rows: count,
+        });
+    }
+    trans
+        .commit()
+        .await
+        .into_report()
+        .change_context(DatabaseBackupError)
+        .attach("Failed to commit transaction.")?;
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        schema_version: schema_version(schema),
+        app_version: env!("CARGO_PKG_VERSION").into(),
+        created_at: time::OffsetDateTime::now_utc(),
+        tables,
+    };
+    archive
+        .start_file(MANIFEST_FILE, options)
+        .into_report()
+        .change_context(DatabaseBackupError)
+        .attach("Failed to add manifest to archive.")?;
+    serde_json::to_writer_pretty(&mut archive, &manifest)
+        .into_report()
+        .change_context(DatabaseBackupError)
+        .attach("Failed to write manifest.")?;
+    archive
+        .finish()
+        .into_report()
+        .change_context(DatabaseBackupError)
+        .attach("Failed to finish archive.")?
+        .flush()
+        .into_report()
+        .change_context(DatabaseBackupError)
+        .attach("Failed to flush archive.")?;
+    Ok(manifest)
+}
@@ -1,12 +1,14 @@
 use error_stack::{IntoReport, Result, ResultExt};
 
-use crate::error::DatabaseConnectError;
+use crate::{config::DatabaseConfig, error::DatabaseConnectError};
 
-/// Create a database connection from a `database_url` string.
-pub async fn connect_to_database(database_url: &str) -> Result<sqlx::PgPool, DatabaseConnectError> {
+/// Create a database connection pool from the database configuration.
+pub async fn connect_to_database(
+    config: &DatabaseConfig,
+) -> Result<sqlx::PgPool, DatabaseConnectError> {
     sqlx::postgres::PgPoolOptions::new()
-        .max_connections(5)
-        .connect(database_url)
+        .max_connections(config.max_connections)
+        .connect(&config.url)
         .await
         .into_report()
         .change_context(DatabaseConnectError)
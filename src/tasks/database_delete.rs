@@ -0,0 +1,86 @@
+use error_stack::{IntoReport, Result, ResultExt};
+
+use crate::{
+    error::DatabaseDeleteError,
+    model::{ChangeAuthor, FinancialDataReport},
+};
+
+use super::{mark_stale_month, record_change, record_revision};
+
+/// Postgres notification channel on which deleted `FinancialDataReport` are published as JSON.
+pub const FINANCIAL_DATA_DELETIONS_CHANNEL: &str = "financial_data_deletions";
+
+/// Deletes the entry of `symbol` on `date`, returning its last values, or `None` if it doesn't exist.
+///
+/// Within the deleting transaction, the last revision of the entry is closed on the `financial_data_revisions` table,
+/// and the deletion is recorded on the `financial_data_audit` table and published on the
+/// `FINANCIAL_DATA_DELETIONS_CHANNEL` notification channel.
+#[tracing::instrument(skip(pool, author))]
+pub async fn delete_from_database(
+    pool: sqlx::PgPool,
+    symbol: &str,
+    date: time::Date,
+    author: &ChangeAuthor,
+) -> Result<Option<FinancialDataReport>, DatabaseDeleteError> {
+    let query = r#"
+    DELETE FROM financial_data
+    WHERE symbol = CAST($1 AS BPCHAR) AND date = $2
+    RETURNING symbol, date, open_price, close_price, volume;"#;
+    log::trace!("Initializing delete transaction.");
+    let mut trans = pool
+        .begin()
+        .await
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to create transaction on Postgres database.")?;
+
+    let deleted = sqlx::query_as::<_, FinancialDataReport>(query)
+        .bind(symbol)
+        .bind(date)
+        .fetch_optional(&mut trans)
+        .await
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to delete value from database.")?;
+    let Some(mut deleted) = deleted else {
+        return Ok(None);
+    };
+    deleted.symbol = deleted.symbol.trim().into();
+
+    record_revision(&mut trans, &deleted.symbol, deleted.date, None)
+        .await
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to record revision of value.")?;
+    mark_stale_month(&mut trans, &deleted.symbol, deleted.date)
+        .await
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to mark aggregates of value as stale.")?;
+    record_change(&mut trans, author, Some(&deleted), None)
+        .await
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to record deletion on audit table.")?;
+    let payload = serde_json::to_string(&deleted)
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to serialize deletion notification.")?;
+    sqlx::query("SELECT pg_notify($1, $2);")
+        .bind(FINANCIAL_DATA_DELETIONS_CHANNEL)
+        .bind(payload)
+        .execute(&mut trans)
+        .await
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to notify deletion of value.")?;
+
+    log::trace!("Committing delete transaction.");
+    trans
+        .commit()
+        .await
+        .into_report()
+        .change_context(DatabaseDeleteError)
+        .attach("Failed to commit transaction on Postgres database.")?;
+    Ok(Some(deleted))
+}
@@ -1,14 +1,17 @@
+use std::path::Path;
+
 use error_stack::{IntoReport, Result, ResultExt};
+use sqlx::Executor;
 
 use crate::error::DatabaseInitializationError;
 
-/// Creates the `financial_data` table into the database from the schema file if not exists.
+/// Creates the tables into the database from the schema file if not exists.
 pub async fn create_table_if_not_exists(
     pool: sqlx::PgPool,
+    schema_path: &Path,
 ) -> Result<(), DatabaseInitializationError> {
     log::trace!("Reading schema file.");
-    let schema = std::fs::read_to_string("schema.sql")
-        .or_else(|_| std::fs::read_to_string("/var/app/schema.sql"))
+    let schema = std::fs::read_to_string(schema_path)
         .into_report()
         .change_context(DatabaseInitializationError)
         .attach("Failed to read schema file.")?;
@@ -21,9 +24,10 @@ pub async fn create_table_if_not_exists(
         .change_context(DatabaseInitializationError)
         .attach("Failed to create transaction on Postgres database.")?;
 
-    log::trace!("Creating table if not exists.");
-    let rows = sqlx::query(&schema)
-        .execute(&mut trans)
+    log::trace!("Creating tables if not exists.");
+    // Executed without arguments so that the schema file can hold multiple statements.
+    let rows = trans
+        .execute(schema.as_str())
         .await
         .into_report()
         .change_context(DatabaseInitializationError)
@@ -0,0 +1,76 @@
+use error_stack::{IntoReport, Result, ResultExt};
+use tokio::sync::broadcast::Sender;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    error::DatabaseListenError,
+    model::{FinancialDataReport, FinancialDataUpdate, PrunedSeries},
+};
+
+use super::{
+    FINANCIAL_DATA_DELETIONS_CHANNEL, FINANCIAL_DATA_PRUNINGS_CHANNEL,
+    FINANCIAL_DATA_UPDATES_CHANNEL,
+};
+
+/// Parses a notification of `upsert_in_database`, `delete_from_database` or `enforce_retention`.
+fn parse_update(channel: &str, payload: &str) -> serde_json::Result<FinancialDataUpdate> {
+    if channel == FINANCIAL_DATA_PRUNINGS_CHANNEL {
+        let mut pruned = serde_json::from_str::<PrunedSeries>(payload)?;
+        pruned.symbol = pruned.symbol.trim().into();
+        return Ok(FinancialDataUpdate::Pruned(pruned));
+    }
+    let mut report = serde_json::from_str::<FinancialDataReport>(payload)?;
+    report.symbol = report.symbol.trim().into();
+    Ok(match channel {
+        FINANCIAL_DATA_DELETIONS_CHANNEL => FinancialDataUpdate::Deleted(report),
+        _ => FinancialDataUpdate::Upserted(report),
+    })
+}
+
+/// Forwards the `FinancialDataReport` notified by `upsert_in_database` and `delete_from_database`, and the
+/// `PrunedSeries` notified by `enforce_retention`, to the `updates` channel.
+///
+/// Listening on Postgres notifications lets every replica of the server receive the updates
+/// committed by any of them, or by the `import` command. Quits once `shutdown` is cancelled.
+pub async fn listen_for_updates(
+    pool: sqlx::PgPool,
+    updates: Sender<FinancialDataUpdate>,
+    shutdown: CancellationToken,
+) -> Result<(), DatabaseListenError> {
+    log::trace!("Connecting listener to database.");
+    let mut listener = sqlx::postgres::PgListener::connect_with(&pool)
+        .await
+        .into_report()
+        .change_context(DatabaseListenError)
+        .attach("Failed to connect listener to Postgres database.")?;
+    listener
+        .listen_all([
+            FINANCIAL_DATA_UPDATES_CHANNEL,
+            FINANCIAL_DATA_DELETIONS_CHANNEL,
+            FINANCIAL_DATA_PRUNINGS_CHANNEL,
+        ])
+        .await
+        .into_report()
+        .change_context(DatabaseListenError)
+        .attach("Failed to listen to update notifications.")?;
+
+    loop {
+        let notification = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            notification = listener.recv() => notification,
+        };
+        let notification = notification
+            .into_report()
+            .change_context(DatabaseListenError)
+            .attach("Failed to receive notification from Postgres database.")?;
+        match parse_update(notification.channel(), notification.payload()) {
+            Ok(update) => {
+                // Sending only fails when no client is subscribed.
+                let _ = updates.send(update);
+            }
+            Err(err) => log::error!("Failed to parse update notification: {}", err),
+        }
+    }
+    log::trace!("Exited from update listener task.");
+    Ok(())
+}
@@ -0,0 +1,354 @@
+use std::{
+    collections::HashSet,
+    io::{BufRead, BufReader, Read, Seek},
+};
+
+use error_stack::{IntoReport, Report, Result, ResultExt};
+use zip::ZipArchive;
+
+use crate::{
+    error::DatabaseRestoreError,
+    model::{BackupManifest, BackupTable, FinancialDataReport},
+};
+
+use super::{
+    schema_version, table_columns, BACKUP_FORMAT_VERSION, BACKUP_TABLES,
+    FINANCIAL_DATA_UPDATES_CHANNEL, MANIFEST_FILE,
+};
+
+/// Number of rows inserted by each statement of a restore.
+const RESTORE_BATCH_SIZE: usize = 1000;
+
+/// Tables referencing the serial `id` of another table, as `(table, referenced table)`,
+/// each listed after the tables it is referenced by.
+const SERIAL_REFERENCES: [(&str, &str); 3] = [
+    ("alert_events", "alert_rules"),
+    ("alert_deliveries", "alert_events"),
+    ("financial_data_audit", "api_keys"),
+];
+
+/// Records a current revision of every entry that has none.
+const REVISIONS_BACKFILL_QUERY: &str = r#"
+    INSERT INTO financial_data_revisions (symbol, date, open_price, close_price, volume)
+    SELECT symbol, date, open_price, close_price, volume
+    FROM financial_data
+    WHERE NOT EXISTS (
+        SELECT 1
+        FROM financial_data_revisions
+        WHERE financial_data_revisions.symbol = financial_data.symbol
+            AND financial_data_revisions.date = financial_data.date
+            AND financial_data_revisions.superseded_at IS NULL
+    );"#;
+
+/// Rows of a table of an archive, and how many of them were missing from the database.
+#[derive(Debug)]
+pub struct RestoredTable {
+    pub name: String,
+    pub rows: u64,
+    pub inserted: u64,
+    /// The table was not restored, since its archived ids could clash with, or point to, existing rows.
+    pub skipped: bool,
+}
+
+/// Checks that a table of the archive is a table of the application holding every archived column,
+/// so that only known identifiers end up in the restore queries.
+async fn validate_table(
+    trans: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    table: &BackupTable,
+) -> Result<(), DatabaseRestoreError> {
+    if !BACKUP_TABLES.iter().any(|(name, _)| *name == table.name) {
+        return Err(Report::new(DatabaseRestoreError)).attach_printable(format!(
+            "Table `{}` of the archive is not a table of the application.",
+            table.name
+        ));
+    }
+    let columns = table_columns(&mut *trans, &table.name)
+        .await
+        .into_report()
+        .change_context(DatabaseRestoreError)
+        .attach_printable_lazy(|| format!("Failed to read columns of `{}`.", table.name))?;
+    let missing: Vec<_> = table
+        .columns
+        .iter()
+        .filter(|column| !columns.contains(column))
+        .collect();
+    if !missing.is_empty() {
+        return Err(Report::new(DatabaseRestoreError)).attach_printable(format!(
+            "Columns {:?} of `{}` do not exist on the database.",
+            missing, table.name
+        ));
+    }
+    Ok(())
+}
+
+/// Finds the tables of the archive that can't be restored: the tables with a serial `id` already holding rows,
+/// whose archived ids may be those of other rows, and the tables referencing their ids.
+async fn skipped_tables(
+    trans: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    manifest: &BackupManifest,
+) -> Result<HashSet<String>, DatabaseRestoreError> {
+    let mut skipped = HashSet::new();
+    for table in manifest.tables.iter() {
+        if !table.columns.iter().any(|column| column == "id") {
+            continue;
+        }
+        let exists_query = format!("SELECT EXISTS (SELECT 1 FROM {});", table.name);
+        let has_rows: bool = sqlx::query_scalar(&exists_query)
+            .fetch_one(&mut *trans)
+            .await
+            .into_report()
+            .change_context(DatabaseRestoreError)
+            .attach_printable_lazy(|| format!("Failed to read rows of `{}`.", table.name))?;
+        if has_rows {
+            skipped.insert(table.name.clone());
+        }
+    }
+    for (table, referenced) in SERIAL_REFERENCES {
+        if skipped.contains(referenced) {
+            skipped.insert(table.to_string());
+        }
+    }
+    Ok(skipped)
+}
+
+/// Query inserting a batch of rows, given as a JSON array of objects, that are missing from a table.
+/// Returns the number of rows inserted, or the inserted rows of `financial_data`.
+///
+/// The months of the restored `financial_data` rows are marked stale, so that their aggregates are refreshed.
+fn restore_query(table: &BackupTable) -> String {
+    let columns = table
+        .columns
+        .iter()
+        .map(|column| format!("\"{}\"", column))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let insert = format!(
+        "INSERT INTO {0} ({1}) SELECT {1} FROM json_populate_recordset(NULL::{0}, CAST($1 AS JSON)) ON CONFLICT DO NOTHING",
+        table.name, columns
+    );
+    match table.name.as_str() {
+        "financial_data" => format!(
+            r#"
+    WITH restored AS (
+        {}
+        RETURNING symbol, date, open_price, close_price, volume
+    ),
+    stale AS (
+        INSERT INTO financial_data_stale_months (symbol, month)
+        SELECT DISTINCT symbol, CAST(date_trunc('month', date) AS DATE)
+        FROM restored
+        ON CONFLICT DO NOTHING
+    )
+    SELECT TRIM(symbol) AS symbol, date, open_price, close_price, volume FROM restored;"#,
+            insert
+        ),
+        _ => format!(
+            r#"
+    WITH restored AS (
+        {}
+        RETURNING 1
+    )
+    SELECT COUNT(*) FROM restored;"#,
+            insert
+        ),
+    }
+}
+
+/// Inserts a batch of rows of a table that are missing from it, returning the number of rows inserted.
+/// Each restored `financial_data` row is published on the `FINANCIAL_DATA_UPDATES_CHANNEL`.
+async fn restore_batch(
+    trans: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    table: &BackupTable,
+    query_str: &str,
+    rows: &[String],
+) -> Result<u64, DatabaseRestoreError> {
+    // Partitions are created within the restore, which holds the lock of `financial_data` until it commits anyway.
+    let partitions_query = r#"
+    SELECT create_financial_data_partitions(
+        CAST(MIN(EXTRACT(YEAR FROM date)) AS INT),
+        CAST(MAX(EXTRACT(YEAR FROM date)) AS INT)
+    )
+    FROM json_populate_recordset(NULL::financial_data, CAST($1 AS JSON));"#;
+
+    let rows = format!("[{}]", rows.join(","));
+    if table.name == "financial_data" {
+        sqlx::query(partitions_query)
+            .bind(&rows)
+            .execute(&mut *trans)
+            .await
+            .into_report()
+            .change_context(DatabaseRestoreError)
+            .attach("Failed to create partitions of restored rows.")?;
+    }
+    if table.name == "financial_data" {
+        let restored = sqlx::query_as::<_, FinancialDataReport>(query_str)
+            .bind(&rows)
+            .fetch_all(&mut *trans)
+            .await
+            .into_report()
+            .change_context(DatabaseRestoreError)
+            .attach("Failed to restore rows of `financial_data`.")?;
+        // Running servers invalidate their cached responses and stream the restored rows, as for upserts.
+        for report in restored.iter() {
+            let payload = serde_json::to_string(report)
+                .into_report()
+                .change_context(DatabaseRestoreError)
+                .attach("Failed to serialize update notification.")?;
+            sqlx::query("SELECT pg_notify($1, $2);")
+                .bind(FINANCIAL_DATA_UPDATES_CHANNEL)
+                .bind(payload)
+                .execute(&mut *trans)
+                .await
+                .into_report()
+                .change_context(DatabaseRestoreError)
+                .attach("Failed to notify restored value.")?;
+        }
+        return Ok(restored.len() as u64);
+    }
+    let inserted: i64 = sqlx::query_scalar(query_str)
+        .bind(&rows)
+        .fetch_one(&mut *trans)
+        .await
+        .into_report()
+        .change_context(DatabaseRestoreError)
+        .attach_printable_lazy(|| format!("Failed to restore rows of `{}`.", table.name))?;
+    Ok(inserted as u64)
+}
+
+/// Restores the rows of an archive written by `export_database` that are missing from the database,
+/// in a single transaction. Rows whose key already exists are kept as they are, so restoring twice changes nothing.
+/// Tables with a serial `id` are only restored while empty, along with the tables referencing their ids,
+/// so that no restored row points to a row it wasn't archived with. Restored entries without a revision
+/// get a current one, and are published on the `FINANCIAL_DATA_UPDATES_CHANNEL` like upserted ones.
+///
+/// Archives of another format version are refused, as are those holding a column the database lacks.
+pub async fn import_database<R: Read + Seek>(
+    pool: &sqlx::PgPool,
+    schema: &str,
+    reader: R,
+) -> Result<Vec<RestoredTable>, DatabaseRestoreError> {
+    let mut archive = ZipArchive::new(reader)
+        .into_report()
+        .change_context(DatabaseRestoreError)
+        .attach("Failed to open archive.")?;
+    let manifest: BackupManifest = archive
+        .by_name(MANIFEST_FILE)
+        .into_report()
+        .change_context(DatabaseRestoreError)
+        .attach("Archive has no manifest.")
+        .and_then(|file| {
+            serde_json::from_reader(file)
+                .into_report()
+                .change_context(DatabaseRestoreError)
+                .attach("Failed to parse manifest.")
+        })?;
+    if manifest.format_version != BACKUP_FORMAT_VERSION {
+        return Err(Report::new(DatabaseRestoreError)).attach_printable(format!(
+            "Archive has format version `{}`, but only version `{}` can be restored.",
+            manifest.format_version, BACKUP_FORMAT_VERSION
+        ));
+    }
+    if manifest.schema_version != schema_version(schema) {
+        log::warn!(
+            "Archive was written by version `{}` with another schema, restoring the columns it holds.",
+            manifest.app_version
+        );
+    }
+
+    let mut trans = pool
+        .begin()
+        .await
+        .into_report()
+        .change_context(DatabaseRestoreError)
+        .attach("Failed to start transaction.")?;
+    for table in manifest.tables.iter() {
+        validate_table(&mut trans, table).await?;
+    }
+    let skipped = skipped_tables(&mut trans, &manifest).await?;
+
+    let mut restored = vec![];
+    for table in manifest.tables.iter() {
+        if skipped.contains(&table.name) {
+            log::warn!(
+                "Skipping `{}`, its ids could clash with rows already on the database.",
+                table.name
+            );
+            restored.push(RestoredTable {
+                name: table.name.clone(),
+                rows: table.rows,
+                inserted: 0,
+                skipped: true,
+            });
+            continue;
+        }
+        log::trace!("Restoring rows of `{}`.", table.name);
+        let query_str = restore_query(table);
+        let file = archive
+            .by_name(&table.file)
+            .into_report()
+            .change_context(DatabaseRestoreError)
+            .attach_printable_lazy(|| format!("Archive has no `{}`.", table.file))?;
+        let mut batch = Vec::with_capacity(RESTORE_BATCH_SIZE);
+        let mut inserted = 0;
+        for line in BufReader::new(file).lines() {
+            let line = line
+                .into_report()
+                .change_context(DatabaseRestoreError)
+                .attach_printable_lazy(|| format!("Failed to read `{}`.", table.file))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            batch.push(line);
+            if batch.len() == RESTORE_BATCH_SIZE {
+                inserted += restore_batch(&mut trans, table, &query_str, &batch).await?;
+                batch.clear();
+            }
+        }
+        if !batch.is_empty() {
+            inserted += restore_batch(&mut trans, table, &query_str, &batch).await?;
+        }
+
+        if table.columns.iter().any(|column| column == "id") {
+            let sequence_query = format!(
+                "SELECT setval(pg_get_serial_sequence('{0}', 'id'), MAX(id)) FROM {0};",
+                table.name
+            );
+            sqlx::query(&sequence_query)
+                .execute(&mut trans)
+                .await
+                .into_report()
+                .change_context(DatabaseRestoreError)
+                .attach_printable_lazy(|| {
+                    format!("Failed to reset sequence of `{}`.", table.name)
+                })?;
+        }
+        restored.push(RestoredTable {
+            name: table.name.clone(),
+            rows: table.rows,
+            inserted,
+            skipped: false,
+        });
+    }
+
+    // Restored entries without a revision, such as those restored next to existing revisions, start their history,
+    // as `schema.sql` does for entries predating revisions. Done last, so that no archived revision id is taken.
+    if restored
+        .iter()
+        .any(|table| table.name == "financial_data" && table.inserted > 0)
+    {
+        sqlx::query(REVISIONS_BACKFILL_QUERY)
+            .execute(&mut trans)
+            .await
+            .into_report()
+            .change_context(DatabaseRestoreError)
+            .attach("Failed to record revisions of restored rows.")?;
+    }
+
+    trans
+        .commit()
+        .await
+        .into_report()
+        .change_context(DatabaseRestoreError)
+        .attach("Failed to commit transaction.")?;
+    Ok(restored)
+}
@@ -1,17 +1,34 @@
+use std::collections::HashSet;
+
 use error_stack::{IntoReport, Result, ResultExt};
 use serde::Deserialize;
-use tokio::sync::oneshot::Receiver;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+
+use crate::{
+    calendar::TradingCalendar,
+    config::CalendarConfig,
+    error::DatabaseUpsertError,
+    metrics,
+    model::{ChangeAuthor, FinancialDataReport},
+};
+
+use super::{
+    create_partitions, create_partitions_ahead, evaluate_alerts, mark_stale_month, record_change,
+    record_ingestion_run, record_revision, ALPHA_VANTAGE_SOURCE,
+};
 
-use crate::{error::DatabaseUpsertError, model::FinancialDataReport};
+/// Postgres notification channel on which new or corrected `FinancialDataReport` are published as JSON.
+pub const FINANCIAL_DATA_UPDATES_CHANNEL: &str = "financial_data_updates";
 
 /// Generates a placeholder value for the `symbol` value for the `RawFinancialDataReport` struct.
 fn default_resource() -> String {
     "uninitialized".into()
 }
 
-/// Values extracted from the CSV returned from the Alpha Vantage API.
+/// Values extracted from the CSV returned from the Alpha Vantage API, or from an imported file.
 #[derive(Debug, Deserialize)]
-struct RawFinancialDataReport {
+pub(crate) struct RawFinancialDataReport {
     #[serde(default = "default_resource")]
     pub symbol: String,
     pub timestamp: time::Date,
@@ -34,17 +51,28 @@ impl From<RawFinancialDataReport> for FinancialDataReport {
 }
 
 /// Queries the Alpha Vantange API for a given global equity.
+///
+/// Returns the latest `COMPACT_ENTRIES` entries, or the full history if `full` is set.
+#[tracing::instrument(name = "provider.query", skip(api_key))]
 async fn query_alpha_vantage(
     api_key: &str,
     symbol: &str,
+    full: bool,
 ) -> Result<Vec<FinancialDataReport>, DatabaseUpsertError> {
     log::trace!(
         "Requesting data from Alpha Vantage API for `{}` in CSV format.",
         symbol
     );
+    let output_size = match full {
+        true => "full",
+        false => "compact",
+    };
     let resp = reqwest::get(
-        format!("https://www.alphavantage.co/query?function=TIME_SERIES_DAILY_ADJUSTED&apikey={}&symbol={}&datatype=csv", api_key, symbol)
+        format!("https://www.alphavantage.co/query?function=TIME_SERIES_DAILY_ADJUSTED&apikey={}&symbol={}&outputsize={}&datatype=csv", api_key, symbol, output_size)
     ).await
+        .inspect_err(|_| metrics::PROVIDER_ERRORS.with_label_values(&["request"]).inc())
+        // The URL holds the API key, which must not end up in the logs nor in `ingestion_runs`.
+        .map_err(reqwest::Error::without_url)
         .into_report()
         .change_context(DatabaseUpsertError)
         .attach("Failed to query Alpha Vantage API.")?;
@@ -52,49 +80,86 @@ async fn query_alpha_vantage(
     let text = resp
         .text()
         .await
+        .inspect_err(|_| metrics::PROVIDER_ERRORS.with_label_values(&["body"]).inc())
+        .map_err(reqwest::Error::without_url)
         .into_report()
         .change_context(DatabaseUpsertError)
         .attach("Failed to read Alpha Vantage API query response body.")?;
     log::trace!("Create CSV reader.");
     let mut csv = csv::Reader::from_reader(text.as_bytes());
 
-    log::trace!("Getting date from 2 weeks ago for filtering.");
-    let today = time::OffsetDateTime::now_utc();
-    let two_weeks = (today - time::Duration::weeks(2)).date();
-
     log::trace!("Deserializing CSV into `RawFinancialDataReport` objects, mapping them into `FinancialDataReport`, and returning.");
     csv.deserialize()
-        .filter_map(
-            |raw: std::result::Result<RawFinancialDataReport, csv::Error>| match raw {
-                Ok(mut dr) => {
+        .map(
+            |raw: std::result::Result<RawFinancialDataReport, csv::Error>| {
+                raw.map(|mut dr| {
                     dr.symbol = symbol.to_string();
-                    match &dr.timestamp.cmp(&two_weeks) {
-                        std::cmp::Ordering::Greater | std::cmp::Ordering::Equal => {
-                            Some(Ok(FinancialDataReport::from(dr)))
-                        }
-                        _ => None,
-                    }
-                }
-                Err(er) => Some(Err(er)),
+                    FinancialDataReport::from(dr)
+                })
             },
         )
         .collect::<std::result::Result<Vec<FinancialDataReport>, csv::Error>>()
+        .inspect_err(|_| metrics::PROVIDER_ERRORS.with_label_values(&["parse"]).inc())
         .into_report()
         .change_context(DatabaseUpsertError)
         .attach("Failed to process Alpha Vantage API response.")
 }
 
 /// Upserts `FinancialDataReport` into database.
-async fn upsert_in_database(
+///
+/// Rows that were inserted or changed are published on the `FINANCIAL_DATA_UPDATES_CHANNEL`
+/// notification channel, which Postgres delivers once the transaction commits, and returned.
+/// Their previous values are kept on the `financial_data_revisions` table.
+#[tracing::instrument(skip_all, fields(rows = rows.len()))]
+pub async fn upsert_in_database(
     pool: sqlx::PgPool,
     rows: Vec<FinancialDataReport>,
-) -> Result<(), DatabaseUpsertError> {
+) -> Result<Vec<FinancialDataReport>, DatabaseUpsertError> {
+    upsert_rows(pool, rows, None).await
+}
+
+/// Upserts `FinancialDataReport` into database like `upsert_in_database`, recording each insert
+/// or change, along with the previous values, on the `financial_data_audit` table.
+#[tracing::instrument(skip_all, fields(rows = rows.len(), author = %author.name))]
+pub async fn upsert_audited(
+    pool: sqlx::PgPool,
+    rows: Vec<FinancialDataReport>,
+    author: &ChangeAuthor,
+) -> Result<Vec<FinancialDataReport>, DatabaseUpsertError> {
+    upsert_rows(pool, rows, Some(author)).await
+}
+
+/// Upserts each row within a single transaction, auditing the changes when `author` is given.
+async fn upsert_rows(
+    pool: sqlx::PgPool,
+    rows: Vec<FinancialDataReport>,
+    author: Option<&ChangeAuthor>,
+) -> Result<Vec<FinancialDataReport>, DatabaseUpsertError> {
+    let previous_query = r#"
+    SELECT symbol, date, open_price, close_price, volume
+    FROM financial_data
+    WHERE symbol = CAST($1 AS BPCHAR) AND date = $2
+    FOR UPDATE;"#;
     let query = r#"
     INSERT INTO financial_data (symbol, date, open_price, close_price, volume)
     VALUES ($1, $2, $3, $4, $5)
     ON CONFLICT (symbol, date)
     DO UPDATE
-    SET open_price = EXCLUDED.open_price, close_price = EXCLUDED.close_price, volume = EXCLUDED.volume;"#;
+    SET open_price = EXCLUDED.open_price, close_price = EXCLUDED.close_price, volume = EXCLUDED.volume
+    WHERE (financial_data.open_price, financial_data.close_price, financial_data.volume)
+        IS DISTINCT FROM (EXCLUDED.open_price, EXCLUDED.close_price, EXCLUDED.volume);"#;
+    let notify_query = "SELECT pg_notify($1, $2);";
+
+    let years = rows.iter().map(|r| r.date.year());
+    if let (Some(first_year), Some(last_year)) = (years.clone().min(), years.max()) {
+        log::trace!("Creating the partitions of the values.");
+        create_partitions(&pool, first_year, last_year)
+            .await
+            .into_report()
+            .change_context(DatabaseUpsertError)
+            .attach("Failed to create partitions of values.")?;
+    }
+
     log::trace!("Initializing upsert transaction.");
     let mut trans = pool
         .begin()
@@ -104,19 +169,68 @@ async fn upsert_in_database(
         .attach("Failed to create transaction on Postgres database.")?;
 
     log::trace!("Upserting each value from the Alpha Vantage API query into the database.");
+    let mut updated = vec![];
     for r in rows.into_iter() {
-        let urows = sqlx::query(query)
-            .bind(r.symbol)
-            .bind(r.date)
-            .bind(r.open_price)
-            .bind(r.close_price)
-            .bind(r.volume)
-            .execute(&mut trans)
-            .await
-            .into_report()
-            .change_context(DatabaseUpsertError)
-            .attach("Failed to upsert value into database.")?;
+        // Locking the previous values keeps the audit consistent with concurrent writers.
+        let previous = match author {
+            Some(_) => sqlx::query_as::<_, FinancialDataReport>(previous_query)
+                .bind(&r.symbol)
+                .bind(r.date)
+                .fetch_optional(&mut trans)
+                .await
+                .into_report()
+                .change_context(DatabaseUpsertError)
+                .attach("Failed to query previous value from database.")?,
+            None => None,
+        };
+        let urows = metrics::time_query(
+            "upsert_financial_data",
+            sqlx::query(query)
+                .bind(&r.symbol)
+                .bind(r.date)
+                .bind(r.open_price)
+                .bind(r.close_price)
+                .bind(r.volume)
+                .execute(&mut trans),
+        )
+        .await
+        .into_report()
+        .change_context(DatabaseUpsertError)
+        .attach("Failed to upsert value into database.")?;
         log::info!("`{}` rows were updated.", urows.rows_affected());
+
+        if urows.rows_affected() > 0 {
+            let payload = serde_json::to_string(&r)
+                .into_report()
+                .change_context(DatabaseUpsertError)
+                .attach("Failed to serialize update notification.")?;
+            sqlx::query(notify_query)
+                .bind(FINANCIAL_DATA_UPDATES_CHANNEL)
+                .bind(payload)
+                .execute(&mut trans)
+                .await
+                .into_report()
+                .change_context(DatabaseUpsertError)
+                .attach("Failed to notify update of value.")?;
+            record_revision(&mut trans, &r.symbol, r.date, Some(&r))
+                .await
+                .into_report()
+                .change_context(DatabaseUpsertError)
+                .attach("Failed to record revision of value.")?;
+            mark_stale_month(&mut trans, &r.symbol, r.date)
+                .await
+                .into_report()
+                .change_context(DatabaseUpsertError)
+                .attach("Failed to mark aggregates of value as stale.")?;
+            if let Some(author) = author {
+                record_change(&mut trans, author, previous.as_ref(), Some(&r))
+                    .await
+                    .into_report()
+                    .change_context(DatabaseUpsertError)
+                    .attach("Failed to record change on audit table.")?;
+            }
+            updated.push(r);
+        }
     }
 
     log::trace!("Committing upsert transaction.");
@@ -125,60 +239,217 @@ async fn upsert_in_database(
         .await
         .into_report()
         .change_context(DatabaseUpsertError)
-        .attach("Failed to commit transaction on Postgres database.")
+        .attach("Failed to commit transaction on Postgres database.")?;
+    for r in updated.iter() {
+        metrics::ROWS_UPSERTED
+            .with_label_values(&[r.symbol.trim()])
+            .inc();
+    }
+    Ok(updated)
+}
+
+/// Global equities queried from Alpha Vantage.
+const SYMBOLS: [&str; 2] = ["IBM", "AAPL"];
+
+/// Number of latest entries returned by Alpha Vantage, unless the full history is requested.
+const COMPACT_ENTRIES: usize = 100;
+
+/// Time waited before retrying a failed query of Alpha Vantage.
+const RETRY_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Finds the trading days of the last `days` days, up to yesterday, on which the time series of `symbol` has no entry.
+async fn find_gaps(
+    pool: &sqlx::PgPool,
+    calendar: &TradingCalendar,
+    symbol: &str,
+    today: time::Date,
+    days: u32,
+) -> Result<Vec<time::Date>, DatabaseUpsertError> {
+    if days == 0 {
+        return Ok(vec![]);
+    }
+    let start = today - time::Duration::days(days as i64);
+    let end = today - time::Duration::days(1);
+    calendar
+        .missing_days(pool, symbol, start, end)
+        .await
+        .change_context(DatabaseUpsertError)
+        .attach_printable_lazy(|| format!("Failed to find the missing days of `{}`.", symbol))
 }
 
-/// Queries Alpha Vantage API and upserts into database
-pub async fn get_raw_data(pool: sqlx::PgPool, api_key: String) -> Result<(), DatabaseUpsertError> {
+/// Number of missing trading days that are among the fetched entries, each counted once
+/// however many entries of its date were fetched.
+fn filled_gaps(gaps: &[time::Date], fetched: &[FinancialDataReport]) -> usize {
+    let fetched: HashSet<_> = fetched.iter().map(|r| r.date).collect();
+    gaps.iter().filter(|date| fetched.contains(date)).count()
+}
+
+/// Queries Alpha Vantage API, upserts into database, and evaluates the alert rules against the updated values.
+/// Returns the number of new or changed rows, or `None` if interrupted.
+///
+/// Along with the entries of the last 2 weeks, the trading days missing within the last `gap_fill_days` days
+/// are saved if Alpha Vantage provides them, requesting the full history when they are too old for the compact one.
+/// `shutdown` is checked between symbols, so that a shutdown never interrupts the upsert transaction.
+pub async fn get_raw_data(
+    pool: sqlx::PgPool,
+    api_key: String,
+    calendar: &CalendarConfig,
+    gap_fill_days: u32,
+    shutdown: &CancellationToken,
+) -> Result<Option<usize>, DatabaseUpsertError> {
+    log::trace!("Loading trading calendar.");
+    let calendar = TradingCalendar::load(&pool, calendar)
+        .await
+        .change_context(DatabaseUpsertError)?;
+    let today = time::OffsetDateTime::now_utc().date();
+    let two_weeks = today - time::Duration::weeks(2);
+
     log::trace!("Querying AlphaVantage");
-    let ibm = query_alpha_vantage(&api_key, "IBM").await?;
-    let aapl = query_alpha_vantage(&api_key, "AAPL").await?;
-    let rows = [ibm, aapl].into_iter().flatten().collect();
+    let mut rows = vec![];
+    for symbol in SYMBOLS {
+        if shutdown.is_cancelled() {
+            log::info!("Shutdown requested, skipping remaining queries of Alpha Vantage API.");
+            return Ok(None);
+        }
+        let gaps = find_gaps(&pool, &calendar, symbol, today, gap_fill_days).await?;
+        let full = gaps
+            .first()
+            .is_some_and(|first| calendar.trading_days(*first, today).count() > COMPACT_ENTRIES);
+        let fetched = query_alpha_vantage(&api_key, symbol, full).await?;
+
+        let filled = filled_gaps(&gaps, &fetched);
+        if !gaps.is_empty() {
+            log::info!(
+                "`{}` trading days of `{}` were missing, `{}` were provided by Alpha Vantage.",
+                gaps.len(),
+                symbol,
+                filled
+            );
+        }
+        metrics::MISSING_TRADING_DAYS
+            .with_label_values(&[symbol])
+            .set((gaps.len() - filled) as i64);
+        rows.extend(
+            fetched
+                .into_iter()
+                .filter(|r| r.date >= two_weeks || gaps.binary_search(&r.date).is_ok()),
+        );
+    }
 
     log::trace!("Saving values into database");
-    upsert_in_database(pool, rows).await
+    let updated = upsert_in_database(pool.clone(), rows).await?;
+
+    log::trace!("Evaluating alert rules");
+    // The values are already saved, so a failure here must not make the ingestion be retried.
+    if let Err(err) = evaluate_alerts(pool, &updated).await {
+        log::error!("{:?}", err);
+    }
+    Ok(Some(updated.len()))
 }
 
 /// Creates a recurring task to collect data from Alpha Vantage and upserts into the database.
 ///
-/// Runs every day. A channel is used signal if the task should be quit. The channel is queried every 5 seconds.
+/// Runs every `days` days, retrying failed runs after a few seconds. Quits once `shutdown` is cancelled.
+/// Each completed run is recorded on the `ingestion_runs` table.
 pub async fn recurring_get_raw_data(
     pool: sqlx::PgPool,
-    mut stop_channel: Receiver<()>,
+    shutdown: CancellationToken,
     api_key: String,
     days: i64,
+    calendar: CalendarConfig,
+    gap_fill_days: u32,
 ) -> Result<(), DatabaseUpsertError> {
-    log::trace!("Collecting current time and initializing interval.");
-    let mut last_exec = time::OffsetDateTime::now_utc();
-    let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+    log::trace!("Collecting current time.");
+    let mut next_exec = time::OffsetDateTime::now_utc();
     loop {
         let now = time::OffsetDateTime::now_utc();
-        if now.cmp(&last_exec).is_ge() {
+        let wait = if now.cmp(&next_exec).is_ge() {
+            if let Err(err) = create_partitions_ahead(&pool).await {
+                log::error!("Failed to create partitions ahead of time: {}", err);
+            }
             log::trace!("Daily quering of Alpha Vantage API.");
-            match get_raw_data(pool.clone(), api_key.clone()).await {
+            let started = std::time::Instant::now();
+            let result = get_raw_data(
+                pool.clone(),
+                api_key.clone(),
+                &calendar,
+                gap_fill_days,
+                &shutdown,
+            )
+            .instrument(tracing::info_span!("ingestion_run"))
+            .await;
+            let outcome = match &result {
+                Ok(Some(count)) => Some(Ok(*count)),
+                Ok(None) => None,
+                Err(err) => Some(Err(format!("{:#}", err))),
+            };
+            let label = match &outcome {
+                Some(Ok(_)) => "success",
+                Some(Err(_)) => "failure",
+                None => "interrupted",
+            };
+            metrics::INGESTION_RUN_DURATION
+                .with_label_values(&[label])
+                .observe(started.elapsed().as_secs_f64());
+            if let Some(outcome) = outcome {
+                if let Err(err) =
+                    record_ingestion_run(&pool, ALPHA_VANTAGE_SOURCE, now, outcome).await
+                {
+                    log::error!("{:?}", err);
+                }
+            }
+            match result {
                 Ok(_) => {
-                    last_exec += time::Duration::days(days);
+                    next_exec += time::Duration::days(days);
+                    (next_exec - time::OffsetDateTime::now_utc())
+                        .try_into()
+                        .unwrap_or_default()
                 }
                 Err(err) => {
                     log::error!("{}", err);
+                    RETRY_DELAY
                 }
-            };
-        }
-
-        log::trace!("Querying channel for stop signal.");
-        let stop_signal = stop_channel.try_recv();
-        match stop_signal {
-            Ok(_) => break,
-            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => {
-                interval.tick().await;
-            }
-            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => {
-                return Err(DatabaseUpsertError)
-                    .into_report()
-                    .attach("Recurring task was disconnected from it's channel")
             }
+        } else {
+            (next_exec - now).try_into().unwrap_or_default()
+        };
+
+        log::trace!("Waiting for next execution or stop signal.");
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(wait) => {}
         }
     }
     log::trace!("Exited from recurring task.");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(date: time::Date) -> FinancialDataReport {
+        FinancialDataReport {
+            symbol: "IBM".into(),
+            date,
+            open_price: 1.,
+            close_price: 1.,
+            volume: 1,
+        }
+    }
+
+    #[test]
+    fn filled_gaps_count_each_missing_day_once() {
+        let day = |d| time::Date::from_calendar_date(2023, time::Month::March, d).unwrap();
+        let gaps = [day(1), day(2), day(3)];
+        let fetched = [
+            report(day(1)),
+            report(day(1)),
+            report(day(3)),
+            report(day(6)),
+            report(day(7)),
+        ];
+        assert_eq!(filled_gaps(&gaps, &fetched), 2);
+        assert_eq!(filled_gaps(&[], &fetched), 0);
+    }
+}
@@ -0,0 +1,445 @@
+use std::{collections::HashMap, path::Path};
+
+use error_stack::{IntoReport, Report, Result, ResultExt};
+
+use crate::{error::FileImportError, model::FinancialDataReport};
+
+use super::RawFinancialDataReport;
+
+/// Maximum length of a symbol, bound by the `CHAR(8)` column on the `financial_data` table.
+const MAX_SYMBOL_LENGTH: usize = 8;
+
+/// Format of a file being imported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImportFormat {
+    Csv,
+    Json,
+}
+
+impl ImportFormat {
+    /// Guesses the format of a file from its extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("csv") => Some(ImportFormat::Csv),
+            Some("json") => Some(ImportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Where the symbol of each imported row comes from.
+#[derive(Debug, Clone)]
+pub enum SymbolSource {
+    /// Read from a column of the file.
+    Column(String),
+    /// Same symbol for every row.
+    Fixed(String),
+    /// Derived from the name of the file, e.g. `ibm.csv` => `IBM`.
+    FileName,
+}
+
+/// Names of the columns (or JSON keys) holding each value of a `FinancialDataReport`.
+#[derive(Debug, Clone)]
+pub struct ColumnMapping {
+    pub symbol: SymbolSource,
+    pub date: String,
+    pub open: String,
+    pub close: String,
+    pub volume: String,
+}
+
+/// Changes that an import would make to the `financial_data` table.
+#[derive(Debug, Default)]
+pub struct UpsertPlan {
+    pub inserted: Vec<FinancialDataReport>,
+    pub updated: Vec<(FinancialDataReport, FinancialDataReport)>,
+    pub unchanged: usize,
+    /// Rows overridden by a later row with the same symbol and date, which the upsert writes last.
+    pub duplicates: usize,
+}
+
+/// Reads every record of a CSV file as a map of column name to value.
+fn read_csv_records(path: &Path) -> Result<Vec<HashMap<String, String>>, FileImportError> {
+    let mut reader = csv::Reader::from_path(path)
+        .into_report()
+        .change_context(FileImportError)
+        .attach("Failed to open CSV file.")?;
+    let headers = reader
+        .headers()
+        .into_report()
+        .change_context(FileImportError)
+        .attach("Failed to read CSV headers.")?
+        .clone();
+
+    reader
+        .records()
+        .map(|record| {
+            record.map(|record| {
+                headers
+                    .iter()
+                    .zip(record.iter())
+                    .map(|(header, value)| (header.trim().to_string(), value.trim().to_string()))
+                    .collect()
+            })
+        })
+        .collect::<std::result::Result<_, csv::Error>>()
+        .into_report()
+        .change_context(FileImportError)
+        .attach("Failed to read CSV record.")
+}
+
+/// Reads every object of a JSON array as a map of key to value.
+fn read_json_records(path: &Path) -> Result<Vec<HashMap<String, String>>, FileImportError> {
+    let file = std::fs::File::open(path)
+        .into_report()
+        .change_context(FileImportError)
+        .attach("Failed to open JSON file.")?;
+    let objects: Vec<serde_json::Map<String, serde_json::Value>> =
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .into_report()
+            .change_context(FileImportError)
+            .attach("JSON file must contain an array of objects.")?;
+
+    Ok(objects
+        .into_iter()
+        .map(|object| {
+            object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (key, value)
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Gets and parses the value of a column of a record.
+fn parse_column<T>(
+    record: &HashMap<String, String>,
+    column: &str,
+    parse: impl FnOnce(&str) -> Option<T>,
+) -> Result<T, FileImportError> {
+    let value = record
+        .get(column)
+        .ok_or_else(|| Report::new(FileImportError))
+        .attach_printable_lazy(|| format!("Column `{}` is missing.", column))?;
+    parse(value)
+        .ok_or_else(|| Report::new(FileImportError))
+        .attach_printable_lazy(|| format!("Invalid value `{}` on column `{}`.", value, column))
+}
+
+/// Converts a record of an imported file into a `RawFinancialDataReport`.
+fn convert_record(
+    record: &HashMap<String, String>,
+    mapping: &ColumnMapping,
+    date_format: &[time::format_description::FormatItem<'_>],
+    file_symbol: &str,
+) -> Result<RawFinancialDataReport, FileImportError> {
+    let symbol = match &mapping.symbol {
+        SymbolSource::Column(column) => parse_column(record, column, |s| Some(s.to_string()))?,
+        SymbolSource::Fixed(symbol) => symbol.clone(),
+        SymbolSource::FileName => file_symbol.to_string(),
+    }
+    .trim()
+    .to_uppercase();
+    if symbol.is_empty() || symbol.len() > MAX_SYMBOL_LENGTH {
+        return Err(Report::new(FileImportError)).attach_printable(format!(
+            "Symbol `{}` must have between 1 and {} characters.",
+            symbol, MAX_SYMBOL_LENGTH
+        ));
+    }
+
+    Ok(RawFinancialDataReport {
+        symbol,
+        timestamp: parse_column(record, &mapping.date, |s| {
+            time::Date::parse(s, date_format).ok()
+        })?,
+        open: parse_column(record, &mapping.open, |s| s.parse().ok())?,
+        close: parse_column(record, &mapping.close, |s| s.parse().ok())?,
+        volume: parse_column(record, &mapping.volume, |s| s.parse().ok())?,
+    })
+}
+
+/// Reads a CSV or JSON file and converts its records into `FinancialDataReport`.
+///
+/// `date_format` uses the `time` crate format description syntax, e.g. `[year]-[month]-[day]`.
+pub fn read_import_file(
+    path: &Path,
+    format: ImportFormat,
+    mapping: &ColumnMapping,
+    date_format: &str,
+) -> Result<Vec<FinancialDataReport>, FileImportError> {
+    log::trace!("Parsing date format.");
+    let date_format = time::format_description::parse(date_format)
+        .into_report()
+        .change_context(FileImportError)
+        .attach("Failed to parse date format.")?;
+
+    let file_symbol = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_string();
+
+    log::trace!("Reading records from `{}`.", path.display());
+    let records = match format {
+        ImportFormat::Csv => read_csv_records(path)?,
+        ImportFormat::Json => read_json_records(path)?,
+    };
+
+    log::trace!("Converting records into `FinancialDataReport` objects.");
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, record)| {
+            convert_record(record, mapping, &date_format, &file_symbol)
+                .map(FinancialDataReport::from)
+                .attach_printable_lazy(|| format!("Failed to convert record number {}.", i + 1))
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .attach_printable_lazy(|| format!("Failed to import `{}`.", path.display()))
+}
+
+/// Compares `rows` against the values on the database without changing anything.
+pub async fn plan_upsert(
+    pool: sqlx::PgPool,
+    rows: Vec<FinancialDataReport>,
+) -> Result<UpsertPlan, FileImportError> {
+    let query = r#"
+    SELECT *
+    FROM financial_data
+    WHERE symbol = CAST($1 AS BPCHAR) AND date BETWEEN $2 AND $3;"#;
+
+    log::trace!("Grouping rows by symbol.");
+    let mut ranges: HashMap<String, (time::Date, time::Date)> = HashMap::new();
+    for r in rows.iter() {
+        ranges
+            .entry(r.symbol.clone())
+            .and_modify(|(start, end)| {
+                *start = (*start).min(r.date);
+                *end = (*end).max(r.date);
+            })
+            .or_insert((r.date, r.date));
+    }
+
+    log::trace!("Querying existing values for each symbol.");
+    let mut existing = HashMap::new();
+    for (symbol, (start, end)) in ranges {
+        let current = sqlx::query_as::<_, FinancialDataReport>(query)
+            .bind(&symbol)
+            .bind(start)
+            .bind(end)
+            .fetch_all(&pool)
+            .await
+            .into_report()
+            .change_context(FileImportError)
+            .attach("Failed to query financial data on Postgres database.")?;
+        existing.extend(current.into_iter().map(|mut r| {
+            r.symbol = r.symbol.trim().into();
+            ((r.symbol.clone(), r.date), r)
+        }));
+    }
+
+    log::trace!("Classifying rows.");
+    Ok(classify_rows(rows, existing))
+}
+
+/// Keeps the last row of each symbol and date, in the order of the rows, like successive upserts do.
+/// Returns the rows kept and the number of rows dropped.
+fn deduplicate_rows(rows: Vec<FinancialDataReport>) -> (Vec<FinancialDataReport>, usize) {
+    let mut last = HashMap::new();
+    for (i, r) in rows.iter().enumerate() {
+        last.insert((r.symbol.clone(), r.date), i);
+    }
+    let count = rows.len();
+    let kept: Vec<_> = rows
+        .into_iter()
+        .enumerate()
+        .filter(|(i, r)| last.get(&(r.symbol.clone(), r.date)) == Some(i))
+        .map(|(_, r)| r)
+        .collect();
+    let duplicates = count - kept.len();
+    (kept, duplicates)
+}
+
+/// Classifies `rows` against the `existing` values, keyed by symbol and date.
+fn classify_rows(
+    rows: Vec<FinancialDataReport>,
+    mut existing: HashMap<(String, time::Date), FinancialDataReport>,
+) -> UpsertPlan {
+    let (rows, duplicates) = deduplicate_rows(rows);
+    let mut plan = UpsertPlan {
+        duplicates,
+        ..Default::default()
+    };
+    for r in rows.into_iter() {
+        match existing.remove(&(r.symbol.clone(), r.date)) {
+            None => plan.inserted.push(r),
+            Some(old)
+                if old.open_price == r.open_price
+                    && old.close_price == r.close_price
+                    && old.volume == r.volume =>
+            {
+                plan.unchanged += 1
+            }
+            Some(old) => plan.updated.push((old, r)),
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes `contents` into a new file of the temporary directory, named `name`.
+    fn temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("file_import_{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn mapping(symbol: SymbolSource) -> ColumnMapping {
+        ColumnMapping {
+            symbol,
+            date: "timestamp".into(),
+            open: "open".into(),
+            close: "close".into(),
+            volume: "volume".into(),
+        }
+    }
+
+    fn row(symbol: &str, date: time::Date, close_price: f64) -> FinancialDataReport {
+        FinancialDataReport {
+            symbol: symbol.into(),
+            date,
+            open_price: 1.,
+            close_price,
+            volume: 10,
+        }
+    }
+
+    #[test]
+    fn csv_symbol_comes_from_file_name() {
+        let path = temp_file(
+            "ibm.csv",
+            "timestamp, open, close, volume\n2023-03-01, 128.9, 129.5, 100\n",
+        );
+        let rows = read_import_file(
+            &path,
+            ImportFormat::Csv,
+            &mapping(SymbolSource::FileName),
+            "[year]-[month]-[day]",
+        )
+        .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].symbol, "IBM");
+        assert_eq!(rows[0].date, time::macros::date!(2023 - 03 - 01));
+        assert_eq!(rows[0].close_price, 129.5);
+        assert_eq!(rows[0].volume, 100);
+    }
+
+    #[test]
+    fn json_reads_symbol_column_and_date_format() {
+        let path = temp_file(
+            "prices.json",
+            r#"[{"ticker": " aapl", "day": "01/03/2023", "o": 1.5, "c": 2, "v": 7}]"#,
+        );
+        let mapping = ColumnMapping {
+            symbol: SymbolSource::Column("ticker".into()),
+            date: "day".into(),
+            open: "o".into(),
+            close: "c".into(),
+            volume: "v".into(),
+        };
+        let rows =
+            read_import_file(&path, ImportFormat::Json, &mapping, "[day]/[month]/[year]").unwrap();
+        assert_eq!(rows[0].symbol, "AAPL");
+        assert_eq!(rows[0].date, time::macros::date!(2023 - 03 - 01));
+        assert_eq!(rows[0].open_price, 1.5);
+        assert_eq!(rows[0].close_price, 2.);
+    }
+
+    #[test]
+    fn invalid_records_are_refused() {
+        let path = temp_file("ibm.csv", "timestamp,open,close,volume\n2023-13-01,1,2,3\n");
+        let format = "[year]-[month]-[day]";
+        assert!(read_import_file(
+            &path,
+            ImportFormat::Csv,
+            &mapping(SymbolSource::FileName),
+            format
+        )
+        .is_err());
+
+        let path = temp_file("ibm.csv", "timestamp,open,close,volume\n2023-03-01,1,2,3\n");
+        let long_symbol = SymbolSource::Fixed("TOOLONGSYM".into());
+        assert!(read_import_file(&path, ImportFormat::Csv, &mapping(long_symbol), format).is_err());
+
+        let path = temp_file("ibm.csv", "timestamp,open,volume\n2023-03-01,1,3\n");
+        assert!(read_import_file(
+            &path,
+            ImportFormat::Csv,
+            &mapping(SymbolSource::FileName),
+            format
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn plan_classifies_rows_against_existing_values() {
+        let first = time::macros::date!(2023 - 03 - 01);
+        let second = time::macros::date!(2023 - 03 - 02);
+        let third = time::macros::date!(2023 - 03 - 03);
+        let existing = HashMap::from([
+            (("IBM".into(), first), row("IBM", first, 2.)),
+            (("IBM".into(), second), row("IBM", second, 2.)),
+        ]);
+        let plan = classify_rows(
+            vec![
+                row("IBM", first, 2.),
+                row("IBM", second, 3.),
+                row("IBM", third, 4.),
+            ],
+            existing,
+        );
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(plan.updated.len(), 1);
+        assert_eq!(plan.updated[0].1.close_price, 3.);
+        assert_eq!(plan.inserted.len(), 1);
+        assert_eq!(plan.inserted[0].date, third);
+        assert_eq!(plan.duplicates, 0);
+    }
+
+    #[test]
+    fn plan_keeps_last_duplicate() {
+        let first = time::macros::date!(2023 - 03 - 01);
+        let second = time::macros::date!(2023 - 03 - 02);
+        let existing = HashMap::from([(("IBM".into(), first), row("IBM", first, 2.))]);
+        let plan = classify_rows(
+            vec![
+                row("IBM", second, 5.),
+                row("IBM", first, 3.),
+                row("IBM", second, 6.),
+                row("IBM", first, 2.),
+            ],
+            existing,
+        );
+        assert_eq!(plan.duplicates, 2);
+        assert_eq!(plan.inserted.len(), 1);
+        assert_eq!(plan.inserted[0].close_price, 6.);
+        assert!(plan.updated.is_empty());
+        assert_eq!(plan.unchanged, 1);
+    }
+}
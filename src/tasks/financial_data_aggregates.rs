@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use error_stack::{IntoReport, Result, ResultExt};
+use tokio::sync::broadcast::{
+    error::{RecvError, TryRecvError},
+    Receiver,
+};
+use tokio_util::sync::CancellationToken;
+
+use crate::{error::AggregateRefreshError, metrics, model::FinancialDataUpdate};
+
+/// Time waited after an update before refreshing, so that the updates of a whole ingestion run are aggregated at once.
+const REFRESH_DELAY: Duration = Duration::from_secs(2);
+
+/// Marks the month of `date` as stale for `symbol`, within the transaction changing one of its entries.
+///
+/// Stale months are left out of `financial_data_monthly` by the `statistics` planner until they are refreshed.
+pub(crate) async fn mark_stale_month(
+    trans: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    symbol: &str,
+    date: time::Date,
+) -> std::result::Result<(), sqlx::Error> {
+    let query_str = r#"
+    INSERT INTO financial_data_stale_months (symbol, month)
+    VALUES ($1, CAST(date_trunc('month', CAST($2 AS DATE)) AS DATE))
+    ON CONFLICT (symbol, month) DO NOTHING;"#;
+
+    sqlx::query(query_str)
+        .bind(symbol)
+        .bind(date)
+        .execute(&mut *trans)
+        .await?;
+    Ok(())
+}
+
+/// Recomputes the rows of `financial_data_monthly` of every stale month. Returns the number of months refreshed.
+///
+/// Months marked stale by a transaction that commits during the refresh stay stale until the next one.
+/// Months pruned by the retention policies keep their aggregates, since their entries are gone.
+pub async fn refresh_aggregates(pool: &sqlx::PgPool) -> Result<usize, AggregateRefreshError> {
+    let take_query = r#"
+    WITH taken AS (
+        DELETE FROM financial_data_stale_months
+        RETURNING symbol, month
+    )
+    SELECT taken.symbol, taken.month
+    FROM taken
+    LEFT JOIN financial_data_retention ON financial_data_retention.symbol = taken.symbol
+    WHERE pruned_before IS NULL OR month >= pruned_before;"#;
+    let clear_query = r#"
+    DELETE FROM financial_data_monthly
+    WHERE (symbol, month) IN (SELECT * FROM UNNEST(CAST($1 AS CHAR(8)[]), CAST($2 AS DATE[])));"#;
+    let aggregate_query = r#"
+    INSERT INTO financial_data_monthly
+    SELECT
+        financial_data.symbol,
+        stale.month,
+        COUNT(*),
+        SUM(open_price),
+        SUM(close_price),
+        SUM(volume),
+        SUM(open_price * open_price),
+        SUM(close_price * close_price),
+        SUM(CAST(volume AS FLOAT8) * volume),
+        MIN(open_price),
+        MAX(open_price),
+        MIN(close_price),
+        MAX(close_price),
+        MIN(volume),
+        MAX(volume)
+    FROM UNNEST(CAST($1 AS CHAR(8)[]), CAST($2 AS DATE[])) AS stale (symbol, month)
+    JOIN financial_data ON financial_data.symbol = stale.symbol
+        AND financial_data.date >= stale.month
+        AND financial_data.date < stale.month + INTERVAL '1 month'
+    GROUP BY financial_data.symbol, stale.month;"#;
+
+    let mut trans = pool
+        .begin()
+        .await
+        .into_report()
+        .change_context(AggregateRefreshError)
+        .attach("Failed to start transaction.")?;
+    let stale: Vec<(String, time::Date)> = sqlx::query_as(take_query)
+        .fetch_all(&mut trans)
+        .await
+        .into_report()
+        .change_context(AggregateRefreshError)
+        .attach("Failed to take stale months.")?;
+    if stale.is_empty() {
+        // Commits the removal of the stale months that were pruned, if any.
+        trans
+            .commit()
+            .await
+            .into_report()
+            .change_context(AggregateRefreshError)
+            .attach("Failed to commit transaction.")?;
+        return Ok(0);
+    }
+    let (symbols, months): (Vec<_>, Vec<_>) = stale.into_iter().unzip();
+
+    sqlx::query(clear_query)
+        .bind(&symbols)
+        .bind(&months)
+        .execute(&mut trans)
+        .await
+        .into_report()
+        .change_context(AggregateRefreshError)
+        .attach("Failed to clear stale aggregates.")?;
+    metrics::time_query(
+        "aggregate_refresh",
+        sqlx::query(aggregate_query)
+            .bind(&symbols)
+            .bind(&months)
+            .execute(&mut trans),
+    )
+    .await
+    .into_report()
+    .change_context(AggregateRefreshError)
+    .attach("Failed to aggregate stale months.")?;
+    trans
+        .commit()
+        .await
+        .into_report()
+        .change_context(AggregateRefreshError)
+        .attach("Failed to commit transaction.")?;
+    Ok(months.len())
+}
+
+/// Refreshes the aggregates on startup, then shortly after every burst of updates.
+///
+/// Updates come from the Postgres notifications sent when `upsert_in_database` or `delete_from_database` commits,
+/// so the aggregates follow the ingestion as well as the `import` command and the write endpoints.
+/// Failed refreshes are logged and retried on the next update. Quits once `shutdown` is cancelled.
+pub async fn refresh_aggregates_on_updates(
+    pool: sqlx::PgPool,
+    mut updates: Receiver<FinancialDataUpdate>,
+    shutdown: CancellationToken,
+) {
+    loop {
+        match refresh_aggregates(&pool).await {
+            Ok(0) => {}
+            Ok(months) => log::info!("Refreshed the aggregates of `{}` months.", months),
+            Err(e) => log::error!("{:?}", e),
+        }
+
+        let update = tokio::select! {
+            _ = shutdown.cancelled() => break,
+            update = updates.recv() => update,
+        };
+        if let Err(RecvError::Closed) = update {
+            break;
+        }
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(REFRESH_DELAY) => {}
+        };
+        while let Ok(_) | Err(TryRecvError::Lagged(_)) = updates.try_recv() {}
+    }
+    log::trace!("Exited from aggregate refresh task.");
+}
@@ -0,0 +1,41 @@
+use crate::model::{ChangeAuthor, FinancialDataReport};
+
+/// Records a change of the time series on the `financial_data_audit` table, within the transaction making it.
+///
+/// The action is `insert` without `old` values, `delete` without `new` values, and `update` otherwise.
+pub(crate) async fn record_change(
+    trans: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    author: &ChangeAuthor,
+    old: Option<&FinancialDataReport>,
+    new: Option<&FinancialDataReport>,
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+    INSERT INTO financial_data_audit (
+        action, symbol, date,
+        old_open_price, old_close_price, old_volume,
+        new_open_price, new_close_price, new_volume,
+        author, api_key_id
+    )
+    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11);"#;
+    let (action, entry) = match (old, new) {
+        (None, Some(new)) => ("insert", new),
+        (Some(old), None) => ("delete", old),
+        (Some(_), Some(new)) => ("update", new),
+        (None, None) => return Ok(()),
+    };
+    sqlx::query(query)
+        .bind(action)
+        .bind(&entry.symbol)
+        .bind(entry.date)
+        .bind(old.map(|r| r.open_price))
+        .bind(old.map(|r| r.close_price))
+        .bind(old.map(|r| r.volume))
+        .bind(new.map(|r| r.open_price))
+        .bind(new.map(|r| r.close_price))
+        .bind(new.map(|r| r.volume))
+        .bind(&author.name)
+        .bind(author.api_key_id)
+        .execute(&mut *trans)
+        .await?;
+    Ok(())
+}
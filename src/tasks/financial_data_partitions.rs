@@ -0,0 +1,25 @@
+/// Years for which the partitions of `financial_data` are created ahead of the current one.
+const PARTITIONS_AHEAD: i32 = 1;
+
+/// Creates the missing yearly partitions of `financial_data` from `first_year` to `last_year`.
+///
+/// Creating a partition locks the whole table, so this must not run within a transaction that writes to it.
+pub(crate) async fn create_partitions<'c>(
+    db: impl sqlx::PgExecutor<'c>,
+    first_year: i32,
+    last_year: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT create_financial_data_partitions($1, $2);")
+        .bind(first_year)
+        .bind(last_year)
+        .execute(db)
+        .await?;
+    Ok(())
+}
+
+/// Creates the partitions of `financial_data` of the current year and of the `PARTITIONS_AHEAD` next ones,
+/// so that entries of a new year never wait for the creation of its partition.
+pub(crate) async fn create_partitions_ahead(pool: &sqlx::PgPool) -> Result<(), sqlx::Error> {
+    let year = time::OffsetDateTime::now_utc().year();
+    create_partitions(pool, year, year + PARTITIONS_AHEAD).await
+}
@@ -0,0 +1,387 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use error_stack::{IntoReport, Result, ResultExt};
+use flate2::{write::GzEncoder, Compression};
+use futures::{Stream, TryStreamExt};
+use serde::Serialize;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    config::{ArchiveFormat, RetentionConfig},
+    error::RetentionError,
+    metrics,
+    model::{ExportFormat, FinancialDataReport, PrunedSeries},
+};
+
+use super::{refresh_aggregates, write_financial_data};
+
+/// Postgres channel notified with the `PrunedSeries` of each pruned symbol.
+pub const FINANCIAL_DATA_PRUNINGS_CHANNEL: &str = "financial_data_prunings";
+
+/// Rows of a symbol older than its retention policy, pruned or to be pruned.
+#[derive(Debug)]
+pub struct RetentionReport {
+    pub symbol: String,
+    /// First day kept for the symbol.
+    pub cutoff: time::Date,
+    pub rows: usize,
+    pub first_date: time::Date,
+    pub last_date: time::Date,
+    /// File the rows are archived to, if an archive directory is configured.
+    pub archive: Option<PathBuf>,
+}
+
+/// Row of the CSV archives, with the columns the `import` command reads by default.
+#[derive(Serialize)]
+struct ArchivedRow<'a> {
+    symbol: &'a str,
+    timestamp: String,
+    open: f64,
+    close: f64,
+    volume: i32,
+}
+
+/// Writes the pruned rows into a gzip compressed CSV file. Returns the number of rows written.
+async fn write_csv_archive<S>(mut rows: S, file: File) -> Result<(usize, File), RetentionError>
+where
+    S: Stream<Item = std::result::Result<FinancialDataReport, sqlx::Error>> + Unpin,
+{
+    let mut writer = csv::Writer::from_writer(GzEncoder::new(file, Compression::default()));
+    let mut count = 0;
+    while let Some(row) = rows
+        .try_next()
+        .await
+        .into_report()
+        .change_context(RetentionError)
+        .attach("Failed to delete financial data on Postgres database.")?
+    {
+        writer
+            .serialize(ArchivedRow {
+                symbol: row.symbol.trim(),
+                timestamp: row.date.to_string(),
+                open: row.open_price,
+                close: row.close_price,
+                volume: row.volume,
+            })
+            .into_report()
+            .change_context(RetentionError)
+            .attach("Failed to write archived row.")?;
+        count += 1;
+    }
+    let file = writer
+        .into_inner()
+        .map_err(|err| err.into_error())
+        .and_then(GzEncoder::finish)
+        .into_report()
+        .change_context(RetentionError)
+        .attach("Failed to finish CSV archive.")?;
+    Ok((count, file))
+}
+
+/// Writes the pruned rows into a Parquet file, with the schema of the exports. Returns the number of rows written.
+async fn write_parquet_archive<S>(rows: S, file: File) -> Result<(usize, File), RetentionError>
+where
+    S: Stream<Item = std::result::Result<FinancialDataReport, sqlx::Error>> + Unpin,
+{
+    let (count, writer) = write_financial_data(rows, ExportFormat::Parquet, BufWriter::new(file))
+        .await
+        .change_context(RetentionError)?;
+    let file = writer
+        .into_inner()
+        .map_err(|err| err.into_error())
+        .into_report()
+        .change_context(RetentionError)
+        .attach("Failed to finish Parquet archive.")?;
+    Ok((count, file))
+}
+
+/// Temporary file an archive is written to until the pruning is committed, unique to each run, so that the files
+/// left by a crashed run or written by another replica never clash with it.
+fn partial_archive_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{}.{:08x}.partial", name, rand::random::<u32>()))
+}
+
+/// Archives the pruned rows into a new file at `path`, which is removed if any of them can't be written.
+/// Returns the number of rows archived.
+async fn archive_rows<S>(
+    rows: S,
+    path: &Path,
+    format: ArchiveFormat,
+) -> Result<usize, RetentionError>
+where
+    S: Stream<Item = std::result::Result<FinancialDataReport, sqlx::Error>> + Unpin,
+{
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .into_report()
+            .change_context(RetentionError)
+            .attach_printable_lazy(|| format!("Failed to create `{}`.", dir.display()))?;
+    }
+    let file = File::create(path)
+        .into_report()
+        .change_context(RetentionError)
+        .attach_printable_lazy(|| format!("Failed to create archive `{}`.", path.display()))?;
+
+    let archived = match format {
+        ArchiveFormat::Csv => write_csv_archive(rows, file).await,
+        ArchiveFormat::Parquet => write_parquet_archive(rows, file).await,
+    }
+    .and_then(|(count, file)| {
+        file.sync_all()
+            .into_report()
+            .change_context(RetentionError)
+            .attach("Failed to sync archive to disk.")?;
+        Ok(count)
+    });
+    // Another replica pruned the same rows first when none are left to archive.
+    if !matches!(archived, Ok(count) if count > 0) {
+        let _ = std::fs::remove_file(path);
+    }
+    archived.attach_printable_lazy(|| format!("Failed to archive to `{}`.", path.display()))
+}
+
+/// Removes the revisions of the pruned rows and records how far the symbol was pruned, then commits,
+/// notifying the pruning so that the cached responses of the symbol are invalidated.
+async fn finish_pruning(
+    mut trans: sqlx::Transaction<'_, sqlx::Postgres>,
+    symbol: &str,
+    cutoff: time::Date,
+) -> std::result::Result<(), sqlx::Error> {
+    let revisions_query = r#"
+    DELETE FROM financial_data_revisions
+    WHERE symbol = CAST($1 AS BPCHAR) AND date < $2;"#;
+    let retention_query = r#"
+    INSERT INTO financial_data_retention (symbol, pruned_before)
+    VALUES ($1, $2)
+    ON CONFLICT (symbol) DO UPDATE
+    SET pruned_before = GREATEST(financial_data_retention.pruned_before, EXCLUDED.pruned_before),
+        pruned_at = NOW();"#;
+
+    sqlx::query(revisions_query)
+        .bind(symbol)
+        .bind(cutoff)
+        .execute(&mut trans)
+        .await?;
+    sqlx::query(retention_query)
+        .bind(symbol)
+        .bind(cutoff)
+        .execute(&mut trans)
+        .await?;
+    let pruned = PrunedSeries {
+        symbol: symbol.trim().into(),
+        pruned_before: cutoff,
+    };
+    let payload =
+        serde_json::to_string(&pruned).expect("Pruned series are serialized to JSON infallibly.");
+    sqlx::query("SELECT pg_notify($1, $2);")
+        .bind(FINANCIAL_DATA_PRUNINGS_CHANNEL)
+        .bind(payload)
+        .execute(&mut trans)
+        .await?;
+    trans.commit().await
+}
+
+/// Deletes the rows of `symbol` before `cutoff` in a single transaction, archiving them first if configured.
+///
+/// Returns `None` if the symbol has no such rows. When `dry_run` is set, only reports what would be pruned.
+async fn prune_symbol(
+    pool: &sqlx::PgPool,
+    config: &RetentionConfig,
+    symbol: &str,
+    cutoff: time::Date,
+    dry_run: bool,
+) -> Result<Option<RetentionReport>, RetentionError> {
+    let plan_query = r#"
+    SELECT COUNT(*), MIN(date), MAX(date)
+    FROM financial_data
+    WHERE symbol = CAST($1 AS BPCHAR) AND date < $2;"#;
+    let prune_query = r#"
+    WITH pruned AS (
+        DELETE FROM financial_data
+        WHERE symbol = CAST($1 AS BPCHAR) AND date < $2
+        RETURNING *
+    )
+    SELECT * FROM pruned ORDER BY date;"#;
+
+    let mut trans = pool
+        .begin()
+        .await
+        .into_report()
+        .change_context(RetentionError)
+        .attach("Failed to start transaction.")?;
+    let (rows, first_date, last_date): (i64, Option<time::Date>, Option<time::Date>) =
+        sqlx::query_as(plan_query)
+            .bind(symbol)
+            .bind(cutoff)
+            .fetch_one(&mut trans)
+            .await
+            .into_report()
+            .change_context(RetentionError)
+            .attach("Failed to count rows to prune.")?;
+    let (Some(first_date), Some(last_date)) = (first_date, last_date) else {
+        return Ok(None);
+    };
+    let archive = config.archive_dir.as_ref().map(|dir| {
+        dir.join(format!(
+            "{}_{}_{}.{}",
+            symbol.trim(),
+            first_date,
+            last_date,
+            config.archive_format.extension()
+        ))
+    });
+    let mut report = RetentionReport {
+        symbol: symbol.trim().to_string(),
+        cutoff,
+        rows: rows as usize,
+        first_date,
+        last_date,
+        archive,
+    };
+    if dry_run {
+        return Ok(Some(report));
+    }
+
+    let pruned = sqlx::query_as::<_, FinancialDataReport>(prune_query)
+        .bind(symbol)
+        .bind(cutoff)
+        .fetch(&mut trans);
+    let partial = report.archive.as_deref().map(partial_archive_path);
+    report.rows = match &partial {
+        Some(path) => archive_rows(pruned, path, config.archive_format).await?,
+        None => pruned
+            .try_fold(0, |count, _| async move { Ok(count + 1) })
+            .await
+            .into_report()
+            .change_context(RetentionError)
+            .attach("Failed to delete financial data on Postgres database.")?,
+    };
+    if report.rows == 0 {
+        return Ok(None);
+    }
+
+    if let Err(err) = finish_pruning(trans, symbol, cutoff).await {
+        if let Some(path) = &partial {
+            let _ = std::fs::remove_file(path);
+        }
+        return Err(err)
+            .into_report()
+            .change_context(RetentionError)
+            .attach("Failed to commit transaction on Postgres database.");
+    }
+    metrics::ROWS_PRUNED
+        .with_label_values(&[symbol.trim()])
+        .inc_by(report.rows as u64);
+
+    // Only committed rows are given the final name, replacing the archive of the same rows if they were
+    // pruned before, then saved again.
+    if let (Some(partial), Some(path)) = (&partial, &report.archive) {
+        std::fs::rename(partial, path)
+            .into_report()
+            .change_context(RetentionError)
+            .attach_printable_lazy(|| {
+                format!(
+                    "Pruned rows of `{}` are archived to `{}`, which failed to be renamed to `{}`.",
+                    symbol.trim(),
+                    partial.display(),
+                    path.display()
+                )
+            })?;
+    }
+    Ok(Some(report))
+}
+
+/// Prunes the rows of every symbol older than its retention policy, returning a report per pruned symbol.
+///
+/// Stale aggregates are refreshed first, so that the aggregates of the pruned months are complete.
+/// Symbols that fail to be pruned are logged and skipped, so that they don't hold back the other ones.
+/// When `dry_run` is set, only reports what would be pruned.
+pub async fn enforce_retention(
+    pool: &sqlx::PgPool,
+    config: &RetentionConfig,
+    dry_run: bool,
+) -> Result<Vec<RetentionReport>, RetentionError> {
+    let symbols_query = r#"
+    SELECT DISTINCT symbol
+    FROM financial_data
+    WHERE date < $1
+    ORDER BY symbol;"#;
+
+    let today = time::OffsetDateTime::now_utc().date();
+    let Some(latest_cutoff) = config
+        .policies
+        .iter()
+        .map(|policy| policy.cutoff(today))
+        .max()
+    else {
+        return Ok(vec![]);
+    };
+    if !dry_run {
+        refresh_aggregates(pool)
+            .await
+            .change_context(RetentionError)
+            .attach("Failed to refresh aggregates before pruning.")?;
+    }
+    let symbols = sqlx::query_scalar::<_, String>(symbols_query)
+        .bind(latest_cutoff)
+        .fetch_all(pool)
+        .await
+        .into_report()
+        .change_context(RetentionError)
+        .attach("Failed to list symbols to prune.")?;
+
+    let mut reports = vec![];
+    for symbol in symbols {
+        let Some(policy) = config.policy(&symbol) else {
+            continue;
+        };
+        match prune_symbol(pool, config, &symbol, policy.cutoff(today), dry_run).await {
+            Ok(Some(report)) => reports.push(report),
+            Ok(None) => {}
+            Err(err) => log::error!("Failed to prune `{}`: {:?}", symbol.trim(), err),
+        }
+    }
+    Ok(reports)
+}
+
+/// Enforces the retention policies on startup, then every `interval_hours`. Quits once `shutdown` is cancelled.
+///
+/// Failed runs are logged and retried on the next one.
+pub async fn recurring_retention(
+    pool: sqlx::PgPool,
+    config: RetentionConfig,
+    shutdown: CancellationToken,
+) {
+    let interval = Duration::from_secs(config.interval_hours * 60 * 60);
+    loop {
+        match enforce_retention(&pool, &config, false).await {
+            Ok(reports) => {
+                for report in reports {
+                    log::info!(
+                        "Pruned `{}` rows of `{}` from {} to {}.",
+                        report.rows,
+                        report.symbol,
+                        report.first_date,
+                        report.last_date
+                    );
+                }
+            }
+            Err(e) => log::error!("{:?}", e),
+        }
+
+        tokio::select! {
+            _ = shutdown.cancelled() => break,
+            _ = tokio::time::sleep(interval) => {}
+        };
+    }
+    log::trace!("Exited from retention task.");
+}
@@ -0,0 +1,38 @@
+use crate::model::FinancialDataReport;
+
+/// Closes the current revision of the entry of `symbol` on `date`, then opens a revision holding `current`,
+/// within the transaction changing the entry. `None` for a deleted entry.
+///
+/// Revisions are valid from `recorded_at` until `superseded_at`, both set to the start of the transaction,
+/// so that every change of a transaction becomes known at the same time.
+pub(crate) async fn record_revision(
+    trans: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    symbol: &str,
+    date: time::Date,
+    current: Option<&FinancialDataReport>,
+) -> Result<(), sqlx::Error> {
+    let supersede_query = r#"
+    UPDATE financial_data_revisions
+    SET superseded_at = NOW()
+    WHERE symbol = CAST($1 AS BPCHAR) AND date = $2 AND superseded_at IS NULL;"#;
+    let insert_query = r#"
+    INSERT INTO financial_data_revisions (symbol, date, open_price, close_price, volume, recorded_at)
+    VALUES ($1, $2, $3, $4, $5, NOW());"#;
+
+    sqlx::query(supersede_query)
+        .bind(symbol)
+        .bind(date)
+        .execute(&mut *trans)
+        .await?;
+    if let Some(current) = current {
+        sqlx::query(insert_query)
+            .bind(&current.symbol)
+            .bind(current.date)
+            .bind(current.open_price)
+            .bind(current.close_price)
+            .bind(current.volume)
+            .execute(&mut *trans)
+            .await?;
+    }
+    Ok(())
+}
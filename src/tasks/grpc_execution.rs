@@ -0,0 +1,32 @@
+use error_stack::{IntoReport, Result, ResultExt};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    config::Config,
+    error::GrpcStartupError,
+    grpc::{proto::financial_data_server::FinancialDataServer, FinancialDataService},
+};
+
+/// Initializes and runs the gRPC server, on its own port.
+/// Runs until `shutdown` is cancelled, then stops accepting calls and waits for open ones.
+pub async fn grpc_startup(
+    config: Config,
+    database_pool: sqlx::PgPool,
+    shutdown: CancellationToken,
+) -> Result<(), GrpcStartupError> {
+    let service = FinancialDataService::new(database_pool, config.auth.enabled);
+
+    log::info!("Serving gRPC on `{}`.", config.grpc.bind_address);
+    tonic::transport::Server::builder()
+        .add_service(FinancialDataServer::new(service))
+        .serve_with_shutdown(config.grpc.bind_address, shutdown.cancelled_owned())
+        .await
+        .into_report()
+        .change_context(GrpcStartupError)
+        .attach_printable_lazy(|| {
+            format!(
+                "Failed to serve gRPC server on `{}`.",
+                config.grpc.bind_address
+            )
+        })
+}
@@ -0,0 +1,201 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    config::Config,
+    model::{HealthCheck, IngestionRun, PoolStatus, ProviderStatus},
+};
+
+use super::{last_successful_ingestion, LatestEntries};
+
+/// Tables created by `schema.sql`, which must exist for the server to be ready.
+const REQUIRED_TABLES: [&str; 13] = [
+    "financial_data",
+    "alert_rules",
+    "alert_events",
+    "alert_deliveries",
+    "ingestion_runs",
+    "api_keys",
+    "rate_limits",
+    "financial_data_audit",
+    "financial_data_revisions",
+    "trading_calendar_overrides",
+    "financial_data_monthly",
+    "financial_data_stale_months",
+    "financial_data_retention",
+];
+
+/// Time given to each check before it is considered failed.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Address probed to check if Alpha Vantage is reachable.
+const PROVIDER_URL: &str = "https://www.alphavantage.co/";
+
+/// Time the reachability of Alpha Vantage is reused for, so that the open `status` endpoint can't be used to
+/// flood it with probes.
+const PROVIDER_STATUS_TTL: Duration = Duration::from_secs(30);
+
+/// State shared with the health endpoints.
+#[derive(Debug, Clone)]
+pub struct HealthState {
+    pub started: Instant,
+    pub started_at: time::OffsetDateTime,
+    pub freshness_slo: Duration,
+    pub max_connections: u32,
+    pub client: reqwest::Client,
+    pub latest_entries: LatestEntries,
+    /// Last probe of Alpha Vantage, along with when it was made.
+    provider: Arc<Mutex<Option<(Instant, ProviderStatus)>>>,
+}
+
+impl HealthState {
+    /// Creates the state when the server starts.
+    pub fn new(config: &Config) -> HealthState {
+        HealthState {
+            started: Instant::now(),
+            started_at: time::OffsetDateTime::now_utc(),
+            freshness_slo: config.freshness_slo(),
+            max_connections: config.database.max_connections,
+            client: reqwest::Client::builder()
+                .timeout(CHECK_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            latest_entries: LatestEntries::default(),
+            provider: Arc::default(),
+        }
+    }
+
+    /// Runs the checks deciding if the server is ready, returning the latest successful ingestion run.
+    pub async fn readiness(&self, pool: &sqlx::PgPool) -> (Vec<HealthCheck>, Option<IngestionRun>) {
+        let database = check_database(pool).await;
+        if !database.healthy {
+            // The other checks need the database, so they would only repeat its error.
+            return (vec![database], None);
+        }
+        let schema = check_schema(pool).await;
+        let (ingestion, last_run) = self.check_ingestion(pool).await;
+        (vec![database, schema, ingestion], last_run)
+    }
+
+    /// Checks that the last successful ingestion is within the freshness SLO.
+    ///
+    /// Before any ingestion has succeeded, the server is given the SLO since its start to run one.
+    async fn check_ingestion(&self, pool: &sqlx::PgPool) -> (HealthCheck, Option<IngestionRun>) {
+        let last_run =
+            match tokio::time::timeout(CHECK_TIMEOUT, last_successful_ingestion(pool)).await {
+                Ok(Ok(last_run)) => last_run,
+                Ok(Err(err)) => return (check("ingestion", false, format!("{:#}", err)), None),
+                Err(_) => return (check("ingestion", false, timed_out()), None),
+            };
+        let slo = time::Duration::try_from(self.freshness_slo).unwrap_or(time::Duration::MAX);
+        let check = match &last_run {
+            Some(run) => {
+                let age = time::OffsetDateTime::now_utc() - run.finished_at;
+                check(
+                    "ingestion",
+                    age <= slo,
+                    format!(
+                        "Last successful ingestion finished {} seconds ago, the limit is {}.",
+                        age.whole_seconds(),
+                        slo.whole_seconds()
+                    ),
+                )
+            }
+            None => check(
+                "ingestion",
+                self.started.elapsed() <= self.freshness_slo,
+                "No ingestion has succeeded yet.".into(),
+            ),
+        };
+        (check, last_run)
+    }
+
+    /// Connections of the database pool.
+    pub fn pool_status(&self, pool: &sqlx::PgPool) -> PoolStatus {
+        PoolStatus {
+            size: pool.size(),
+            idle: pool.num_idle(),
+            max_connections: self.max_connections,
+        }
+    }
+
+    /// Checks if Alpha Vantage answers, with any status code, reusing the last probe for `PROVIDER_STATUS_TTL`.
+    ///
+    /// Concurrent callers wait for the same probe.
+    pub async fn provider_status(&self) -> ProviderStatus {
+        let mut last = self.provider.lock().await;
+        if let Some((probed, status)) = last.as_ref() {
+            if probed.elapsed() < PROVIDER_STATUS_TTL {
+                return status.clone();
+            }
+        }
+        let status = self.probe_provider().await;
+        *last = Some((Instant::now(), status.clone()));
+        status
+    }
+
+    /// Sends a request to Alpha Vantage.
+    async fn probe_provider(&self) -> ProviderStatus {
+        let start = Instant::now();
+        match self.client.get(PROVIDER_URL).send().await {
+            Ok(_) => ProviderStatus {
+                reachable: true,
+                latency_ms: Some(start.elapsed().as_millis()),
+                error: None,
+            },
+            Err(err) => ProviderStatus {
+                reachable: false,
+                latency_ms: None,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+}
+
+/// Builds the outcome of a check.
+fn check(name: &str, healthy: bool, detail: String) -> HealthCheck {
+    HealthCheck {
+        name: name.into(),
+        healthy,
+        detail,
+    }
+}
+
+fn timed_out() -> String {
+    format!("Timed out after {} seconds.", CHECK_TIMEOUT.as_secs())
+}
+
+/// Checks that the database pool can run a query.
+async fn check_database(pool: &sqlx::PgPool) -> HealthCheck {
+    match tokio::time::timeout(CHECK_TIMEOUT, sqlx::query("SELECT 1;").execute(pool)).await {
+        Ok(Ok(_)) => check("database", true, "".into()),
+        Ok(Err(err)) => check("database", false, err.to_string()),
+        Err(_) => check("database", false, timed_out()),
+    }
+}
+
+/// Checks that every table of `schema.sql` exists.
+async fn check_schema(pool: &sqlx::PgPool) -> HealthCheck {
+    let query = r#"
+    SELECT table_name
+    FROM UNNEST($1::TEXT[]) as table_name
+    WHERE to_regclass(table_name) IS NULL;"#;
+
+    let missing = sqlx::query_scalar::<_, String>(query)
+        .bind(&REQUIRED_TABLES[..])
+        .fetch_all(pool);
+    match tokio::time::timeout(CHECK_TIMEOUT, missing).await {
+        Ok(Ok(missing)) if missing.is_empty() => check("schema", true, "".into()),
+        Ok(Ok(missing)) => check(
+            "schema",
+            false,
+            format!("Tables {:?} do not exist.", missing),
+        ),
+        Ok(Err(err)) => check("schema", false, err.to_string()),
+        Err(_) => check("schema", false, timed_out()),
+    }
+}
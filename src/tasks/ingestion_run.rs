@@ -0,0 +1,59 @@
+use error_stack::{IntoReport, Result, ResultExt};
+
+use crate::{error::DatabaseUpsertError, model::IngestionRun};
+
+/// Source of the runs of the recurring task.
+pub const ALPHA_VANTAGE_SOURCE: &str = "alpha_vantage";
+
+/// Source of the runs of the `import` command.
+pub const IMPORT_SOURCE: &str = "import";
+
+/// Records a run of an ingestion on the `ingestion_runs` table, used to check the freshness of the data.
+///
+/// `outcome` is the number of changed rows, or the error of a failed run.
+pub async fn record_ingestion_run(
+    pool: &sqlx::PgPool,
+    source: &str,
+    started_at: time::OffsetDateTime,
+    outcome: std::result::Result<usize, String>,
+) -> Result<(), DatabaseUpsertError> {
+    let query = r#"
+    INSERT INTO ingestion_runs (source, started_at, rows_changed, error)
+    VALUES ($1, $2, $3, $4);"#;
+
+    let (rows_changed, error) = match outcome {
+        Ok(rows_changed) => (rows_changed as i32, None),
+        Err(error) => (0, Some(error)),
+    };
+    log::trace!("Recording ingestion run of `{}`.", source);
+    sqlx::query(query)
+        .bind(source)
+        .bind(started_at)
+        .bind(rows_changed)
+        .bind(error)
+        .execute(pool)
+        .await
+        .into_report()
+        .change_context(DatabaseUpsertError)
+        .attach("Failed to record ingestion run on Postgres database.")?;
+    Ok(())
+}
+
+/// Gets the latest successful ingestion run, from any source.
+pub async fn last_successful_ingestion(
+    pool: &sqlx::PgPool,
+) -> Result<Option<IngestionRun>, DatabaseUpsertError> {
+    let query = r#"
+    SELECT source, started_at, finished_at, rows_changed, error
+    FROM ingestion_runs
+    WHERE error IS NULL
+    ORDER BY finished_at DESC
+    LIMIT 1;"#;
+
+    sqlx::query_as::<_, IngestionRun>(query)
+        .fetch_optional(pool)
+        .await
+        .into_report()
+        .change_context(DatabaseUpsertError)
+        .attach("Failed to query ingestion runs on Postgres database.")
+}
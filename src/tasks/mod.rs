@@ -1,11 +1,68 @@
+mod alert_evaluation;
+pub use alert_evaluation::*;
+
+mod api_keys;
+pub use api_keys::*;
+
+mod arrow_export;
+pub use arrow_export::*;
+
+mod cache_invalidation;
+pub use cache_invalidation::*;
+
+mod data_freshness;
+pub use data_freshness::*;
+
+mod database_backup;
+pub use database_backup::*;
+
 mod database_connect;
 pub use database_connect::*;
 
+mod database_delete;
+pub use database_delete::*;
+
 mod database_initialization;
 pub use database_initialization::*;
 
+mod database_listen;
+pub use database_listen::*;
+
+mod database_restore;
+pub use database_restore::*;
+
 mod database_upsert;
 pub use database_upsert::*;
 
+mod file_import;
+pub use file_import::*;
+
+mod financial_data_aggregates;
+pub use financial_data_aggregates::*;
+
+mod financial_data_audit;
+use financial_data_audit::*;
+
+mod financial_data_partitions;
+use financial_data_partitions::*;
+
+mod financial_data_retention;
+pub use financial_data_retention::*;
+
+mod financial_data_revisions;
+use financial_data_revisions::*;
+
+mod grpc_execution;
+pub use grpc_execution::*;
+
+mod health_check;
+pub use health_check::*;
+
+mod ingestion_run;
+pub use ingestion_run::*;
+
 mod server_execution;
 pub use server_execution::*;
+
+mod shutdown_signal;
+pub use shutdown_signal::*;
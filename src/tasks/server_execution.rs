@@ -1,42 +1,236 @@
-use axum::{routing::get, Router};
+use std::net::SocketAddr;
+
+use axum::{
+    middleware,
+    routing::{get, post, put},
+    Extension, Router,
+};
 use error_stack::{IntoReport, Result, ResultExt};
-use tokio::sync::oneshot::Sender;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
+
+use crate::{
+    auth,
+    cache::ResponseCache,
+    config::Config,
+    error::ServerStartupError,
+    graphql, metrics,
+    model::{ApiVersion, FinancialDataUpdate, Scope},
+    openapi::ApiDoc,
+    rate_limit::{self, RateLimiter},
+    routes, telemetry,
+    versioning::{self, Deprecation},
+};
+
+use super::{invalidate_cache_on_updates, track_latest_entries, HealthState};
+
+/// Creates the router of every endpoint, along with the layers and the state they share.
+pub fn app_router(
+    config: &Config,
+    database_pool: sqlx::PgPool,
+    cache: ResponseCache,
+    health: HealthState,
+    shutdown: CancellationToken,
+    updates: broadcast::Sender<FinancialDataUpdate>,
+) -> Router {
+    log::trace!("Creating routers.");
+    // Layers added first are the inner ones, so that the rate limits know the API key,
+    // while the requests rejected by the authentication are limited per IP outside of it.
+    let limiter = config
+        .rate_limit
+        .enabled
+        .then(|| RateLimiter::new(config.rate_limit.clone(), database_pool.clone()));
+    let auth_enabled = config.auth.enabled;
+    let restrict = |router, scope| {
+        let router = rate_limit::with_rate_limit(router, limiter.clone());
+        let router = auth::with_scope(router, scope, auth_enabled);
+        rate_limit::with_failed_authentication_limit(
+            router,
+            limiter.clone().filter(|_| auth_enabled),
+        )
+    };
+
+    let v1_router = Router::new()
+        .merge(restrict(
+            Router::new()
+                .route("/financial_data", get(routes::financial_data))
+                .route("/export", get(routes::export))
+                .route("/stream", get(routes::stream))
+                .route("/symbols/:symbol/gaps", get(routes::gaps)),
+            Scope::ReadData,
+        ))
+        .merge(restrict(
+            Router::new().route("/statistics", get(routes::statistics)),
+            Scope::ReadAnalytics,
+        ))
+        .merge(restrict(
+            Router::new()
+                .route(
+                    "/alerts",
+                    post(routes::create_alert).get(routes::list_alerts),
+                )
+                .route(
+                    "/alerts/:id",
+                    get(routes::get_alert)
+                        .put(routes::update_alert)
+                        .delete(routes::delete_alert),
+                )
+                .route("/alerts/:id/events", get(routes::alert_events)),
+            Scope::AdminAlerts,
+        ))
+        .merge(restrict(
+            Router::new()
+                .route("/financial_data", post(routes::post_financial_data))
+                .route(
+                    "/financial_data/:symbol/:date",
+                    put(routes::put_financial_data).delete(routes::delete_financial_data),
+                ),
+            Scope::AdminIngest,
+        ))
+        .route("/status", get(routes::status));
+    let v2_router = Router::new()
+        .merge(restrict(
+            Router::new()
+                .route("/financial_data", get(routes::v2::financial_data))
+                .route("/export", get(routes::v2::export))
+                .route("/stream", get(routes::v2::stream))
+                .route("/symbols/:symbol/gaps", get(routes::v2::gaps)),
+            Scope::ReadData,
+        ))
+        .merge(restrict(
+            Router::new().route("/statistics", get(routes::v2::statistics)),
+            Scope::ReadAnalytics,
+        ))
+        .merge(restrict(
+            Router::new()
+                .route(
+                    "/alerts",
+                    post(routes::v2::create_alert).get(routes::v2::list_alerts),
+                )
+                .route(
+                    "/alerts/:id",
+                    get(routes::v2::get_alert)
+                        .put(routes::v2::update_alert)
+                        .delete(routes::v2::delete_alert),
+                )
+                .route("/alerts/:id/events", get(routes::v2::alert_events)),
+            Scope::AdminAlerts,
+        ))
+        .merge(restrict(
+            Router::new()
+                .route("/financial_data", post(routes::v2::post_financial_data))
+                .route(
+                    "/financial_data/:symbol/:date",
+                    put(routes::v2::put_financial_data).delete(routes::v2::delete_financial_data),
+                ),
+            Scope::AdminIngest,
+        ))
+        .route("/status", get(routes::v2::status));
+
+    // Only nested routers know the full matched route, so each router tracks its own routes.
+    // The version is added outside of the authentication and rate limits, which answer in its shape.
+    let v1_router = versioning::with_deprecation(
+        v1_router
+            .route_layer(middleware::from_fn(metrics::track_http_metrics))
+            .layer(Extension(ApiVersion::V1)),
+        Deprecation::v1(config.api.v1_sunset),
+    );
+    let v2_router = v2_router
+        .route_layer(middleware::from_fn(metrics::track_http_metrics))
+        .layer(Extension(ApiVersion::V2));
 
-use crate::{error::ServerStartupError, routes};
+    // Unversioned, the schema evolves by deprecating fields. Errors outside of the schema are shaped like `v2`.
+    let graphql_router = match config.graphql.enabled {
+        true => restrict(
+            Router::new().route("/graphql", post(routes::graphql)),
+            Scope::ReadData,
+        )
+        .route("/graphql/playground", get(routes::graphql_playground))
+        .route_layer(middleware::from_fn(metrics::track_http_metrics))
+        .layer(Extension(ApiVersion::V2))
+        .layer(Extension(graphql::build_schema(
+            &config.graphql,
+            database_pool.clone(),
+        ))),
+        false => Router::new(),
+    };
+
+    Router::new()
+        .route("/healthz", get(routes::healthz))
+        .route("/readyz", get(routes::readyz))
+        .route("/metrics", get(routes::metrics))
+        .route_layer(middleware::from_fn(metrics::track_http_metrics))
+        .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi()))
+        .nest("/api/v1", v1_router.clone())
+        .nest("/api/v2", v2_router)
+        // Unversioned alias of `v1`, kept for the clients predating the versions.
+        .nest("/api", v1_router)
+        .merge(graphql_router)
+        .layer(Extension(database_pool.clone()))
+        .layer(Extension(health))
+        .layer(Extension(config.calendar.clone()))
+        .layer(Extension(cache))
+        .layer(Extension(updates))
+        .layer(Extension(shutdown))
+        .layer(axum_sqlx_tx::Layer::new(database_pool))
+        .layer(middleware::from_fn(telemetry::propagate_request_id))
+}
 
 /// Initializes and runs Axum server.
-/// Runs until a SIGTERM (or CTRL+C) is received.
+/// Runs until `shutdown` is cancelled, e.g. when a SIGTERM (or CTRL+C) is received,
+/// then stops accepting connections and waits for open ones up to a deadline.
 pub async fn server_startup(
+    config: Config,
     database_pool: sqlx::PgPool,
-    upsert_channel: Sender<()>,
+    shutdown: CancellationToken,
+    updates: broadcast::Sender<FinancialDataUpdate>,
 ) -> Result<(), ServerStartupError> {
-    log::trace!("Creating routers.");
-    let api_router = Router::new()
-        .route("/financial_data", get(routes::financial_data))
-        .route("/statistics", get(routes::statistics));
-
-    let app = Router::new()
-        .nest("/api", api_router)
-        .layer(axum_sqlx_tx::Layer::new(database_pool));
+    let cache = ResponseCache::new(&config.cache, !config.auth.enabled);
+    tokio::spawn(invalidate_cache_on_updates(
+        cache.clone(),
+        updates.subscribe(),
+        shutdown.clone(),
+    ));
+    let health = HealthState::new(&config);
+    tokio::spawn(track_latest_entries(
+        database_pool.clone(),
+        health.latest_entries.clone(),
+        updates.subscribe(),
+        shutdown.clone(),
+    ));
+    let app = app_router(
+        &config,
+        database_pool,
+        cache,
+        health,
+        shutdown.clone(),
+        updates,
+    );
 
-    log::trace!("Binding server to port 8000.");
-    let bind_url = "0.0.0.0:8000"
-        .parse()
+    log::trace!("Binding server to `{}`.", config.server.bind_address);
+    let server = axum::Server::try_bind(&config.server.bind_address)
         .into_report()
         .change_context(ServerStartupError)
-        .attach("Failed to parse URL to bind to.")?;
-    axum::Server::bind(&bind_url)
-        .serve(app.into_make_service())
-        .await
-        .into_report()
-        .change_context(ServerStartupError)
-        .attach("Failed to serve Axum server.")?;
+        .attach_printable_lazy(|| {
+            format!("Failed to bind server to `{}`.", config.server.bind_address)
+        })?
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(shutdown.clone().cancelled_owned());
 
-    log::trace!("Sending signal to upsert task.");
-    upsert_channel
-        .send(())
-        .map_err(|_| ServerStartupError)
-        .into_report()
-        .change_context(ServerStartupError)
-        .attach("Failed to send signal to Upsert task.")
+    let deadline = async {
+        shutdown.cancelled().await;
+        tokio::time::sleep(config.shutdown_deadline()).await;
+    };
+    tokio::select! {
+        served = server => served
+            .into_report()
+            .change_context(ServerStartupError)
+            .attach("Failed to serve Axum server."),
+        _ = deadline => {
+            log::warn!("Open connections were not drained before the deadline, closing them.");
+            Ok(())
+        }
+    }
 }
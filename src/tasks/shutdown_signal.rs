@@ -0,0 +1,35 @@
+use tokio_util::sync::CancellationToken;
+
+/// Waits for a SIGTERM (or CTRL+C) and cancels `shutdown`, signaling every task to stop.
+///
+/// Returns early if `shutdown` is cancelled by another task.
+pub async fn wait_for_shutdown_signal(shutdown: CancellationToken) {
+    let ctrl_c = async {
+        if let Err(err) = tokio::signal::ctrl_c().await {
+            log::error!("Failed to listen for CTRL+C: {}", err);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(err) => {
+                log::error!("Failed to listen for SIGTERM: {}", err);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => log::info!("Received CTRL+C, shutting down."),
+        _ = terminate => log::info!("Received SIGTERM, shutting down."),
+        _ = shutdown.cancelled() => return,
+    }
+    shutdown.cancel();
+}
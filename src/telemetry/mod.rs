@@ -0,0 +1,4 @@
+mod request_id;
+pub use request_id::*;
+mod subscriber;
+pub use subscriber::*;
@@ -0,0 +1,69 @@
+use axum::{
+    http::{HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use rand::Rng;
+use tracing::Instrument;
+
+/// Header holding the ID of a request, taken from the client or generated, and echoed in the response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Maximum length of a request ID taken from the client.
+const MAX_REQUEST_ID_LENGTH: usize = 128;
+
+tokio::task_local! {
+    /// ID of the request being handled by the current task.
+    static REQUEST_ID: String;
+}
+
+/// Gets the ID of the request being handled by the current task, if any.
+///
+/// Tasks spawned by a handler don't inherit it.
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Checks that a request ID taken from the client is short and printable, so that it's safe to log and echo.
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+/// Generates a random 128 bits request ID, as hexadecimal.
+fn generate_request_id() -> String {
+    format!("{:032x}", rand::thread_rng().gen::<u128>())
+}
+
+/// Middleware giving an ID to each request, taken from the `X-Request-Id` header or generated.
+///
+/// The request is handled inside a `request` span holding the ID, so that every log line and trace is
+/// correlated to it, and the ID is echoed in the `X-Request-Id` header of the response.
+pub async fn propagate_request_id<B>(request: Request<B>, next: Next<B>) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(String::from)
+        .unwrap_or_else(generate_request_id);
+
+    let span = tracing::info_span!(
+        "request",
+        request_id = %id,
+        method = %request.method(),
+        path = %request.uri().path(),
+    );
+    let mut response = REQUEST_ID
+        .scope(id.clone(), next.run(request))
+        .instrument(span)
+        .await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
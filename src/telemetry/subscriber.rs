@@ -0,0 +1,104 @@
+use error_stack::{IntoReport, Result, ResultExt};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer,
+};
+
+use crate::{
+    config::{LogFormat, LoggingConfig},
+    error::TelemetryError,
+};
+
+/// Keeps the trace exporter running, flushing the remaining traces when shut down.
+#[derive(Debug, Default)]
+pub struct TelemetryGuard {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl TelemetryGuard {
+    /// Flushes and stops the trace exporter, if any.
+    pub fn shutdown(self) {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.provider {
+            if let Err(err) = provider.shutdown() {
+                log::error!("Failed to flush traces: {}", err);
+            }
+        }
+    }
+}
+
+/// Sets up the logs, filtered by the `RUST_LOG` environment variable, and the optional trace exporter.
+///
+/// Lines logged with the `log` crate are forwarded, with the fields of the spans they are logged in.
+/// Must be called from inside the Tokio runtime when exporting traces.
+pub fn init_telemetry(config: &LoggingConfig) -> Result<TelemetryGuard, TelemetryError> {
+    let fmt_layer = match config.format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .flatten_event(true)
+            .boxed(),
+    };
+    let fmt_layer = fmt_layer.with_filter(EnvFilter::from_default_env());
+
+    #[cfg(feature = "otlp")]
+    let (otlp_layer, guard) = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let (layer, provider) = otlp_layer(endpoint)?;
+            (
+                Some(layer.with_filter(LevelFilter::INFO)),
+                TelemetryGuard {
+                    provider: Some(provider),
+                },
+            )
+        }
+        None => (None, TelemetryGuard::default()),
+    };
+    #[cfg(not(feature = "otlp"))]
+    let (otlp_layer, guard) = (None::<LevelFilter>, TelemetryGuard::default());
+
+    tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otlp_layer)
+        .try_init()
+        .into_report()
+        .change_context(TelemetryError)
+        .attach("Failed to install the tracing subscriber.")?;
+    Ok(guard)
+}
+
+/// Creates the layer exporting spans to an OpenTelemetry collector over OTLP/gRPC.
+#[cfg(feature = "otlp")]
+fn otlp_layer<S>(
+    endpoint: &str,
+) -> Result<
+    (
+        tracing_opentelemetry::OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>,
+        opentelemetry_sdk::trace::SdkTracerProvider,
+    ),
+    TelemetryError,
+>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_otlp::WithExportConfig;
+
+    let exporter = opentelemetry_otlp::SpanExporter::builder()
+        .with_tonic()
+        .with_endpoint(endpoint)
+        .build()
+        .into_report()
+        .change_context(TelemetryError)
+        .attach_printable_lazy(|| format!("Failed to create OTLP exporter to `{}`.", endpoint))?;
+    let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(
+            opentelemetry_sdk::Resource::builder()
+                .with_service_name(env!("CARGO_PKG_NAME"))
+                .build(),
+        )
+        .build();
+    let tracer = provider.tracer(env!("CARGO_PKG_NAME"));
+    Ok((tracing_opentelemetry::layer().with_tracer(tracer), provider))
+}
@@ -0,0 +1,69 @@
+use axum::{
+    extract::State,
+    http::{header, HeaderValue, Request},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use time::{format_description::FormatItem, macros::format_description, Date, OffsetDateTime};
+
+/// Day `v1` was deprecated in favour of `v2`, as a Unix timestamp.
+pub const V1_DEPRECATED_AT: i64 = 1_792_368_000;
+
+/// `IMF-fixdate` format of the `Sunset` header.
+const HTTP_DATE: &[FormatItem<'static>] = format_description!(
+    "[weekday repr:short], [day] [month repr:short] [year] [hour]:[minute]:[second] GMT"
+);
+
+/// Values of the headers announcing the deprecation of a version of the API.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    deprecation: HeaderValue,
+    sunset: HeaderValue,
+    successor: &'static str,
+}
+
+impl Deprecation {
+    /// Deprecation of `v1`, which stops being served at the start of `sunset`.
+    pub fn v1(sunset: Date) -> Self {
+        let sunset = OffsetDateTime::new_utc(sunset, time::Time::MIDNIGHT)
+            .format(HTTP_DATE)
+            .expect("HTTP dates only have formattable components.");
+        Deprecation {
+            deprecation: HeaderValue::from_str(&format!("@{}", V1_DEPRECATED_AT))
+                .expect("Timestamps are valid header values."),
+            sunset: HeaderValue::from_str(&sunset).expect("HTTP dates are valid header values."),
+            successor: "/api/v2",
+        }
+    }
+}
+
+/// Middleware adding the `Deprecation` and `Sunset` headers to every response,
+/// along with a `Link` to the same route of the successor version.
+///
+/// Must be added to a nested router, which only sees the path after its prefix.
+pub async fn deprecate<B>(
+    State(deprecation): State<Deprecation>,
+    request: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let link = format!(
+        "<{}{}>; rel=\"successor-version\"",
+        deprecation.successor,
+        request.uri().path()
+    );
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    headers.insert("deprecation", deprecation.deprecation);
+    headers.insert("sunset", deprecation.sunset);
+    if let Ok(link) = HeaderValue::from_str(&link) {
+        headers.insert(header::LINK, link);
+    }
+    response
+}
+
+/// Marks every route of `router` as deprecated.
+pub fn with_deprecation(router: Router, deprecation: Deprecation) -> Router {
+    router.layer(middleware::from_fn_with_state(deprecation, deprecate))
+}
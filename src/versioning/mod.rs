@@ -0,0 +1,2 @@
+mod deprecation;
+pub use deprecation::*;
@@ -0,0 +1,241 @@
+//! Restores archives of `export_database` into throwaway databases of the server at `DATABASE_URL`.
+//!
+//! Run with `cargo test --test database_restore -- --ignored`.
+
+use std::{io::Cursor, path::Path, time::Duration};
+
+use rust_stack_example::tasks::{self, RestoredTable};
+
+const SCHEMA_PATH: &str = "schema.sql";
+
+/// Tables holding a row written by `seed`.
+const SEEDED_TABLES: [&str; 6] = [
+    "api_keys",
+    "financial_data",
+    "financial_data_audit",
+    "alert_rules",
+    "alert_events",
+    "alert_deliveries",
+];
+
+/// Database created for a test, dropped by `drop`, or when created again by the next run.
+struct TestDatabase {
+    admin: sqlx::PgPool,
+    name: String,
+    pool: sqlx::PgPool,
+}
+
+impl TestDatabase {
+    async fn create(name: &str) -> Self {
+        let url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set.");
+        let admin = sqlx::PgPool::connect(&url).await.unwrap();
+        let name = format!("restore_test_{}", name);
+        sqlx::query(&format!("DROP DATABASE IF EXISTS {};", name))
+            .execute(&admin)
+            .await
+            .unwrap();
+        sqlx::query(&format!("CREATE DATABASE {};", name))
+            .execute(&admin)
+            .await
+            .unwrap();
+        let (base, _) = url.rsplit_once('/').unwrap();
+        let pool = sqlx::PgPool::connect(&format!("{}/{}", base, name))
+            .await
+            .unwrap();
+        tasks::create_table_if_not_exists(pool.clone(), Path::new(SCHEMA_PATH))
+            .await
+            .unwrap();
+        TestDatabase { admin, name, pool }
+    }
+
+    async fn execute(&self, query: &str) {
+        sqlx::query(query).execute(&self.pool).await.unwrap();
+    }
+
+    async fn drop(self) {
+        self.pool.close().await;
+        sqlx::query(&format!("DROP DATABASE {};", self.name))
+            .execute(&self.admin)
+            .await
+            .unwrap();
+    }
+}
+
+/// Adds an API key, an alert rule with an event and a delivery, and an audited entry.
+async fn seed(database: &TestDatabase, key: &str, symbol: &str) {
+    database
+        .execute(&format!(
+            "INSERT INTO api_keys (name, prefix, key_hash, scopes) VALUES ('{0}', '{0}', '{0}', '{{read:data}}');",
+            key
+        ))
+        .await;
+    database
+        .execute(&format!(
+            "INSERT INTO alert_rules (symbol, kind, threshold, webhook_url) VALUES ('{}', 'price_cross', 100, 'http://127.0.0.1:1');",
+            symbol
+        ))
+        .await;
+    database
+        .execute(&format!(
+            "INSERT INTO alert_events (rule_id, symbol, date, value) SELECT MAX(id), '{}', '2023-03-01', 101 FROM alert_rules;",
+            symbol
+        ))
+        .await;
+    database
+        .execute("INSERT INTO alert_deliveries (event_id, attempt, status_code) SELECT MAX(id), 1, 200 FROM alert_events;")
+        .await;
+    database
+        .execute("SELECT create_financial_data_partitions(2023, 2023);")
+        .await;
+    database
+        .execute(&format!(
+            "INSERT INTO financial_data (symbol, date, open_price, close_price, volume) VALUES ('{}', '2023-03-01', 99, 101, 1000);",
+            symbol
+        ))
+        .await;
+    database
+        .execute(&format!(
+            "INSERT INTO financial_data_audit (action, symbol, date, new_open_price, new_close_price, new_volume, author, api_key_id) SELECT 'insert', '{}', '2023-03-01', 99, 101, 1000, '{}', MAX(id) FROM api_keys;",
+            symbol, key
+        ))
+        .await;
+}
+
+async fn archive_of(database: &TestDatabase) -> Vec<u8> {
+    let schema = std::fs::read_to_string(SCHEMA_PATH).unwrap();
+    let mut archive = Cursor::new(vec![]);
+    tasks::export_database(&database.pool, &schema, &mut archive)
+        .await
+        .unwrap();
+    archive.into_inner()
+}
+
+async fn restore(database: &TestDatabase, archive: &[u8]) -> Vec<RestoredTable> {
+    let schema = std::fs::read_to_string(SCHEMA_PATH).unwrap();
+    tasks::import_database(&database.pool, &schema, Cursor::new(archive))
+        .await
+        .unwrap()
+}
+
+fn table<'a>(restored: &'a [RestoredTable], name: &str) -> &'a RestoredTable {
+    restored.iter().find(|table| table.name == name).unwrap()
+}
+
+/// Symbols of the alert events, along with the symbol of their rule.
+async fn event_rules(database: &TestDatabase) -> Vec<(String, String)> {
+    sqlx::query_as(
+        "SELECT TRIM(e.symbol), TRIM(r.symbol) FROM alert_events e JOIN alert_rules r ON r.id = e.rule_id ORDER BY e.id;",
+    )
+    .fetch_all(&database.pool)
+    .await
+    .unwrap()
+}
+
+/// Keys of the audited changes, along with the symbol of the change.
+async fn audit_keys(database: &TestDatabase) -> Vec<(String, Option<String>)> {
+    sqlx::query_as(
+        "SELECT TRIM(a.symbol), k.name FROM financial_data_audit a LEFT JOIN api_keys k ON k.id = a.api_key_id ORDER BY a.id;",
+    )
+    .fetch_all(&database.pool)
+    .await
+    .unwrap()
+}
+
+/// Symbols of the entries with a current revision.
+async fn current_revisions(database: &TestDatabase) -> Vec<String> {
+    sqlx::query_scalar(
+        "SELECT TRIM(symbol) FROM financial_data_revisions WHERE superseded_at IS NULL ORDER BY symbol;",
+    )
+    .fetch_all(&database.pool)
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres server at DATABASE_URL"]
+async fn restoring_twice_changes_nothing() {
+    let source = TestDatabase::create("twice_source").await;
+    seed(&source, "source", "AAA").await;
+    let archive = archive_of(&source).await;
+    let target = TestDatabase::create("twice_target").await;
+
+    let first = restore(&target, &archive).await;
+    assert!(first.iter().all(|table| !table.skipped));
+    for name in SEEDED_TABLES {
+        assert_eq!(
+            table(&first, name).inserted,
+            1,
+            "`{}` was not restored.",
+            name
+        );
+    }
+    assert_eq!(event_rules(&target).await, event_rules(&source).await);
+    assert_eq!(audit_keys(&target).await, audit_keys(&source).await);
+    assert_eq!(current_revisions(&target).await, ["AAA"]);
+
+    let second = restore(&target, &archive).await;
+    assert!(second.iter().all(|table| table.inserted == 0));
+    assert!(table(&second, "alert_rules").skipped);
+    assert!(table(&second, "alert_deliveries").skipped);
+    assert!(!table(&second, "financial_data").skipped);
+    assert_eq!(event_rules(&target).await, event_rules(&source).await);
+    assert_eq!(audit_keys(&target).await, audit_keys(&source).await);
+
+    // Sequences continue after the restored ids.
+    seed(&target, "target", "BBB").await;
+    assert_eq!(event_rules(&target).await.len(), 2);
+
+    source.drop().await;
+    target.drop().await;
+}
+
+#[tokio::test]
+#[ignore = "needs a Postgres server at DATABASE_URL"]
+async fn restoring_into_a_non_empty_database_keeps_references() {
+    let source = TestDatabase::create("non_empty_source").await;
+    seed(&source, "source", "AAA").await;
+    let archive = archive_of(&source).await;
+    let target = TestDatabase::create("non_empty_target").await;
+    // Rows with the ids of the archived rows, but other values.
+    seed(&target, "target", "BBB").await;
+    target.execute("DELETE FROM alert_events;").await;
+    target.execute("DELETE FROM financial_data_audit;").await;
+
+    target
+        .execute("INSERT INTO financial_data_revisions (symbol, date, open_price, close_price, volume) SELECT symbol, date, open_price, close_price, volume FROM financial_data;")
+        .await;
+    let mut listener = sqlx::postgres::PgListener::connect_with(&target.pool)
+        .await
+        .unwrap();
+    listener
+        .listen(tasks::FINANCIAL_DATA_UPDATES_CHANNEL)
+        .await
+        .unwrap();
+
+    let restored = restore(&target, &archive).await;
+    for name in SEEDED_TABLES
+        .iter()
+        .filter(|name| **name != "financial_data")
+    {
+        let table = table(&restored, name);
+        assert!(table.skipped, "`{}` was restored.", name);
+        assert_eq!(table.inserted, 0);
+    }
+    assert_eq!(table(&restored, "financial_data").inserted, 1);
+    assert!(event_rules(&target).await.is_empty());
+    assert!(audit_keys(&target).await.is_empty());
+    // Restored entries are found by `as_of` and notified, even though the revisions were skipped.
+    assert!(table(&restored, "financial_data_revisions").skipped);
+    assert_eq!(current_revisions(&target).await, ["AAA", "BBB"]);
+    let notification = tokio::time::timeout(Duration::from_secs(5), listener.recv())
+        .await
+        .expect("Restored entries must be notified.")
+        .unwrap();
+    let update: serde_json::Value = serde_json::from_str(notification.payload()).unwrap();
+    assert_eq!(update["symbol"], "AAA");
+    assert_eq!(update["date"], "2023-03-01");
+    drop(listener);
+
+    source.drop().await;
+    target.drop().await;
+}
@@ -0,0 +1,221 @@
+//! Checks that the OpenAPI document stays in sync with the router and the types of `model`.
+
+use std::time::Duration;
+
+use axum::{
+    body::Body,
+    http::{Method, Request, StatusCode},
+    Router,
+};
+use rust_stack_example::{
+    cache::ResponseCache,
+    config::{Config, ConfigArgs},
+    openapi::ApiDoc,
+    tasks,
+};
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+use utoipa::{openapi::PathItemType, OpenApi};
+
+/// Configuration of the server, with a database that refuses connections so that no handler blocks.
+fn config() -> Config {
+    std::env::set_var("DATABASE_URL", "postgres://postgres@127.0.0.1:1/postgres");
+    std::env::set_var("AUTH_ENABLED", "true");
+    Config::load(&ConfigArgs::default()).expect("Test configuration must be valid.")
+}
+
+fn router() -> Router {
+    router_with(config())
+}
+
+fn router_with(config: Config) -> Router {
+    let pool = sqlx::postgres::PgPoolOptions::new()
+        .acquire_timeout(Duration::from_millis(100))
+        .connect_lazy(&config.database.url)
+        .expect("Database URL must be valid.");
+    let (updates, _) = tokio::sync::broadcast::channel(1);
+    let cache = ResponseCache::new(&config.cache, false);
+    let health = tasks::HealthState::new(&config);
+    tasks::app_router(
+        &config,
+        pool,
+        cache,
+        health,
+        CancellationToken::new(),
+        updates,
+    )
+}
+
+fn method(item_type: &PathItemType) -> Method {
+    match item_type {
+        PathItemType::Get => Method::GET,
+        PathItemType::Post => Method::POST,
+        PathItemType::Put => Method::PUT,
+        PathItemType::Delete => Method::DELETE,
+        PathItemType::Options => Method::OPTIONS,
+        PathItemType::Head => Method::HEAD,
+        PathItemType::Patch => Method::PATCH,
+        PathItemType::Trace => Method::TRACE,
+        PathItemType::Connect => Method::CONNECT,
+    }
+}
+
+#[tokio::test]
+async fn documented_operations_are_routed() {
+    let router = router();
+    for (path, item) in ApiDoc::openapi().paths.paths {
+        let uri = path.replace("{id}", "1");
+        for item_type in item.operations.keys() {
+            let method = method(item_type);
+            let request = Request::builder()
+                .method(method.clone())
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap();
+            let status = router.clone().oneshot(request).await.unwrap().status();
+            assert!(
+                status != StatusCode::NOT_FOUND && status != StatusCode::METHOD_NOT_ALLOWED,
+                "`{} {}` is documented but not routed, answered `{}`.",
+                method,
+                path,
+                status
+            );
+        }
+    }
+}
+
+/// Source of the router, from which the routes are read, since an Axum router can't list them.
+const ROUTER_SOURCE: &str = include_str!("../src/tasks/server_execution.rs");
+
+/// Prefixes under which the routers are mounted. `/api` is the unversioned alias of `/api/v1`, documented once.
+const ROUTE_PREFIXES: [&str; 3] = ["", "/api/v1", "/api/v2"];
+
+/// Routes that are not part of the REST API, and so are not documented.
+const UNDOCUMENTED_ROUTES: [&str; 2] = ["/graphql", "/graphql/playground"];
+
+const PROBED_METHODS: [Method; 5] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::DELETE,
+    Method::PATCH,
+];
+
+/// Every path passed to `Router::route` in `ROUTER_SOURCE`.
+fn routed_paths() -> Vec<String> {
+    ROUTER_SOURCE
+        .split(".route(")
+        .skip(1)
+        .filter_map(|call| call.trim_start().strip_prefix('"'))
+        .filter_map(|call| call.split('"').next())
+        .map(str::to_string)
+        .collect()
+}
+
+#[tokio::test]
+async fn routed_operations_are_documented() {
+    // Authentication answers before the method is checked, and would limit the rejected requests of the probes.
+    let mut config = config();
+    config.auth.enabled = false;
+    config.rate_limit.enabled = false;
+    let router = router_with(config);
+    let documented = ApiDoc::openapi().paths.paths;
+    let paths = routed_paths();
+    assert!(paths.contains(&"/financial_data/:symbol/:date".to_string()));
+    for prefix in ROUTE_PREFIXES {
+        for path in &paths {
+            let path = format!("{}{}", prefix, path);
+            if UNDOCUMENTED_ROUTES.contains(&path.as_str()) {
+                continue;
+            }
+            let segments: Vec<&str> = path.split('/').collect();
+            let uri = segments
+                .iter()
+                .map(|segment| match segment.starts_with(':') {
+                    true => "1",
+                    false => segment,
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            let documented_path = segments
+                .iter()
+                .map(|segment| match segment.strip_prefix(':') {
+                    Some(name) => format!("{{{}}}", name),
+                    None => segment.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+
+            for method in PROBED_METHODS {
+                let request = Request::builder()
+                    .method(method.clone())
+                    .uri(&uri)
+                    .body(Body::empty())
+                    .unwrap();
+                let status = router.clone().oneshot(request).await.unwrap().status();
+                if status == StatusCode::NOT_FOUND || status == StatusCode::METHOD_NOT_ALLOWED {
+                    continue;
+                }
+                let operation = documented.get(&documented_path).and_then(|item| {
+                    item.operations
+                        .keys()
+                        .find(|item_type| self::method(item_type) == method)
+                });
+                assert!(
+                    operation.is_some(),
+                    "`{} {}` is routed, answered `{}`, but not documented on `ApiDoc`.",
+                    method,
+                    documented_path,
+                    status
+                );
+            }
+        }
+    }
+}
+
+/// Collects every `$ref` of a JSON value.
+fn references(value: &serde_json::Value, found: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match (key.as_str(), value) {
+                    ("$ref", serde_json::Value::String(reference)) => found.push(reference.clone()),
+                    _ => references(value, found),
+                }
+            }
+        }
+        serde_json::Value::Array(values) => values.iter().for_each(|v| references(v, found)),
+        _ => {}
+    }
+}
+
+#[test]
+fn referenced_schemas_are_registered() {
+    let document = serde_json::to_value(ApiDoc::openapi()).unwrap();
+    let mut found = vec![];
+    references(&document, &mut found);
+    assert!(!found.is_empty());
+    for reference in found {
+        let name = reference
+            .strip_prefix("#/components/schemas/")
+            .unwrap_or_else(|| panic!("`{}` is not a schema reference.", reference));
+        assert!(
+            document["components"]["schemas"].get(name).is_some(),
+            "Schema `{}` is referenced but not registered on `ApiDoc`.",
+            name
+        );
+    }
+}
+
+#[tokio::test]
+async fn document_is_served() {
+    let request = Request::builder()
+        .uri("/api/openapi.json")
+        .body(Body::empty())
+        .unwrap();
+    let response = router().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    let served: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(served, serde_json::to_value(ApiDoc::openapi()).unwrap());
+}